@@ -0,0 +1,13 @@
+#version 130
+
+uniform vec4 u_color;
+
+// How far inside the edges across and along a stroke, 1 or more is fully covered
+in vec2 coverage;
+
+out vec4 fragColor;
+
+void main() {
+    float alpha = clamp(coverage.x, 0.0, 1.0) * clamp(coverage.y, 0.0, 1.0);
+    fragColor = vec4(u_color.rgb, u_color.a * alpha);
+}
@@ -0,0 +1,10 @@
+#version 130
+
+in vec4 vert;
+
+out vec2 coverage;
+
+void main() {
+    gl_Position = gl_ModelViewProjectionMatrix * vec4(vert.xy, 0.0, 1.0);
+    coverage = vert.zw;
+}
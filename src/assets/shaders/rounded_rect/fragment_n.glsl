@@ -10,6 +10,11 @@ uniform vec4 u_color_rb;
 uniform vec2 u_size;
 uniform float u_radius;
 
+uniform sampler2D u_texture;
+uniform int u_textured;
+// left, top, right, bottom of the texture region drawn
+uniform vec4 u_uv;
+
 in vec2 uvs;
 
 out vec4 fragColor;
@@ -27,6 +32,9 @@ void main() {
     vec4 bottom_color = mix(u_color_lb, u_color_rb, uvs.x);
 
     vec4 blend_color = mix(top_color, bottom_color, uvs.y);
+    if (u_textured == 1) {
+        blend_color *= texture(u_texture, mix(u_uv.xy, u_uv.zw, uvs));
+    }
 
     float avg_brightness = ((1.0 - blend_color.r) + (1.0 - blend_color.g) + (1.0 - blend_color.b)) / 3;
 //    blend_color += vec4(mix(avg_color / 255 + 0.0 / 255, -(avg_color / 255 + 0.0 / 255), fract(sin(dot(uvs.xy + vec2(u_random), vec2(12.9898, 78.233))) * 43758.5453)));
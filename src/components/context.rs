@@ -10,6 +10,7 @@ use crate::components::framework::Framework;
 use crate::components::framework::layout::LayoutEvent;
 use crate::components::framework::ui_traits::TickResult;
 use crate::components::render::font::manager::FontManager;
+use crate::components::render::image::ImageManager;
 use crate::components::render::renderer::Renderer;
 use crate::components::render::stack::State;
 use crate::components::spatial::vec2::Vec2;
@@ -42,7 +43,7 @@ pub unsafe fn context() -> &'static mut UIContext {
 ///
 /// Handles the [`Glfw`] context, events, window etc.
 ///
-/// Has a [`Window`], [`Renderer`], [`FontManager`], [`Framework`], and [`FramebufferManager`]
+/// Has a [`Window`], [`Renderer`], [`FontManager`], [`ImageManager`], [`Framework`], and [`FramebufferManager`]
 /// which provides almost all necessary functionality for a UI.
 pub struct UIContext {
     glfw: Glfw,
@@ -57,6 +58,7 @@ pub struct UIContext {
     window: Window,
     renderer: Renderer,
     font_manager: FontManager,
+    image_manager: ImageManager,
     framework: Framework,
     fb_manager: FramebufferManager,
     keyboard: Keyboard,
@@ -93,6 +95,7 @@ impl UIContext {
             window: Window::new(builder.width, builder.height),
             renderer: Renderer::new(),
             font_manager: FontManager::new(""),
+            image_manager: ImageManager::new(),
             framework: Framework::new(),
             fb_manager,
             keyboard: Keyboard::new(),
@@ -265,6 +268,9 @@ impl UIContext {
 
                             self.framework.event(Event::Keyboard(*key, *action, *mods));
                         }
+                        WindowEvent::Char(c) => {
+                            self.framework.event(Event::Char(*c));
+                        }
                         WindowEvent::Size(width, height) => {
                             self.fb_manager().resize(*width, *height);
                             self.framework.on_resize(*width as f32, *height as f32);
@@ -300,6 +306,7 @@ impl UIContext {
         &mut self.renderer
     }
     pub fn fonts(&mut self) -> &mut FontManager { &mut self.font_manager }
+    pub fn images(&mut self) -> &mut ImageManager { &mut self.image_manager }
     pub fn window(&mut self) -> &mut Window {
         &mut self.window
     }
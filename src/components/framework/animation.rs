@@ -1,14 +1,19 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
 use std::f64::consts::PI;
 use std::rc::Rc;
 use num_traits::Pow;
 use rand::random;
 
 use crate::components::context::context;
+use crate::components::framework::state::State;
+use crate::components::render::color::Color;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
 
 /// Different animation types will give different animation curves, and provide a cleaner visual than `linear`
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Easing {
     Linear,
     Log,
@@ -22,9 +27,54 @@ pub enum Easing {
     EaseOutElastic,
     EaseOutBounce,
     Sin,
+    /// A CSS `cubic-bezier(x1, y1, x2, y2)` timing function, where `x1` and `x2` must be from 0 to 1
+    CubicBezier(f32, f32, f32, f32),
 }
 
 impl Easing {
+    /// CSS `ease`
+    pub const EASE: Easing = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    /// CSS `ease-in`
+    pub const EASE_IN: Easing = Easing::CubicBezier(0.42, 0.0, 1.0, 1.0);
+    /// CSS `ease-out`
+    pub const EASE_OUT: Easing = Easing::CubicBezier(0.0, 0.0, 0.58, 1.0);
+    /// CSS `ease-in-out`
+    pub const EASE_IN_OUT: Easing = Easing::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+    /// Parses the name of a variant, such as `"CubicIn"`, `"Progressive(0.5)"` or `"CubicBezier(0.4, 0, 0.2, 1)"`,
+    /// or a CSS timing function such as `"ease-out"` or `"cubic-bezier(0.4, 0, 0.2, 1)"`
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(speed) = name.strip_prefix("Progressive(").and_then(|n| n.strip_suffix(')')) {
+            return speed.trim().parse().ok().map(Easing::Progressive);
+        }
+        let bezier = name.strip_prefix("CubicBezier(").or_else(|| name.strip_prefix("cubic-bezier("));
+        if let Some(args) = bezier.and_then(|n| n.strip_suffix(')')) {
+            let v: Vec<f32> = args.split(',').map(|a| a.trim().parse().ok()).collect::<Option<_>>()?;
+            return match v[..] {
+                [x1, y1, x2, y2] => Some(Easing::CubicBezier(x1, y1, x2, y2)),
+                _ => None,
+            };
+        }
+        Some(match name {
+            "ease" => Easing::EASE,
+            "ease-in" => Easing::EASE_IN,
+            "ease-out" => Easing::EASE_OUT,
+            "ease-in-out" => Easing::EASE_IN_OUT,
+            "linear" => Easing::Linear,
+            "Linear" => Easing::Linear,
+            "Log" => Easing::Log,
+            "CubicIn" => Easing::CubicIn,
+            "CubicOut" => Easing::CubicOut,
+            "QuarticIn" => Easing::QuarticIn,
+            "QuarticOut" => Easing::QuarticOut,
+            "EaseInElastic" => Easing::EaseInElastic,
+            "EaseOutElastic" => Easing::EaseOutElastic,
+            "EaseOutBounce" => Easing::EaseOutBounce,
+            "Sin" => Easing::Sin,
+            _ => return None,
+        })
+    }
+
     fn get_value(&self, x: f32) -> f32 {
         match self {
             Easing::Linear => {
@@ -48,6 +98,9 @@ impl Easing {
             Easing::Sin => {
                 (x * (PI/2.0) as f32).sin()
             }
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                cubic_bezier(*x1, *y1, *x2, *y2, x)
+            }
             Easing::Progressive(speed) => {
                 2.0/(1.0+20f32.powf(-(20.0/(speed*5.+5.))* x))-1.0
             }
@@ -96,17 +149,272 @@ impl Easing {
     }
 }
 
+/// Solves the curve for the `t` at which its x is `x`, and returns its y there, like CSS does
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    if x <= 0.0 || x >= 1.0 {
+        return x.clamp(0.0, 1.0);
+    }
+    // Polynomial coefficients of one axis of the curve from (0, 0) to (1, 1)
+    let coefficients = |p1: f32, p2: f32| {
+        let c = 3.0 * p1;
+        let b = 3.0 * (p2 - p1) - c;
+        (1.0 - c - b, b, c)
+    };
+    let (ax, bx, cx) = coefficients(x1, x2);
+    let (ay, by, cy) = coefficients(y1, y2);
+    let sample_x = |t: f32| ((ax * t + bx) * t + cx) * t;
+
+    // Newton's method, which converges quickly unless the slope is flat
+    let mut t = x;
+    for _ in 0..8 {
+        let error = sample_x(t) - x;
+        if error.abs() < 1e-6 {
+            return ((ay * t + by) * t + cy) * t;
+        }
+        let slope = (3.0 * ax * t + 2.0 * bx) * t + cx;
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        t -= error / slope;
+    }
+
+    // Otherwise bisection, as x always increases with t when x1 and x2 are from 0 to 1
+    let (mut low, mut high) = (0.0, 1.0);
+    t = x;
+    for _ in 0..32 {
+        let sample = sample_x(t);
+        if (sample - x).abs() < 1e-6 {
+            break;
+        }
+        if sample < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.0;
+    }
+    ((ay * t + by) * t + cy) * t
+}
+
+/// A value that can be blended towards another one, where `t` goes from 0 (`self`) to 1 (`other`)
+pub trait Interpolate: Sized {
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vec2<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Vec2::new(self.x.interpolate(&other.x, t), self.y.interpolate(&other.y, t))
+    }
+}
+
+impl Interpolate for Vec4 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Vec4::xywh(
+            self.x.interpolate(&other.x, t),
+            self.y.interpolate(&other.y, t),
+            self.width.interpolate(&other.width, t),
+            self.height.interpolate(&other.height, t),
+        )
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Color::from_f32(
+            self.red().interpolate(&other.red(), t),
+            self.green().interpolate(&other.green(), t),
+            self.blue().interpolate(&other.blue(), t),
+            self.alpha().interpolate(&other.alpha(), t),
+        )
+    }
+}
+
+/// A [`Color`] that is interpolated through hue, saturation and value instead of RGB,
+/// taking the shortest way around the hue circle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv(pub Color);
+
+impl Interpolate for Hsv {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let (h1, s1, v1) = self.0.hsv_values();
+        let (h2, s2, v2) = other.0.hsv_values();
+        // Grays have no hue, so keep the other one's instead of going through red
+        let (h1, mut h2) = (if s1 == 0.0 { h2 } else { h1 }, if s2 == 0.0 { h1 } else { h2 });
+        if h2 - h1 > 0.5 {
+            h2 -= 1.0;
+        } else if h1 - h2 > 0.5 {
+            h2 += 1.0;
+        }
+        let hue = h1.interpolate(&h2, t).rem_euclid(1.0);
+        Hsv(Color::from_hsv(hue, s1.interpolate(&s2, t), v1.interpolate(&v2, t))
+            .set_alpha_f32(self.0.alpha().interpolate(&other.0.alpha(), t)))
+    }
+}
+
+impl State {
+    /// Interpolates between two states of the same kind, or returns [`None`] if they can't be interpolated
+    pub fn try_interpolate(&self, other: &State, t: f32) -> Option<State> {
+        match (self, other) {
+            (State::Color(a), State::Color(b)) => Some(State::Color(a.interpolate(b, t))),
+            (State::Vec4(a), State::Vec4(b)) => Some(State::Vec4(a.interpolate(b, t))),
+            (State::Vec2(a), State::Vec2(b)) => Some(State::Vec2(a.interpolate(b, t))),
+            (State::Number(a), State::Number(b)) => Some(State::Number(a.interpolate(b, t))),
+            _ => None,
+        }
+    }
+}
+
+/// Registers animations, or [`Timeline`]s, in the screen's [`AnimationRegistry`]
 #[macro_export]
 macro_rules! register_anims {
     [$( $expr:expr ),* $(,)?] => {
         $(
-            context().framework().screen_animations().register($expr.clone())
+            $crate::components::framework::animation::Register::register_into(&$expr, context().framework().screen_animations());
         )*
     }
 }
 
-/// An easy-to-use object to animate objects over time
+/// How many times a timed [`Animation`] plays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Repeat {
+    Times(u32),
+    Infinite,
+}
+
+type AnimationCallback = Rc<RefCell<dyn FnMut(&mut Animation)>>;
+
+/// A value a keyframe [`Animation`] passes through
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    /// In seconds from the start of the animation
+    pub time: f32,
+    pub value: f32,
+    /// The easing of the segment that ends at this keyframe
+    pub easing: Easing,
+}
+
+impl Keyframe {
+    pub fn new(time: f32, value: f32) -> Self {
+        Keyframe {
+            time,
+            value,
+            easing: Easing::Linear,
+        }
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+/// The physics of a spring [`Animation`], which moves towards its target with a velocity that is kept
+/// when the target changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spring {
+    pub stiffness: f32,
+    pub damping: f32,
+    pub mass: f32,
+    pub velocity: f32,
+}
+
+impl Spring {
+    pub fn new(stiffness: f32, damping: f32) -> Self {
+        Spring {
+            stiffness,
+            damping,
+            mass: 1.0,
+            velocity: 0.0,
+        }
+    }
+
+    /// A spring that reaches its target as fast as possible without overshooting
+    pub fn critical(stiffness: f32) -> Self {
+        Spring::new(stiffness, 2.0 * stiffness.sqrt())
+    }
+
+    /// A spring that overshoots and oscillates around its target, where a lower `ratio` (from 0 to 1) bounces more
+    pub fn bouncy(stiffness: f32, ratio: f32) -> Self {
+        Spring::new(stiffness, 2.0 * stiffness.sqrt() * ratio.clamp(0.0, 1.0))
+    }
+
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = mass.max(0.0001);
+        self
+    }
+
+    /// Returns the displacement from the target and the velocity after `t` seconds.
+    ///
+    /// Solved exactly rather than integrated, so that it is stable with any frame time.
+    fn solve(&self, x0: f32, v0: f32, t: f32) -> (f32, f32) {
+        let w0 = (self.stiffness / self.mass).sqrt();
+        let zeta = self.damping / (2.0 * (self.stiffness * self.mass).sqrt());
+        if (zeta - 1.0).abs() < 1e-4 {
+            let decay = (-w0 * t).exp();
+            let b = v0 + w0 * x0;
+            ((x0 + b * t) * decay, (v0 - w0 * b * t) * decay)
+        } else if zeta < 1.0 {
+            let wd = w0 * (1.0 - zeta * zeta).sqrt();
+            let decay = (-zeta * w0 * t).exp();
+            let (sin, cos) = (wd * t).sin_cos();
+            (
+                decay * (x0 * cos + (v0 + zeta * w0 * x0) / wd * sin),
+                decay * (v0 * cos - (w0 * w0 * x0 + zeta * w0 * v0) / wd * sin),
+            )
+        } else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let (r1, r2) = (-w0 * (zeta - root), -w0 * (zeta + root));
+            let c2 = (r1 * x0 - v0) / (r1 - r2);
+            let c1 = x0 - c2;
+            let (e1, e2) = ((r1 * t).exp(), (r2 * t).exp());
+            (c1 * e1 + c2 * e2, r1 * c1 * e1 + r2 * c2 * e2)
+        }
+    }
+}
+
+thread_local! {
+    /// The number of the current frame and its delta, which registries advance animations by
+    static FRAME: Cell<(u64, f32)> = const { Cell::new((0, 0.0)) };
+}
+
+/// Starts a frame that is `delta` seconds after the previous one, called by the [`Framework`] before each frame.
+///
+/// [`Framework`]: crate::components::framework::Framework
+pub(crate) fn begin_frame(frame: u64, delta: f32) {
+    FRAME.set((frame, delta));
+}
+
+/// The duration and playback of a timed [`Animation`]
 #[derive(Debug, Clone, Copy)]
+struct Timing {
+    duration: f32,
+    delay: f32,
+    easing: Easing,
+    repeat: Repeat,
+    alternate: bool,
+    elapsed: f32,
+    iteration: u32,
+    playing: bool,
+    finished: bool,
+}
+
+/// An easy-to-use object to animate objects over time
+///
+/// Either moved by calling [`animate()`] every frame, or timed with [`Animation::timed()`],
+/// in which case it is advanced by the [`AnimationRegistry`] it is registered in.
+///
+/// It is `Clone` but not `Copy`, as it holds the callbacks of [`on_repeat()`] and [`on_complete()`].
+///
+/// [`animate()`]: Animation::animate
+/// [`on_repeat()`]: Animation::on_repeat
+/// [`on_complete()`]: Animation::on_complete
+#[derive(Clone)]
 pub struct Animation {
     id: u32,
     target: f32,
@@ -114,6 +422,28 @@ pub struct Animation {
     value: f32,
     last_value: f32,
     state: f32,
+    timing: Option<Timing>,
+    keyframes: Vec<Keyframe>,
+    spring: Option<Spring>,
+    /// The last frame the animation was advanced in, so that it is only advanced once when in several registries
+    frame: u64,
+    on_repeat: Vec<AnimationCallback>,
+    on_complete: Vec<AnimationCallback>,
+}
+
+impl Debug for Animation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Animation")
+            .field("id", &self.id)
+            .field("target", &self.target)
+            .field("starting", &self.starting)
+            .field("value", &self.value)
+            .field("state", &self.state)
+            .field("timing", &self.timing)
+            .field("keyframes", &self.keyframes)
+            .field("spring", &self.spring)
+            .finish()
+    }
 }
 
 impl Animation {
@@ -125,7 +455,127 @@ impl Animation {
             value,
             last_value: value,
             state: 0.0,
+            timing: None,
+            keyframes: Vec::new(),
+            spring: None,
+            frame: 0,
+            on_repeat: Vec::new(),
+            on_complete: Vec::new(),
+        }
+    }
+
+    /// Creates an animation that goes from `from` to `to` in `duration` seconds once registered,
+    /// without needing to call [`animate()`]
+    ///
+    /// [`animate()`]: Animation::animate
+    pub fn timed(from: f32, to: f32, duration: f32, easing: Easing) -> Self {
+        let mut animation = Animation::new(to, from);
+        animation.timing = Some(Timing {
+            duration: duration.max(0.0),
+            delay: 0.0,
+            easing,
+            repeat: Repeat::Times(1),
+            alternate: false,
+            elapsed: 0.0,
+            iteration: 0,
+            playing: true,
+            finished: false,
+        });
+        animation
+    }
+
+    /// Creates an animation that follows its target like a spring once registered.
+    ///
+    /// Unlike other animations, [`set_target()`] keeps the current velocity, so changing the target
+    /// while it moves does not cause a hitch.
+    ///
+    /// [`set_target()`]: Animation::set_target
+    pub fn spring(value: f32, spring: Spring) -> Self {
+        let mut animation = Animation::new(value, value);
+        animation.spring = Some(spring);
+        animation
+    }
+
+    pub fn velocity(&self) -> f32 {
+        self.spring.map_or(0.0, |s| s.velocity)
+    }
+
+    /// Whether a spring animation has settled on its target
+    pub fn is_at_rest(&self) -> bool {
+        self.spring.is_none_or(|s| s.velocity == 0.0 && self.value == self.target)
+    }
+
+    /// Creates a timed animation that passes through each keyframe, with the easing of each segment
+    /// given by the keyframe it ends at. The duration is the time of the last keyframe.
+    ///
+    /// Keyframes are sorted by time. Without any, the animation stays at 0.
+    pub fn keyframes(mut keyframes: Vec<Keyframe>) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let first = keyframes.first().map_or(0.0, |k| k.value);
+        let last = keyframes.last().map_or(0.0, |k| k.value);
+        let duration = keyframes.last().map_or(0.0, |k| k.time);
+        let mut animation = Animation::timed(first, last, duration, Easing::Linear);
+        animation.keyframes = keyframes;
+        animation
+    }
+
+    /// The value of a keyframe animation at `time` seconds into an iteration
+    fn sample(&self, time: f32) -> f32 {
+        let next = match self.keyframes.iter().position(|k| k.time > time) {
+            None => return self.keyframes.last().map_or(0.0, |k| k.value),
+            Some(0) => return self.keyframes[0].value,
+            Some(i) => i,
+        };
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let t = (time - from.time) / (to.time - from.time);
+        to.easing.get_value(t) * (to.value - from.value) + from.value
+    }
+
+    /// How long a timed animation plays for, including its delay, or [`None`] if it repeats infinitely
+    pub fn total_duration(&self) -> Option<f32> {
+        let timing = self.timing.as_ref()?;
+        match timing.repeat {
+            Repeat::Times(n) => Some(timing.delay + timing.duration * n.max(1) as f32),
+            Repeat::Infinite => None,
+        }
+    }
+
+    /// Waits `delay` seconds, not frames, before the first iteration and again after [`restart()`].
+    /// Repeated iterations follow each other without waiting.
+    ///
+    /// [`restart()`]: Animation::restart
+    pub fn delay(mut self, delay: f32) -> Self {
+        if let Some(timing) = &mut self.timing {
+            timing.delay = delay;
         }
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        if let Some(timing) = &mut self.timing {
+            timing.repeat = repeat;
+        }
+        self
+    }
+
+    /// Plays every other iteration backwards, going back and forth between the values
+    pub fn alternate(mut self, alternate: bool) -> Self {
+        if let Some(timing) = &mut self.timing {
+            timing.alternate = alternate;
+        }
+        self
+    }
+
+    /// Called every time an iteration ends, except the last
+    pub fn on_repeat<F: FnMut(&mut Animation) + 'static>(mut self, callback: F) -> Self {
+        self.on_repeat.push(Rc::new(RefCell::new(callback)));
+        self
+    }
+
+    /// Called when a timed animation finishes playing
+    pub fn on_complete<F: FnMut(&mut Animation) + 'static>(mut self, callback: F) -> Self {
+        self.on_complete.push(Rc::new(RefCell::new(callback)));
+        self
     }
 
     pub fn zero() -> Self {
@@ -141,7 +591,14 @@ impl Animation {
         self.animate(speed, animation_type)
     }
 
+    /// Moves the animation towards its target at `speed` with `animation` easing, to be called every frame.
+    ///
+    /// Timed and spring animations are advanced by their registry instead, so this only returns their value
+    /// and keeps their timing and velocity.
     pub unsafe fn animate(&mut self, speed: f32, animation: Easing) -> f32 {
+        if self.timing.is_some() || self.spring.is_some() {
+            return self.value;
+        }
         self.state += speed * context().framework().pre_delta();
         self.state = self.state.clamp(0.0, 1.0);
 
@@ -151,13 +608,128 @@ impl Animation {
     }
 
     pub unsafe fn set_target(&mut self, target: f32) {
+        if self.spring.is_some() {
+            self.target = target;
+            self.starting = self.value;
+            return;
+        }
         if self.target != target {
             self.target = target;
             self.starting = self.value;
             self.state = 0f32;
+            if let Some(timing) = &mut self.timing {
+                timing.elapsed = 0.0;
+                timing.iteration = 0;
+                timing.finished = false;
+            }
+        }
+    }
+
+    /// Moves a timed or spring animation forward by `delta` seconds, calling the callbacks of the iterations that ended
+    pub fn advance(&mut self, delta: f32) {
+        if let Some(spring) = &mut self.spring {
+            let (x, v) = spring.solve(self.value - self.target, spring.velocity, delta);
+            if x.abs() < 0.0001 && v.abs() < 0.001 {
+                self.value = self.target;
+                spring.velocity = 0.0;
+            } else {
+                self.value = self.target + x;
+                spring.velocity = v;
+            }
+            return;
+        }
+        let timing = match &mut self.timing {
+            Some(timing) if timing.playing && !timing.finished => timing,
+            _ => return,
+        };
+        timing.elapsed += delta;
+
+        let iterations = match timing.repeat {
+            Repeat::Times(n) => n.max(1),
+            Repeat::Infinite => u32::MAX,
+        };
+        let time = timing.elapsed - timing.delay;
+        let (iteration, local) = if time < 0.0 {
+            (0, 0.0)
+        } else if timing.duration == 0.0 {
+            (iterations, 1.0)
+        } else {
+            let i = (time / timing.duration).floor();
+            (i.min(u32::MAX as f32) as u32, (time / timing.duration).fract())
+        };
+
+        let previous = timing.iteration;
+        let (iteration, local) = if iteration >= iterations {
+            timing.finished = true;
+            (iterations - 1, 1.0)
+        } else {
+            (iteration, local)
+        };
+        timing.iteration = iteration;
+
+        let reversed = timing.alternate && iteration % 2 == 1;
+        let (easing, duration, finished) = (timing.easing, timing.duration, timing.finished);
+        self.state = if reversed { 1.0 - local } else { local };
+        self.value = if self.keyframes.is_empty() {
+            easing.get_value(self.state) * (self.target - self.starting) + self.starting
+        } else {
+            self.sample(self.state * duration)
+        };
+
+        for _ in previous..iteration {
+            for callback in self.on_repeat.clone() {
+                (callback.borrow_mut())(self);
+            }
+        }
+        if finished {
+            for callback in self.on_complete.clone() {
+                (callback.borrow_mut())(self);
+            }
         }
     }
 
+    /// Advances a timed or spring animation by the frame's delta, once per frame
+    fn step(&mut self) {
+        let (frame, delta) = FRAME.get();
+        if (self.timing.is_none() && self.spring.is_none()) || self.frame == frame {
+            return;
+        }
+        self.frame = frame;
+        self.advance(delta);
+    }
+
+    pub fn play(&mut self) {
+        if let Some(timing) = &mut self.timing {
+            timing.playing = true;
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(timing) = &mut self.timing {
+            timing.playing = false;
+        }
+    }
+
+    /// Plays a timed animation again from the start, including the delay
+    pub fn restart(&mut self) {
+        if let Some(timing) = &mut self.timing {
+            timing.elapsed = 0.0;
+            timing.iteration = 0;
+            timing.finished = false;
+            timing.playing = true;
+        }
+        self.state = 0.0;
+        self.value = self.starting;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.timing.as_ref().is_some_and(|t| t.playing && !t.finished)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.timing.as_ref().is_some_and(|t| t.finished)
+    }
+
     pub fn has_changed(&self) -> bool {
         // println!("changed? {} {} {}", self.last_value, self.value, self.last_value != self.value);
         (self.last_value - self.value).abs() > 0.0001
@@ -190,7 +762,7 @@ impl Into<AnimationRef> for Animation {
     }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct AnimationRegistry {
     animations: HashMap<u32, AnimationRef>,
 }
@@ -239,9 +811,12 @@ impl AnimationRegistry {
         result
     }
 
+    /// Ends the frame for all animations, and advances the timed ones
     pub fn post(&mut self) {
         for anim in self.animations.values() {
-            anim.borrow_mut().update();
+            let mut anim = anim.borrow_mut();
+            anim.update();
+            anim.step();
         }
     }
 }
@@ -254,6 +829,264 @@ impl AnimationRegTrait for AnimationRegistry {
 
 pub type AnimationRef = Rc<RefCell<Animation>>;
 
+/// Something that can be added to an [`AnimationRegistry`], used by [`register_anims!`]
+///
+/// [`register_anims!`]: crate::register_anims
+pub trait Register {
+    fn register_into(&self, registry: &mut AnimationRegistry);
+}
+
+impl Register for AnimationRef {
+    fn register_into(&self, registry: &mut AnimationRegistry) {
+        registry.register(self.clone());
+    }
+}
+
+impl Register for Timeline {
+    fn register_into(&self, registry: &mut AnimationRegistry) {
+        for a in self.animations() {
+            registry.register(a);
+        }
+    }
+}
+
+impl<T> Register for Animated<T> {
+    fn register_into(&self, registry: &mut AnimationRegistry) {
+        registry.register(self.progress.clone());
+    }
+}
+
+/// Animates any [`Interpolate`] value, such as a [`Color`], [`Hsv`] color, [`Vec2`] or [`Vec4`],
+/// with a single [`Animation`] going from 0 to 1.
+///
+/// Registering it registers that animation, so it is advanced and redrawn like any other.
+#[derive(Debug, Clone)]
+pub struct Animated<T> {
+    from: T,
+    to: T,
+    progress: AnimationRef,
+    /// When timed, how the animation to a new target plays
+    timing: Option<(f32, Easing)>,
+}
+
+impl<T: Interpolate + Clone + PartialEq> Animated<T> {
+    /// Creates a value that is animated with [`animate_to()`] every frame
+    ///
+    /// [`animate_to()`]: Animated::animate_to
+    pub fn new(value: T) -> Self {
+        Animated {
+            from: value.clone(),
+            to: value,
+            progress: Animation::new(1.0, 1.0).into(),
+            timing: None,
+        }
+    }
+
+    /// Creates a value that, once registered, animates to each new target in `duration` seconds
+    pub fn timed(value: T, duration: f32, easing: Easing) -> Self {
+        let mut animation = Animation::timed(1.0, 1.0, duration, easing);
+        if let Some(timing) = &mut animation.timing {
+            timing.finished = true;
+        }
+        Animated {
+            from: value.clone(),
+            to: value,
+            progress: animation.into(),
+            timing: Some((duration, easing)),
+        }
+    }
+
+    /// The current value
+    pub fn value(&self) -> T {
+        self.from.interpolate(&self.to, self.progress.borrow().value())
+    }
+
+    pub fn target(&self) -> &T {
+        &self.to
+    }
+
+    /// Starts animating from the current value towards `target`
+    pub fn set_target(&mut self, target: T) {
+        if target == self.to {
+            return;
+        }
+        self.from = self.value();
+        self.to = target;
+        let mut progress = self.progress.borrow_mut();
+        progress.starting = 0.0;
+        progress.target = 1.0;
+        match self.timing {
+            Some(_) => progress.restart(),
+            None => {
+                progress.state = 0.0;
+                progress.value = 0.0;
+            }
+        }
+    }
+
+    /// Sets the value right away, without animating
+    pub fn set(&mut self, value: T) {
+        self.from = value.clone();
+        self.to = value;
+        let mut progress = self.progress.borrow_mut();
+        progress.value = 1.0;
+        progress.state = 1.0;
+    }
+
+    /// Same as [`Animation::animate_to()`], for animations that are not timed
+    pub unsafe fn animate_to(&mut self, target: T, speed: f32, easing: Easing) -> T {
+        self.set_target(target);
+        self.progress.borrow_mut().animate(speed, easing);
+        self.value()
+    }
+
+    /// The animation driving this value, which goes from 0 to 1 after each new target
+    pub fn animation(&self) -> AnimationRef {
+        self.progress.clone()
+    }
+
+    pub fn has_changed(&self) -> bool {
+        self.progress.borrow().has_changed()
+    }
+}
+
+/// Part of a [`Timeline`]
+#[derive(Debug, Clone)]
+pub enum Track {
+    Animation(AnimationRef),
+    Timeline(Timeline),
+}
+
+impl From<Animation> for Track {
+    fn from(animation: Animation) -> Self {
+        Track::Animation(animation.into())
+    }
+}
+
+impl From<AnimationRef> for Track {
+    fn from(animation: AnimationRef) -> Self {
+        Track::Animation(animation)
+    }
+}
+
+impl From<Timeline> for Track {
+    fn from(timeline: Timeline) -> Self {
+        Track::Timeline(timeline)
+    }
+}
+
+impl Track {
+    fn duration(&self) -> Option<f32> {
+        match self {
+            Track::Animation(a) => a.borrow().total_duration(),
+            Track::Timeline(t) => t.duration(),
+        }
+    }
+
+    /// Delays the start by `offset` seconds
+    fn shift(&self, offset: f32) {
+        match self {
+            Track::Animation(a) => {
+                if let Some(timing) = &mut a.borrow_mut().timing {
+                    timing.delay += offset;
+                }
+            }
+            Track::Timeline(t) => {
+                for track in &t.tracks {
+                    track.shift(offset);
+                }
+            }
+        }
+    }
+}
+
+/// Timed animations composed to play one after another, together, or staggered.
+///
+/// Composition works by delaying the start of each animation, so the animations of a timeline
+/// are registered and advanced like any other, and timelines can be nested:
+///
+/// ```ignore
+/// // Fades the items in one after another, 30ms apart
+/// let fade = Timeline::stagger(items.iter().map(|_| Animation::timed(0.0, 1.0, 0.2, Easing::Sin)), 0.03);
+/// register_anims![fade];
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    tracks: Vec<Track>,
+}
+
+impl Timeline {
+    /// Plays each track after the previous one has finished. Tracks after one that repeats infinitely never start.
+    pub fn sequence<T: Into<Track>>(tracks: impl IntoIterator<Item=T>) -> Self {
+        let tracks: Vec<Track> = tracks.into_iter().map(|t| t.into()).collect();
+        let mut offset = 0.0;
+        for track in &tracks {
+            track.shift(offset);
+            offset = track.duration().map_or(f32::INFINITY, |d| d + offset);
+        }
+        Timeline { tracks }
+    }
+
+    /// Plays all tracks at the same time
+    pub fn parallel<T: Into<Track>>(tracks: impl IntoIterator<Item=T>) -> Self {
+        Timeline {
+            tracks: tracks.into_iter().map(|t| t.into()).collect(),
+        }
+    }
+
+    /// Starts each track `interval` seconds after the previous one started
+    pub fn stagger<T: Into<Track>>(tracks: impl IntoIterator<Item=T>, interval: f32) -> Self {
+        let tracks: Vec<Track> = tracks.into_iter().map(|t| t.into()).collect();
+        for (i, track) in tracks.iter().enumerate() {
+            track.shift(interval * i as f32);
+        }
+        Timeline { tracks }
+    }
+
+    pub fn tracks(&self) -> &Vec<Track> {
+        &self.tracks
+    }
+
+    /// All animations of this timeline and the ones nested in it
+    pub fn animations(&self) -> Vec<AnimationRef> {
+        let mut animations = Vec::new();
+        for track in &self.tracks {
+            match track {
+                Track::Animation(a) => animations.push(a.clone()),
+                Track::Timeline(t) => animations.append(&mut t.animations()),
+            }
+        }
+        animations
+    }
+
+    /// How long until the last track finishes, or [`None`] if one repeats infinitely
+    pub fn duration(&self) -> Option<f32> {
+        self.tracks.iter().try_fold(0f32, |max, t| t.duration().map(|d| d.max(max)))
+    }
+
+    pub fn restart(&self) {
+        for a in self.animations() {
+            a.borrow_mut().restart();
+        }
+    }
+
+    pub fn pause(&self) {
+        for a in self.animations() {
+            a.borrow_mut().pause();
+        }
+    }
+
+    pub fn play(&self) {
+        for a in self.animations() {
+            a.borrow_mut().play();
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.animations().iter().all(|a| a.borrow().is_finished())
+    }
+}
+
 /// Keeps all relevant animations for something in one place,
 /// allowing the framework to determine whether to render or not
 pub trait AnimationRegTrait {
@@ -294,4 +1127,105 @@ pub trait AnimationRegTrait {
 // }
 // pub fn target(&self) -> f64 {
 //     self.target
-// }
\ No newline at end of file
+// }
+#[test]
+pub fn timed() {
+    let finished = Rc::new(RefCell::new(0));
+    let repeats = Rc::new(RefCell::new(0));
+    let (f, r) = (finished.clone(), repeats.clone());
+    let mut anim = Animation::timed(0.0, 10.0, 1.0, Easing::Linear)
+        .delay(0.5)
+        .repeat(Repeat::Times(3))
+        .alternate(true)
+        .on_repeat(move |_| *r.borrow_mut() += 1)
+        .on_complete(move |_| *f.borrow_mut() += 1);
+
+    anim.advance(0.25);
+    assert_eq!(anim.value(), 0.0);
+    anim.advance(0.5);
+    assert!((anim.value() - 2.5).abs() < 0.001);
+    anim.advance(1.0);
+    assert!((anim.value() - 7.5).abs() < 0.001);
+    assert_eq!(*repeats.borrow(), 1);
+    anim.advance(10.0);
+    assert_eq!(anim.value(), 10.0);
+    assert!(anim.is_finished());
+    assert_eq!((*repeats.borrow(), *finished.borrow()), (2, 1));
+
+    anim.advance(1.0);
+    assert_eq!(*finished.borrow(), 1);
+}
+
+#[test]
+pub fn timelines() {
+    let mut keys = Animation::keyframes(vec![
+        Keyframe::new(0.0, 0.0),
+        Keyframe::new(1.0, 10.0),
+        Keyframe::new(2.0, 0.0).easing(Easing::CubicOut),
+    ]);
+    keys.advance(0.5);
+    assert!((keys.value() - 5.0).abs() < 0.001);
+    keys.advance(1.0);
+    assert!((keys.value() - 8.75).abs() < 0.001);
+
+    let items: Vec<AnimationRef> = (0..3).map(|_| Animation::timed(0.0, 1.0, 0.2, Easing::Linear).into()).collect();
+    let staggered = Timeline::stagger(items.clone(), 0.03);
+    assert!((staggered.duration().unwrap() - 0.26).abs() < 0.001);
+
+    let sequence = Timeline::sequence(vec![Track::from(Animation::timed(0.0, 1.0, 1.0, Easing::Linear)), staggered.into()]);
+    assert!((sequence.duration().unwrap() - 1.26).abs() < 0.001);
+    for a in sequence.animations() {
+        a.borrow_mut().advance(1.1);
+    }
+    let values: Vec<f32> = items.iter().map(|a| a.borrow().value()).collect();
+    assert!((values[0] - 0.5).abs() < 0.001 && (values[1] - 0.35).abs() < 0.001 && (values[2] - 0.2).abs() < 0.001);
+    assert!(!sequence.is_finished());
+}
+
+#[test]
+pub fn springs_and_beziers() {
+    assert!((Easing::EASE.get_value(0.5) - 0.8024).abs() < 0.001);
+    assert!((Easing::CubicBezier(0.0, 0.0, 1.0, 1.0).get_value(0.3) - 0.3).abs() < 0.001);
+    assert_eq!(Easing::from_name("cubic-bezier(0.4, 0, 0.2, 1)"), Some(Easing::CubicBezier(0.4, 0.0, 0.2, 1.0)));
+
+    for spring in [Spring::critical(200.0), Spring::bouncy(200.0, 0.3), Spring::new(200.0, 60.0)] {
+        let damped = spring.damping >= 2.0 * (spring.stiffness * spring.mass).sqrt() - 0.001;
+        let mut anim = Animation::spring(0.0, spring);
+        assert_eq!(unsafe { anim.animate_to(10.0, 1.0, Easing::Linear) }, 0.0);
+        let mut peak = 0.0f32;
+        for _ in 0..30 {
+            anim.advance(1.0 / 60.0);
+            peak = peak.max(anim.value());
+        }
+        // Only springs with less than critical damping overshoot their target
+        assert_eq!(peak > 10.0 + 0.001, !damped);
+        let (value, velocity) = (anim.value(), anim.velocity());
+        assert!(velocity != 0.0);
+
+        // Retargeting while moving keeps the value and velocity
+        assert_eq!(unsafe { anim.animate_to(-10.0, 1.0, Easing::Linear) }, value);
+        assert_eq!((anim.value(), anim.velocity()), (value, velocity));
+        let mut low = value;
+        for _ in 0..600 {
+            anim.advance(1.0 / 60.0);
+            low = low.min(anim.value());
+        }
+        assert_eq!(low < -10.0 - 0.001, !damped);
+        assert!(anim.is_at_rest() && anim.value() == -10.0);
+    }
+}
+
+#[test]
+pub fn animated_values() {
+    let mut rect = Animated::timed(Vec4::xywh(0, 0, 10, 10), 1.0, Easing::Linear);
+    assert_eq!(rect.value(), Vec4::xywh(0, 0, 10, 10));
+    rect.set_target(Vec4::xywh(10, 20, 30, 40));
+    rect.animation().borrow_mut().advance(0.5);
+    assert_eq!(rect.value(), Vec4::xywh(5, 10, 20, 25));
+
+    // Red to blue goes through magenta, not green
+    let mid = Hsv(Color::from_u8(255, 0, 0, 255)).interpolate(&Hsv(Color::from_u8(0, 0, 255, 255)), 0.5).0;
+    assert!((mid.red() - 1.0).abs() < 0.01 && mid.green() < 0.01 && (mid.blue() - 1.0).abs() < 0.01);
+    let (h, s, v) = Color::from_hsv(0.3, 0.5, 0.8).hsv_values();
+    assert!((h - 0.3).abs() < 0.001 && (s - 0.5).abs() < 0.001 && (v - 0.8).abs() < 0.001);
+}
@@ -1,5 +1,3 @@
-use std::fmt::Debug;
-
 /// A struct for easy tracking of whether a value has changed since the last check / update.
 ///
 /// Eliminates the need for something like (`value`, `last_value`)
@@ -9,7 +7,7 @@ pub struct Changing<T> {
     last: T,
 }
 
-impl<T: Clone + PartialEq + Debug> Changing<T> {
+impl<T: Clone + PartialEq> Changing<T> {
     pub fn new(value: T) -> Self {
         Changing {
             current: value.clone(),
@@ -0,0 +1,116 @@
+use std::time::Instant;
+
+/// How a [`Clock`] measures the time between frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockMode {
+    /// Wall-clock time between frames
+    Real,
+    /// Every frame lasts exactly the given number of seconds, e.g. `1.0 / 60.0` for capturing
+    FixedStep(f32),
+    /// Time only passes through [`Clock::advance`]
+    Manual,
+}
+
+/// The source of the frame delta that animations are stepped by.
+///
+/// The [`Framework`] ticks its clock once per frame on [`Event::PreRender`],
+/// so swapping the mode makes animation output reproducible.
+///
+/// [`Framework`]: super::Framework
+/// [`Event::PreRender`]: super::event::Event::PreRender
+#[derive(Debug, Clone)]
+pub struct Clock {
+    mode: ClockMode,
+    last: Instant,
+    pending: f32,
+    delta: f32,
+    elapsed: f64,
+}
+
+impl Clock {
+    pub fn new(mode: ClockMode) -> Self {
+        Clock {
+            mode,
+            last: Instant::now(),
+            pending: 0.0,
+            delta: 0.0,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn real() -> Self {
+        Clock::new(ClockMode::Real)
+    }
+
+    pub fn fixed(step: f32) -> Self {
+        Clock::new(ClockMode::FixedStep(step))
+    }
+
+    pub fn manual() -> Self {
+        Clock::new(ClockMode::Manual)
+    }
+
+    pub fn mode(&self) -> ClockMode {
+        self.mode
+    }
+
+    /// Changes the mode, the next frame is measured from now
+    pub fn set_mode(&mut self, mode: ClockMode) {
+        self.mode = mode;
+        self.last = Instant::now();
+        self.pending = 0.0;
+    }
+
+    /// Queues time for the next [`tick`] of a [`ClockMode::Manual`] clock
+    ///
+    /// [`tick`]: Clock::tick
+    pub fn advance(&mut self, seconds: f32) {
+        self.pending += seconds;
+    }
+
+    /// Starts a new frame and returns its delta in seconds
+    pub fn tick(&mut self) -> f32 {
+        let now = Instant::now();
+        self.delta = match self.mode {
+            ClockMode::Real => (now - self.last).as_secs_f64() as f32,
+            ClockMode::FixedStep(step) => step,
+            ClockMode::Manual => std::mem::take(&mut self.pending),
+        };
+        self.last = now;
+        self.elapsed += self.delta as f64;
+        self.delta
+    }
+
+    /// The delta returned by the last [`tick`](Clock::tick)
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// The sum of all deltas so far
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::real()
+    }
+}
+
+#[test]
+pub fn clock() {
+    let mut clock = Clock::fixed(1.0 / 60.0);
+    for _ in 0..60 {
+        clock.tick();
+    }
+    assert_eq!(clock.delta(), 1.0 / 60.0);
+    assert!((clock.elapsed() - 1.0).abs() < 1e-5);
+
+    clock.set_mode(ClockMode::Manual);
+    assert_eq!(clock.tick(), 0.0);
+    clock.advance(0.25);
+    clock.advance(0.25);
+    assert_eq!(clock.tick(), 0.5);
+    assert_eq!(clock.tick(), 0.0);
+}
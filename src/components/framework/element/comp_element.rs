@@ -36,7 +36,8 @@ use crate::components::spatial::vec4::Vec4;
 /// ### [`New`]
 /// [`New`] is the closure that provides a new object of type `dyn UIHandler`. This handler will be the UI representation
 /// of the [`Item`] provided. [`New`] accepts `(bool, &mut State, &mut Item)`. The bool is if the [`Item`] already has
-/// an element to represent it locally in this [`CompElement`]
+/// an element to represent it locally in this [`CompElement`]. Elements are ordered in the same way as their items are iterated,
+/// so the `u64` returned along with the handler is no longer used for ordering, and is ignored.
 ///
 /// ### [`IterFn`]
 /// The closure [`IterFn`] provides the functionality of any kind of iterator, without copying or cloning any values,
@@ -96,14 +97,15 @@ use crate::components::spatial::vec4::Vec4;
 ///         let state_c = *state;
 ///         let username = user.name.to_string();
 ///         if !exists {
-///             Some(
-///                 (Box::new(ElementBuilder::new()
+///             Some((
+///                 Box::new(ElementBuilder::new()
 ///                     .handler(move |el, e| {
 ///                         // handle the events
 ///                     })
 ///                     .build()
-///                 ) as Box<dyn UIHandler>, 0)
-///             )
+///                 ) as Box<dyn UIHandler>,
+///                 0
+///             ))
 ///         } else {
 ///             None
 ///         }
@@ -111,18 +113,121 @@ use crate::components::spatial::vec4::Vec4;
 /// );
 /// ```
 ///
+/// ### Reconciliation
+/// Every update, the [`ui_id()`]s are compared against the previous update in order to find which items
+/// were inserted, removed or moved, which is returned as a [`Reconciliation`]. Hooks can be set with
+/// [`on_enter()`], [`on_exit()`] and [`on_move()`] to react to these changes, such as animating
+/// elements in and out.
+///
 /// [`ui_id()`]: UIIdentifier::ui_id
+/// [`on_enter()`]: CompElement::on_enter
+/// [`on_exit()`]: CompElement::on_exit
+/// [`on_move()`]: CompElement::on_move
 pub struct CompElement<IterFn, State, Item, New> {
     id: u64,
     elements: HashMap<u64, Box<dyn UIHandler>>,
     render_order: Vec<u64>,
+    exiting: Vec<(u64, Box<dyn UIHandler>)>,
     changed: bool,
     iter_fn: IterFn,
     item_construct: Arc<Mutex<New>>,
     states: ChangingRegistry,
+    last_reconciliation: Reconciliation,
+
+    enter_fn: Option<EnterFn>,
+    exit_fn: Option<ExitFn>,
+    move_fn: Option<MoveFn>,
     _phantom: PhantomData<(State, Item)>,
 }
 
+type EnterFn = Box<dyn FnMut(u64, &mut dyn UIHandler)>;
+type ExitFn = Box<dyn FnMut(u64, &mut dyn UIHandler) -> bool>;
+type MoveFn = Box<dyn FnMut(u64, &mut dyn UIHandler, usize, usize)>;
+
+/// The changes between two updates of a [`CompElement`], by [`ui_id()`]
+///
+/// [`ui_id()`]: UIIdentifier::ui_id
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reconciliation {
+    /// IDs that did not exist in the previous update, with their new index
+    pub inserted: Vec<(u64, usize)>,
+    /// IDs that no longer exist, with their previous index
+    pub removed: Vec<(u64, usize)>,
+    /// IDs that changed position relative to the other items, as `(id, from, to)`
+    pub moved: Vec<(u64, usize, usize)>,
+}
+
+impl Reconciliation {
+    /// Compares two orders of IDs.
+    ///
+    /// Items kept in the longest sequence that is still in the same relative order are considered
+    /// stationary, so only the smallest set of items are reported as moved. This way,
+    /// removing the first item from a list does not report every other item as moved.
+    pub fn diff(old: &[u64], new: &[u64]) -> Self {
+        let old_indices: HashMap<u64, usize> = old.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let new_indices: HashMap<u64, usize> = new.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+        let mut diff = Reconciliation::default();
+        for (i, id) in old.iter().enumerate() {
+            if !new_indices.contains_key(id) {
+                diff.removed.push((*id, i));
+            }
+        }
+
+        // (new index, old index) of the items that exist in both
+        let mut kept = Vec::new();
+        for (i, id) in new.iter().enumerate() {
+            match old_indices.get(id) {
+                None => diff.inserted.push((*id, i)),
+                Some(old_index) => kept.push((i, *old_index)),
+            }
+        }
+
+        let stationary = longest_increasing(&kept.iter().map(|(_, old)| *old).collect::<Vec<usize>>());
+        let mut stationary = stationary.iter().peekable();
+        for (k, (new_index, old_index)) in kept.iter().enumerate() {
+            if stationary.peek() == Some(&&k) {
+                stationary.next();
+            } else {
+                diff.moved.push((new[*new_index], *old_index, *new_index));
+            }
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// Returns the positions in `values` of one of its longest strictly increasing subsequences
+fn longest_increasing(values: &[usize]) -> Vec<usize> {
+    // tails[l] is the position of the smallest value that ends an increasing subsequence of length l + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![usize::MAX; values.len()];
+    for (i, v) in values.iter().enumerate() {
+        let l = tails.partition_point(|t| values[*t] < *v);
+        if l > 0 {
+            previous[i] = tails[l - 1];
+        }
+        if l == tails.len() {
+            tails.push(i);
+        } else {
+            tails[l] = i;
+        }
+    }
+
+    let mut result = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied().unwrap_or(usize::MAX);
+    while current != usize::MAX {
+        result.push(current);
+        current = previous[current];
+    }
+    result.reverse();
+    result
+}
+
 impl<IterFn, State, Item, New> CompElement<IterFn, State, Item, New>
     where IterFn: FnMut(Box<dyn for<'a> FnMut(&mut State, &'a mut Item)>),
           New: FnMut(bool, &mut State, &mut Item) -> Option<(Box<dyn UIHandler>, u64)> + 'static,
@@ -133,15 +238,49 @@ impl<IterFn, State, Item, New> CompElement<IterFn, State, Item, New>
             id: random_id(),
             elements: HashMap::new(),
             render_order: Vec::new(),
+            exiting: Vec::new(),
             changed: true,
             iter_fn,
             item_construct: Arc::new(Mutex::new(item_construct)),
             states: ChangingRegistry::new(),
+            last_reconciliation: Reconciliation::default(),
+            enter_fn: None,
+            exit_fn: None,
+            move_fn: None,
             _phantom: PhantomData::default(),
         }
     }
 
-    pub fn update_elements(&mut self) {
+    /// Sets the hook called with the handler of every newly inserted item
+    pub fn on_enter<F: FnMut(u64, &mut dyn UIHandler) + 'static>(mut self, enter_fn: F) -> Self {
+        self.enter_fn = Some(Box::new(enter_fn));
+        self
+    }
+
+    /// Sets the hook called with the handler of every removed item.
+    ///
+    /// If the hook returns `true`, the handler is kept, still receiving events, until none of its
+    /// animations have changed for a frame. This allows it to animate out before being dropped.
+    /// If the item is added again in the meantime, the exiting handler is dropped for the new one.
+    pub fn on_exit<F: FnMut(u64, &mut dyn UIHandler) -> bool + 'static>(mut self, exit_fn: F) -> Self {
+        self.exit_fn = Some(Box::new(exit_fn));
+        self
+    }
+
+    /// Sets the hook called with the handler of every moved item, along with its previous and new index
+    pub fn on_move<F: FnMut(u64, &mut dyn UIHandler, usize, usize) + 'static>(mut self, move_fn: F) -> Self {
+        self.move_fn = Some(Box::new(move_fn));
+        self
+    }
+
+    /// The changes found by the most recent [`update_elements()`]
+    ///
+    /// [`update_elements()`]: CompElement::update_elements
+    pub fn last_reconciliation(&self) -> &Reconciliation {
+        &self.last_reconciliation
+    }
+
+    pub fn update_elements(&mut self) -> &Reconciliation {
         let mut new_elements = Rc::new(RefCell::new(HashMap::new()));
         let mut elements = Rc::new(RefCell::new(std::mem::take(&mut self.elements)));
         let changed = Rc::new(RefCell::new(false));
@@ -160,32 +299,57 @@ impl<IterFn, State, Item, New> CompElement<IterFn, State, Item, New>
 
             let (id, el) = match (cons.lock())(exists, state, item) {
                 None => (id, c_elements.borrow_mut().remove(&id)),
-                Some((el, order)) => {
+                Some((el, _)) => {
                     (*c_changed.borrow_mut()) = true;
-                    c_render_order.borrow_mut().push(order);
                     (id, Some(el))
                 },
             };
 
-            match el {
-                None => {},
-                Some(new) => {
-                    c_new_elements.borrow_mut().insert(id, new);
+            if let Some(new) = el {
+                if c_new_elements.borrow_mut().insert(id, new).is_none() {
+                    c_render_order.borrow_mut().push(id);
                 }
             }
         }));
 
         self.changed = Rc::into_inner(changed).unwrap().into_inner();
-        let old_elements = Rc::into_inner(elements).unwrap().into_inner();
-        if old_elements.len() > 0 {
+        let mut old_elements = Rc::into_inner(elements).unwrap().into_inner();
+        let mut new_elements = Rc::into_inner(new_elements).unwrap().into_inner();
+        let new_ord = Rc::into_inner(render_order).unwrap().into_inner();
+
+        let reconciliation = Reconciliation::diff(&self.render_order, &new_ord);
+        if !reconciliation.is_empty() {
             self.changed = true;
         }
 
-        let new_elements = Rc::into_inner(new_elements).unwrap().into_inner();
-        self.elements = new_elements;
+        for (id, _) in &reconciliation.removed {
+            if let Some(mut el) = old_elements.remove(id) {
+                let keep = match &mut self.exit_fn {
+                    None => false,
+                    Some(exit_fn) => exit_fn(*id, el.as_mut()),
+                };
+                if keep {
+                    self.exiting.push((*id, el));
+                }
+            }
+        }
+        // An item added back while its old handler animates out gets only the new handler
+        self.exiting.retain(|(id, _)| !new_elements.contains_key(id));
+        for (id, _) in &reconciliation.inserted {
+            if let (Some(enter_fn), Some(el)) = (&mut self.enter_fn, new_elements.get_mut(id)) {
+                enter_fn(*id, el.as_mut());
+            }
+        }
+        for (id, from, to) in &reconciliation.moved {
+            if let (Some(move_fn), Some(el)) = (&mut self.move_fn, new_elements.get_mut(id)) {
+                move_fn(*id, el.as_mut(), *from, *to);
+            }
+        }
 
-        let new_ord = Rc::into_inner(render_order).unwrap().into_inner();
+        self.elements = new_elements;
         self.render_order = new_ord;
+        self.last_reconciliation = reconciliation;
+        &self.last_reconciliation
     }
 }
 
@@ -204,18 +368,35 @@ impl<IterFn, State, Item, New> UIHandler for CompElement<IterFn, State, Item, Ne
 
         let mut result = EventResult::Ok;
         let st = Instant::now();
-        for (id, el) in &mut self.elements {
-            match el.handle(event) {
-                EventResult::Ok => {},
-                r => result = r,
+        for id in &self.render_order {
+            if let Some(el) = self.elements.get_mut(id) {
+                match el.handle(event) {
+                    EventResult::Ok => {},
+                    r => result = r,
+                }
             }
         }
+        for (_, el) in &mut self.exiting {
+            el.handle(event);
+        }
         let et = st.elapsed();
         // println!("iter took {:?}", et);
 
         match event {
             Event::PostRender => {
                 self.changed = false;
+                // Drop exiting elements once their animations have finished
+                self.exiting.retain_mut(|(_, el)| {
+                    match el.animations() {
+                        None => false,
+                        Some(mut reg) => {
+                            // Timed animations are only advanced by post(), so check both before and after
+                            let animating = reg.has_changed();
+                            reg.post();
+                            animating || reg.has_changed()
+                        }
+                    }
+                });
             }
             _ => {}
         }
@@ -224,7 +405,7 @@ impl<IterFn, State, Item, New> UIHandler for CompElement<IterFn, State, Item, Ne
     }
 
     unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
-        if self.changed {
+        if self.changed || !self.exiting.is_empty() {
             return TickResult::RedrawLayout;
         }
         for el in self.elements.values_mut() {
@@ -247,4 +428,103 @@ impl<IterFn, State, Item, New> UIHandler for CompElement<IterFn, State, Item, Ne
     fn layout_context(&self) -> LayoutContext {
         todo!()
     }
-}
\ No newline at end of file
+}
+#[test]
+pub fn reconciliation() {
+    let diff = Reconciliation::diff(&[1, 2, 3, 4], &[2, 3, 4, 5]);
+    assert_eq!(diff.removed, vec![(1, 0)]);
+    assert_eq!(diff.inserted, vec![(5, 3)]);
+    assert!(diff.moved.is_empty());
+
+    let diff = Reconciliation::diff(&[1, 2, 3, 4], &[4, 1, 2, 3]);
+    assert_eq!(diff.moved, vec![(4, 3, 0)]);
+}
+
+#[test]
+pub fn hooks_and_exiting() {
+    use crate::components::framework::animation::{begin_frame, Animation, AnimationRef, Easing};
+
+    struct Row {
+        fade: AnimationRef,
+        _alive: Rc<()>,
+    }
+    impl UIHandler for Row {
+        unsafe fn handle(&mut self, _event: &Event) -> EventResult { EventResult::Ok }
+        unsafe fn tick(&mut self, _render_pass: &RenderPass) -> TickResult { TickResult::Valid }
+        fn animations(&mut self) -> Option<AnimationRegistry> {
+            let mut registry = AnimationRegistry::new();
+            registry.register(self.fade.clone());
+            Some(registry)
+        }
+        fn bounds(&self) -> Vec4 { Vec4::default() }
+        fn layout_context(&self) -> LayoutContext { LayoutContext::default() }
+    }
+    struct Id(u64);
+    impl UIIdentifier for Id {
+        fn ui_id(&self) -> u64 { self.0 }
+    }
+
+    let items = Rc::new(RefCell::new(vec![1, 2, 3]));
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let alive = Rc::new(());
+
+    let (c_items, c_alive) = (items.clone(), alive.clone());
+    let (enter_log, exit_log, move_log) = (log.clone(), log.clone(), log.clone());
+    let mut comp = CompElement::new(
+        move |mut inner| {
+            for id in c_items.borrow().iter() {
+                inner(&mut (), &mut Id(*id));
+            }
+        },
+        move |exists, _: &mut (), _: &mut Id| {
+            if exists {
+                return None;
+            }
+            let mut fade = Animation::timed(1.0, 0.0, 0.1, Easing::Linear);
+            fade.pause();
+            Some((Box::new(Row { fade: fade.into(), _alive: c_alive.clone() }) as Box<dyn UIHandler>, 0))
+        },
+    )
+        .on_enter(move |id, _| enter_log.borrow_mut().push(format!("enter {id}")))
+        .on_exit(move |id, handler| {
+            exit_log.borrow_mut().push(format!("exit {id}"));
+            for anim in handler.animations().unwrap().all() {
+                anim.borrow_mut().play();
+            }
+            true
+        })
+        .on_move(move |id, _, from, to| move_log.borrow_mut().push(format!("move {id} {from} {to}")));
+
+    comp.update_elements();
+    assert_eq!(*log.borrow(), vec!["enter 1", "enter 2", "enter 3"]);
+    assert_eq!(Rc::strong_count(&alive), 4);
+
+    log.borrow_mut().clear();
+    *items.borrow_mut() = vec![3, 1];
+    comp.update_elements();
+    assert_eq!(*log.borrow(), vec!["exit 2", "move 3 2 0"]);
+    assert_eq!(comp.exiting.len(), 1);
+
+    // Added back while animating out, so the old handler is dropped for the new one
+    log.borrow_mut().clear();
+    *items.borrow_mut() = vec![3, 1, 2];
+    comp.update_elements();
+    assert_eq!(*log.borrow(), vec!["enter 2"]);
+    assert!(comp.exiting.is_empty());
+    assert_eq!(Rc::strong_count(&alive), 4);
+
+    *items.borrow_mut() = vec![3, 1];
+    comp.update_elements();
+    assert_eq!(comp.exiting.len(), 1);
+
+    // The exiting handler is kept while its animation runs, then dropped
+    let mut frame = 0;
+    while !comp.exiting.is_empty() {
+        frame += 1;
+        assert!(frame < 10, "exiting handler was never dropped");
+        begin_frame(frame, 0.05);
+        unsafe { comp.handle(&Event::PostRender); }
+    }
+    assert!(frame > 1);
+    assert_eq!(Rc::strong_count(&alive), 3);
+}
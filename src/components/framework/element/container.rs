@@ -4,6 +4,7 @@ use crate::components::framework::animation::AnimationRegistry;
 use crate::components::framework::ui_traits::{TickResult, UIHandler};
 use crate::components::framework::event::{Event, EventResult, RenderPass};
 use crate::components::framework::layout::{LayoutContext, LayoutDirection, LayoutEvent, Sizing};
+use crate::components::framework::style::{ComputedStyle, StateFlags, Styled, StyleTarget};
 use crate::components::render::stack::State;
 use crate::components::spatial::vec2::Vec2;
 use crate::components::spatial::vec4::Vec4;
@@ -46,7 +47,8 @@ macro_rules! container {
 pub struct Container {
     pub bounds: Vec4,
     children: Vec<Box<dyn UIHandler>>,
-    pub layout: LayoutContext
+    pub layout: LayoutContext,
+    style: Styled,
 }
 
 impl Container {
@@ -55,6 +57,7 @@ impl Container {
             bounds: Vec4::zero().clone(),
             children: vec![],
             layout,
+            style: Styled::new(StyleTarget::new("Container")),
         };
 
         c.bounds.set_wh(c.layout.pref_size);
@@ -217,6 +220,37 @@ impl Container {
     pub fn add<H: UIHandler + 'static>(&mut self, child: H) {
         self.children.push(Box::new(child));
     }
+
+    pub fn children(&mut self) -> &mut Vec<Box<dyn UIHandler>> {
+        &mut self.children
+    }
+
+    /// Sets the type name that style selectors match, `Container` by default
+    pub fn with_type(mut self, type_name: impl ToString) -> Self {
+        let mut target = self.style.target().clone();
+        target.type_name = Some(type_name.to_string());
+        self.style.set_target(target);
+        self
+    }
+
+    pub fn with_class(mut self, class: impl ToString) -> Self {
+        let mut target = self.style.target().clone();
+        target.classes.push(class.to_string());
+        self.style.set_target(target);
+        self
+    }
+
+    pub fn style(&self) -> &ComputedStyle {
+        self.style.computed()
+    }
+
+    /// The style of this container, which its children inherit from.
+    ///
+    /// Styles are applied over the padding, margin and spacing the layout had when first resolved,
+    /// call [`Styled::reset_base()`] after changing them on [`Container::layout`].
+    pub fn styled(&mut self) -> &mut Styled {
+        &mut self.style
+    }
 }
 
 impl UIHandler for Container {
@@ -225,6 +259,13 @@ impl UIHandler for Container {
         if debug {
             self.bounds.debug_draw(self.layout.debug_color);
         }
+        if let Event::PreRender = event {
+            let mut flags = StateFlags::NONE;
+            flags.set(StateFlags::HOVER, context().window().mouse().hovers(&self.bounds));
+            self.style.set_flags(flags);
+            self.style.resolve(&mut self.layout);
+        }
+        self.style.push();
         // Translate child positions, which also offsets mouse correctly
         context().renderer().stack().push(State::Translate(self.bounds().x(), self.bounds().y()));
         // println!("transled to {:?}", self.bounds);
@@ -246,6 +287,7 @@ impl UIHandler for Container {
             }
         }
         context().renderer().stack().pop();
+        self.style.pop();
 
         result = match event {
             Event::Layout(stage) => {
@@ -282,6 +324,9 @@ impl UIHandler for Container {
     }
 
     unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        if self.style.changed() {
+            return TickResult::RedrawLayout;
+        }
         for c in &mut self.children {
             let r = c.tick(render_pass);
             if !r.is_valid() {
@@ -292,7 +337,7 @@ impl UIHandler for Container {
     }
 
     fn animations(&mut self) -> Option<AnimationRegistry> {
-        None
+        Some(self.style.animations().clone())
     }
 
     fn bounds(&self) -> Vec4 {
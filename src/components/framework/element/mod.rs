@@ -12,6 +12,7 @@ use crate::components::framework::changing::Changing;
 use crate::components::framework::ui_traits::{TickResult, UIHandler, UIIdentifier};
 use crate::components::framework::event::{Event, EventResult, RenderPass};
 use crate::components::framework::layout::{LayoutContext, LayoutEvent};
+use crate::components::framework::style::{ComputedStyle, StateFlags, Styled, StyleTarget};
 use crate::components::framework::ui_traits;
 use crate::components::render::color::ToColor;
 use crate::components::render::stack::State;
@@ -20,16 +21,20 @@ use crate::components::spatial::vec4::Vec4;
 
 pub mod comp_element;
 pub mod container;
+pub mod virtual_list;
 
 pub struct Element {
     id: u64,
     bounds: Changing<Vec4>,
     hovering: bool,
+    focused: bool,
 
     layout_context: LayoutContext,
+    style: Styled,
 
     pub active: bool,
     last_active: bool,
+    disabled: bool,
     pub draggable: bool,
     pub scrollable: bool,
 
@@ -57,9 +62,12 @@ impl Element {
             render_handler: None,
             tick_fn: Arc::new(Mutex::new(Box::new(|_, _| TickResult::Valid))),
             hovering: false,
+            focused: false,
             layout_context,
+            style: Styled::new(StyleTarget::new("Element")),
             active: true,
             last_active: true,
+            disabled: false,
             draggable,
             scrollable: false,
             scroll: Changing::new((0.0, 0.0)),
@@ -106,6 +114,46 @@ impl Element {
     pub fn hovering(&self) -> bool {
         self.hovering
     }
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+    /// If the element is being dragged with the mouse, which moves it on every [`Event::PreRender`]
+    pub fn dragging(&self) -> bool {
+        self.dragging.0
+    }
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+    /// If the element ignores input. Unlike an inactive element it still lays out, renders and tracks hovering
+    pub fn disabled(&self) -> bool {
+        self.disabled
+    }
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.disabled = disabled;
+        if disabled {
+            self.focused = false;
+            self.dragging.0 = false;
+        }
+    }
+    /// If the element is active and not disabled, so it should react to input
+    pub fn interactive(&self) -> bool {
+        self.active && !self.disabled
+    }
+    /// The style resolved for this element, which is updated before the handler receives [`Event::PreRender`]
+    pub fn style(&self) -> &ComputedStyle {
+        self.style.computed()
+    }
+    pub fn styled(&mut self) -> &mut Styled {
+        &mut self.style
+    }
+    pub fn layout_mut(&mut self) -> &mut LayoutContext {
+        &mut self.layout_context
+    }
+    pub fn add_class(&mut self, class: impl ToString) {
+        let mut target = self.style.target().clone();
+        target.classes.push(class.to_string());
+        self.style.set_target(target);
+    }
     pub fn scroll(&mut self) -> &mut Changing<(f32, f32)> {
         &mut self.scroll
     }
@@ -117,6 +165,35 @@ impl Element {
             }
         }
     }
+    /// Replaces the handlers, layout and animations of this element with the ones of `other`,
+    /// while keeping the current bounds, scroll, hover and drag state.
+    ///
+    /// Animations that `other` registers again are the same, so they keep running where they are.
+    pub fn patch(&mut self, other: Element) {
+        self.handler = other.handler;
+        self.render_handler = other.render_handler;
+        self.tick_fn = other.tick_fn;
+        self.active_fn = other.active_fn;
+        self.layout_context = other.layout_context;
+        self.draggable = other.draggable;
+        self.scrollable = other.scrollable;
+        self.active = other.active;
+        self.disabled = other.disabled;
+        self.animations = other.animations;
+
+        self.style.patch(other.style);
+        self.style.reset_base(&self.layout_context);
+    }
+    unsafe fn update_style(&mut self) {
+        let mouse = context().window().mouse();
+        let mut flags = StateFlags::NONE;
+        flags.set(StateFlags::HOVER, self.hovering);
+        flags.set(StateFlags::ACTIVE, self.hovering && mouse.is_pressed(MouseButton::Button1));
+        flags.set(StateFlags::FOCUSED, self.focused);
+        flags.set(StateFlags::DISABLED, !self.interactive());
+        self.style.set_flags(flags);
+        self.style.resolve(&mut self.layout_context);
+    }
     fn dispatch_event(&mut self, event: &Event) {
         // Arc mutex so that can be called with self ref
         let h = self.handler.clone();
@@ -144,7 +221,8 @@ impl UIHandler for Element {
                 if let Some(active_fn) = &mut self.active_fn {
                     self.active = active_fn();
                 }
-                self.hovering = mouse.pos().intersects(self.bounds());
+                self.hovering = mouse.hovers(self.bounds());
+                self.update_style();
                 self.dispatch_event(event);
             }
             Event::PostRender => {
@@ -196,6 +274,12 @@ impl UIHandler for Element {
         if !self.active {
             return EventResult::Ok;
         }
+        if self.disabled {
+            match event {
+                Event::MouseClick(..) | Event::Scroll(..) | Event::Keyboard(..) | Event::Char(..) => return EventResult::Ok,
+                _ => {}
+            }
+        }
 
         match event {
             Event::PreRender => {
@@ -277,7 +361,7 @@ impl UIHandler for Element {
         if !self.active && !changed_active {
             return TickResult::RedrawLayout;
         }
-        if !self.has_rendered || self.scroll.changed() || self.bounds.changed() || changed_active {
+        if !self.has_rendered || self.scroll.changed() || self.bounds.changed() || changed_active || self.style.changed() {
             return TickResult::RedrawLayout;
         }
 
@@ -298,7 +382,11 @@ impl UIHandler for Element {
     }
 
     fn animations(&mut self) -> Option<AnimationRegistry> {
-        Some(self.animations.clone())
+        let mut animations = self.animations.clone();
+        for a in self.style.animations().all() {
+            animations.register(a);
+        }
+        Some(animations)
     }
 
     fn bounds(&self) -> Vec4 {
@@ -385,6 +473,24 @@ impl ElementBuilder {
         self.element.active = active;
         self
     }
+    /// Sets the type name that style selectors match, `Element` by default
+    pub fn style_type(mut self, type_name: impl ToString) -> Self {
+        let mut target = self.element.style.target().clone();
+        target.type_name = Some(type_name.to_string());
+        self.element.style.set_target(target);
+        self
+    }
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+    /// Makes a style property follow a key of the [`Framework`]'s states, see [`Styled::bind()`]
+    ///
+    /// [`Framework`]: crate::components::framework::Framework
+    pub fn bind_style(mut self, property: impl ToString, key: impl ToString) -> Self {
+        self.element.style.bind(property, key);
+        self
+    }
     pub fn active_fn<Fn: FnMut() -> bool + 'static>(mut self, active_fn: Option<Fn>) -> Self {
         self.element.set_active_fn(active_fn);
         self
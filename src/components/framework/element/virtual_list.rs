@@ -0,0 +1,428 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::components::context::context;
+use crate::components::framework::animation::{AnimationRef, AnimationRegistry, Easing};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::{LayoutContext, LayoutEvent};
+use crate::components::framework::ui_traits::{random_id, TickResult, UIHandler, UIIdentifier};
+use crate::components::render::stack::State;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// The index of the item a row handler of a [`VirtualList`] currently represents.
+///
+/// Row handlers are recycled as the list scrolls, so the index can change between frames.
+/// Handlers should read their item through [`RowIndex::get()`] when handling events instead of
+/// capturing item data when they are created.
+#[derive(Clone, Debug)]
+pub struct RowIndex {
+    index: Rc<Cell<usize>>,
+}
+
+impl RowIndex {
+    fn new(index: usize) -> Self {
+        RowIndex {
+            index: Rc::new(Cell::new(index)),
+        }
+    }
+
+    pub fn get(&self) -> usize {
+        self.index.get()
+    }
+
+    fn set(&self, index: usize) {
+        self.index.set(index);
+    }
+}
+
+struct Row {
+    index: RowIndex,
+    handler: Box<dyn UIHandler>,
+}
+
+/// A list that only keeps handlers for the rows that are currently visible.
+///
+/// Unlike [`CompElement`], which creates and keeps a handler for every item, [`VirtualList`] only
+/// knows the amount of items and the height of each one. Handlers are created for the visible
+/// rows, plus [`overscan`] rows above and below, and are recycled once they are scrolled out of view.
+///
+/// ### [`Count`]
+/// Returns the amount of items in the list. Called every frame.
+///
+/// ### [`Height`]
+/// Returns the height of the item at the given index. Heights are cached, so if they change
+/// without the item count changing, [`invalidate_heights()`] should be called.
+///
+/// ### [`New`]
+/// Creates a new row handler. Only called when there is no handler left to recycle.
+/// The [`RowIndex`] given is updated whenever the handler is reused for another item.
+///
+/// Rows are given the width of the list and the height returned by [`Height`] through
+/// [`LayoutEvent`]s, and are positioned relative to the top left of the list.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use parking_lot::Mutex;
+/// use ferrum::components::framework::element::ElementBuilder;
+/// use ferrum::components::framework::element::virtual_list::VirtualList;
+/// use ferrum::components::framework::ui_traits::UIHandler;
+///
+/// let names: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new((0..100_000).map(|i| format!("user_{}", i)).collect()));
+///
+/// let names_c = names.clone();
+/// let list = VirtualList::new(
+///     // Count
+///     move || names_c.lock().len(),
+///     // Height
+///     |index| if index % 10 == 0 { 40.0 } else { 20.0 },
+///     // New
+///     move |row| {
+///         let names = names.clone();
+///         Box::new(ElementBuilder::new()
+///             .handler(move |el, e| {
+///                 // names.lock()[row.get()] is the item this row currently represents
+///             })
+///             .build()
+///         ) as Box<dyn UIHandler>
+///     }
+/// );
+/// ```
+///
+/// [`CompElement`]: crate::components::framework::element::comp_element::CompElement
+/// [`overscan`]: VirtualList::set_overscan
+/// [`invalidate_heights()`]: VirtualList::invalidate_heights
+pub struct VirtualList<Count, Height, New> {
+    id: u64,
+    bounds: Vec4,
+    layout_context: LayoutContext,
+    hovering: bool,
+
+    count_fn: Count,
+    height_fn: Height,
+    item_construct: New,
+
+    /// The top of every row, plus the total height as the last value
+    offsets: Vec<f32>,
+    heights_valid: bool,
+    overscan: usize,
+
+    rows: Vec<Row>,
+    recycled: Vec<Row>,
+    range: (usize, usize),
+    changed: bool,
+
+    animations: AnimationRegistry,
+    scroll: AnimationRef,
+    target_scroll: f32,
+    scroll_speed: f32,
+}
+
+impl<Count, Height, New> VirtualList<Count, Height, New>
+    where Count: FnMut() -> usize,
+          Height: FnMut(usize) -> f32,
+          New: FnMut(RowIndex) -> Box<dyn UIHandler>,
+{
+    pub fn new(count_fn: Count, height_fn: Height, item_construct: New) -> Self {
+        let mut animations = AnimationRegistry::new();
+        let scroll = animations.new_anim();
+        VirtualList {
+            id: random_id(),
+            bounds: *Vec4::zero(),
+            layout_context: LayoutContext::new(),
+            hovering: false,
+            count_fn,
+            height_fn,
+            item_construct,
+            offsets: vec![0.0],
+            heights_valid: false,
+            overscan: 4,
+            rows: Vec::new(),
+            recycled: Vec::new(),
+            range: (0, 0),
+            changed: true,
+            animations,
+            scroll,
+            target_scroll: 0.0,
+            scroll_speed: 40.0,
+        }
+    }
+
+    pub fn layout_context(mut self, layout_context: LayoutContext) -> Self {
+        self.bounds.set_wh(layout_context.pref_size);
+        self.layout_context = layout_context;
+        self
+    }
+
+    /// Sets the amount of rows that are kept above and below the visible rows
+    pub fn set_overscan(&mut self, overscan: usize) {
+        self.overscan = overscan;
+        self.changed = true;
+    }
+
+    /// Sets how many pixels are scrolled per scroll event
+    pub fn set_scroll_speed(&mut self, scroll_speed: f32) {
+        self.scroll_speed = scroll_speed;
+    }
+
+    /// Forces all row heights to be re-queried on the next frame
+    pub fn invalidate_heights(&mut self) {
+        self.heights_valid = false;
+    }
+
+    /// The total height of all rows
+    pub fn content_height(&self) -> f32 {
+        *self.offsets.last().unwrap()
+    }
+
+    pub fn scroll(&self) -> f32 {
+        self.scroll.borrow().value()
+    }
+
+    /// Scrolls so that the top of the item at `index` is at the top of the list
+    pub fn scroll_to(&mut self, index: usize) {
+        let top = self.offsets.get(index).copied().unwrap_or(self.content_height());
+        self.target_scroll = self.clamp_scroll(top);
+    }
+
+    /// Returns the row handler for the item at `index`, if it is currently instantiated
+    pub fn row(&mut self, index: usize) -> Option<&mut Box<dyn UIHandler>> {
+        self.rows.iter_mut().find(|r| r.index.get() == index).map(|r| &mut r.handler)
+    }
+
+    fn clamp_scroll(&self, scroll: f32) -> f32 {
+        scroll.min(self.content_height() - self.bounds.height()).max(0.0)
+    }
+
+    fn update_offsets(&mut self) {
+        let count = (self.count_fn)();
+        if self.heights_valid && self.offsets.len() == count + 1 {
+            return;
+        }
+
+        self.offsets.clear();
+        self.offsets.reserve(count + 1);
+        let mut total = 0.0;
+        for i in 0..count {
+            self.offsets.push(total);
+            total += (self.height_fn)(i).max(0.0);
+        }
+        self.offsets.push(total);
+
+        self.heights_valid = true;
+        self.changed = true;
+    }
+
+    /// Calculates the range of indices that should currently have a handler
+    fn visible_range(&self) -> (usize, usize) {
+        let count = self.offsets.len() - 1;
+        let scroll = self.scroll();
+        // The first row whose bottom is below the top of the list
+        let first = self.offsets[1..].partition_point(|bottom| *bottom <= scroll);
+        // The first row whose top is below the bottom of the list
+        let last = self.offsets[..count].partition_point(|top| *top < scroll + self.bounds.height());
+
+        (first.saturating_sub(self.overscan), (last + self.overscan).min(count))
+    }
+
+    pub fn update_rows(&mut self) {
+        self.update_offsets();
+
+        let range = self.visible_range();
+        if range == self.range && self.rows.len() == range.1 - range.0 {
+            return;
+        }
+        self.range = range;
+        self.changed = true;
+
+        // Move rows that are no longer visible to be recycled
+        let mut i = 0;
+        while i < self.rows.len() {
+            let index = self.rows[i].index.get();
+            if index < range.0 || index >= range.1 {
+                self.recycled.push(self.rows.swap_remove(i));
+            } else {
+                i += 1;
+            }
+        }
+
+        for index in range.0..range.1 {
+            if self.rows.iter().any(|r| r.index.get() == index) {
+                continue;
+            }
+            let row = match self.recycled.pop() {
+                Some(row) => {
+                    row.index.set(index);
+                    row
+                }
+                None => {
+                    let row_index = RowIndex::new(index);
+                    Row {
+                        handler: (self.item_construct)(row_index.clone()),
+                        index: row_index,
+                    }
+                }
+            };
+            self.rows.push(row);
+        }
+        self.rows.sort_by_key(|r| r.index.get());
+    }
+
+    unsafe fn layout_rows(&mut self, stage: &LayoutEvent) {
+        let width = self.bounds.width();
+        for row in &mut self.rows {
+            let index = row.index.get();
+            let height = self.offsets[index + 1] - self.offsets[index];
+            let event = match stage {
+                LayoutEvent::GrowWidth(_) => LayoutEvent::GrowWidth(width - row.handler.bounds().width()),
+                LayoutEvent::GrowHeight(_) => LayoutEvent::GrowHeight(height - row.handler.bounds().height()),
+                LayoutEvent::Position(_) => LayoutEvent::Position(Vec2::new(0.0, self.offsets[index])),
+                LayoutEvent::FitWidth => LayoutEvent::FitWidth,
+                LayoutEvent::FitHeight => LayoutEvent::FitHeight,
+                LayoutEvent::OptimizeSize(v) => LayoutEvent::OptimizeSize(*v),
+            };
+            row.handler.handle(&Event::Layout(event));
+        }
+    }
+}
+
+impl<Count, Height, New> UIHandler for VirtualList<Count, Height, New>
+    where Count: FnMut() -> usize,
+          Height: FnMut(usize) -> f32,
+          New: FnMut(RowIndex) -> Box<dyn UIHandler>,
+{
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        match event {
+            Event::PreRender => {
+                self.hovering = context().window().mouse().hovers(&self.bounds);
+                self.target_scroll = self.clamp_scroll(self.target_scroll);
+                self.scroll.borrow_mut().animate_to(self.target_scroll, 2.0, Easing::Sin);
+                self.update_rows();
+            }
+            Event::Scroll(_, y) if self.hovering => {
+                self.target_scroll = self.clamp_scroll(self.target_scroll - *y * self.scroll_speed);
+            }
+            Event::Layout(stage) => {
+                match stage {
+                    LayoutEvent::FitWidth => self.bounds.width = self.layout_context.min_size.x,
+                    LayoutEvent::FitHeight => self.bounds.height = self.layout_context.min_size.y,
+                    LayoutEvent::GrowWidth(v) => {
+                        self.bounds.width += *v;
+                        if let Some(max) = self.layout_context.max_size {
+                            self.bounds.width = self.bounds.width.min(max.x);
+                        }
+                    }
+                    LayoutEvent::GrowHeight(v) => {
+                        self.bounds.height += *v;
+                        if let Some(max) = self.layout_context.max_size {
+                            self.bounds.height = self.bounds.height.min(max.y);
+                        }
+                    }
+                    LayoutEvent::Position(pos) => self.bounds.set_pos(*pos),
+                    LayoutEvent::OptimizeSize(_) => {}
+                }
+                self.layout_rows(stage);
+                return EventResult::Ok;
+            }
+            _ => {}
+        }
+
+        // Rows scrolled partially out of view must not draw outside of the list
+        let clip = matches!(event, Event::Render(_));
+        if clip {
+            context().renderer().stack().push(State::Clip(self.bounds));
+        }
+        // Translate rows to be relative to the top of the list, which also offsets mouse correctly
+        context().renderer().stack().push(State::Translate(self.bounds.x(), self.bounds.y() - self.scroll()));
+        let mut result = EventResult::Ok;
+        for row in &mut self.rows {
+            if let Event::PostRender = event {
+                if let Some(mut reg) = row.handler.animations() {
+                    reg.post();
+                }
+            }
+
+            match row.handler.handle(event) {
+                EventResult::Ok => {},
+                r => result = r,
+            }
+        }
+        context().renderer().stack().pop();
+        if clip {
+            context().renderer().stack().pop();
+        }
+
+        if let Event::PostRender = event {
+            self.changed = false;
+        }
+
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        if self.changed || self.animations.has_changed() {
+            return TickResult::RedrawLayout;
+        }
+        for row in &mut self.rows {
+            let result = row.handler.tick(render_pass);
+            if !result.is_valid() {
+                return result;
+            }
+        }
+        TickResult::Valid
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        Some(self.animations.clone())
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.bounds
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.layout_context.clone()
+    }
+}
+
+impl<Count, Height, New> UIIdentifier for VirtualList<Count, Height, New> {
+    fn ui_id(&self) -> u64 {
+        self.id
+    }
+}
+
+#[test]
+pub fn visible_rows() {
+    use std::cell::RefCell;
+    use crate::components::framework::animation::Animation;
+    use crate::components::framework::element::ElementBuilder;
+
+    let created = Rc::new(Cell::new(0));
+    let c_created = created.clone();
+    // Every tenth row is 40 high and the others 20, so every ten rows are 220 high
+    let mut list = VirtualList::new(
+        || 1000,
+        |index| if index % 10 == 0 { 40.0 } else { 20.0 },
+        move |_| {
+            c_created.set(c_created.get() + 1);
+            Box::new(ElementBuilder::new().build()) as Box<dyn UIHandler>
+        },
+    ).layout_context(LayoutContext { pref_size: Vec2::new(100.0, 100.0), ..LayoutContext::new() });
+    list.set_overscan(2);
+    let indices = |list: &VirtualList<_, _, _>| list.rows.iter().map(|r| r.index.get()).collect::<Vec<_>>();
+
+    list.update_rows();
+    assert_eq!(list.content_height(), 22000.0);
+    assert_eq!(indices(&list), (0..6).collect::<Vec<_>>());
+    assert_eq!(created.get(), 6);
+
+    // Rows scrolled out of view are recycled for the ones scrolled into view
+    list.scroll = Rc::new(RefCell::new(Animation::new(2200.0, 2200.0)));
+    list.update_rows();
+    assert_eq!(indices(&list), (98..106).collect::<Vec<_>>());
+    assert_eq!(created.get(), 8);
+    assert!(list.row(100).is_some() && list.row(0).is_none());
+}
+
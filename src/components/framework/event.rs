@@ -18,6 +18,8 @@ pub enum Event {
     MousePos(f32, f32),
     Scroll(f32, f32),
     Keyboard(Key, Action, Modifiers),
+    /// Text typed with the keyboard, after the layout and modifiers are applied
+    Char(char),
     Resize(f32, f32),
     GlfwRaw(WindowEvent),
     Layout(LayoutEvent),
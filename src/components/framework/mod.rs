@@ -1,11 +1,23 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::mem;
+use std::rc::Rc;
 use std::time::Instant;
-use crate::components::framework::animation::AnimationRegistry;
+use glfw::{Action, Key};
+use crate::components::context::context;
+use crate::components::framework::animation::{AnimationRegistry, Easing};
+use crate::components::framework::clock::{Clock, ClockMode};
 use crate::components::framework::event::{Event, RenderPass};
 use crate::components::framework::layer::Layer;
+use crate::components::framework::navigation::{ActiveTransition, Leaving, Navigation, ScreenEntry, Transition};
+use crate::components::framework::popup::{Popup, PopupId};
 use crate::components::framework::screen::{DefaultScreen, ScreenTrait};
 use crate::components::framework::state::{ChangingRegistry, UnchangingRegistry};
-use crate::components::framework::ui_traits::TickResult;
+use crate::components::framework::style::StyleSheet;
+use crate::components::framework::theme::Theme;
+use crate::components::framework::ui_traits::{TickResult, UIHandler, UIHandlerRef};
+use crate::components::render::stack::State;
+use crate::components::spatial::vec2::Vec2;
 use crate::components::spatial::vec4::Vec4;
 use crate::components::wrapper::framebuffer::Framebuffer;
 use crate::gl_binds::gl30::{BindFramebuffer, FRAMEBUFFER};
@@ -19,6 +31,22 @@ pub mod state;
 pub mod changing;
 pub mod ui_traits;
 pub mod layout;
+pub mod view;
+pub mod theme;
+pub mod style;
+pub mod clock;
+pub mod widgets;
+pub mod popup;
+pub mod navigation;
+
+/// A popup that is shown, its id and parent are kept outside of it as it is borrowed while handling events
+struct OpenPopup {
+    id: PopupId,
+    parent: Option<PopupId>,
+    modal: bool,
+    layer: Layer,
+    popup: Rc<RefCell<Popup>>,
+}
 
 pub struct Framework {
     pub(super) current_screen: Box<dyn ScreenTrait>,
@@ -26,14 +54,30 @@ pub struct Framework {
     element_passes: HashMap<RenderPass, u32>,
     screen_layer: Layer,
     layers: Vec<Layer>,
+    screen_transition: Transition,
+    history: Vec<ScreenEntry>,
+    navigating: Vec<Navigation>,
+    transition: Option<ActiveTransition>,
+    transition_layer: Layer,
+    transition_duration: f32,
+    transition_easing: Easing,
+    popups: Vec<OpenPopup>,
+    opening: Vec<Popup>,
+    closing: Vec<PopupId>,
     created_at: Instant,
-    last_pre_render: Instant,
+    clock: Clock,
     style: UnchangingRegistry,
+    theme: Option<Theme>,
+    /// Why the theme file last failed to reload, until it reloads again
+    theme_error: Option<String>,
+    style_sheet: StyleSheet,
+    style_sheet_version: u64,
     states: ChangingRegistry,
 
     current_layer_pass: (RenderPass, usize),
 
     pre_delta: f32,
+    frame: u64,
 }
 
 impl Framework {
@@ -44,27 +88,129 @@ impl Framework {
             element_passes: HashMap::new(),
             screen_layer: Layer::new((32, 32)),
             layers: vec![],
+            screen_transition: Transition::None,
+            history: vec![],
+            navigating: vec![],
+            transition: None,
+            transition_layer: Layer::new((32, 32)),
+            transition_duration: 0.3,
+            transition_easing: Easing::EASE_IN_OUT,
+            popups: vec![],
+            opening: vec![],
+            closing: vec![],
             created_at: Instant::now(),
-            last_pre_render: Instant::now(),
+            clock: Clock::real(),
             style: UnchangingRegistry::new(),
+            theme: None,
+            theme_error: None,
+            style_sheet: StyleSheet::new(),
+            style_sheet_version: 0,
             states: ChangingRegistry::new(),
             current_layer_pass: (RenderPass::Main, 0),
             pre_delta: 0.0,
+            frame: 0,
         };
         fr.set_screen(DefaultScreen::new());
         fr
     }
 
     pub unsafe fn mark_layer_dirty(&mut self, area: impl Into<Vec4>) {
-        if self.current_layer_pass.1 == 0 {
-            self.screen_layer.mark_dirty(&self.current_layer_pass.0, area);
+        let pass = self.current_layer_pass.0.clone();
+        self.layer(self.current_layer_pass.1).mark_dirty(&pass, area);
+    }
+
+    /// The screen layer, followed by the layers returned by the screen, the layers of the open popups,
+    /// then the layer and layers of the screen that is moving out during a transition.
+    ///
+    /// Indices past the existing layers, such as ones of a transition that already finished,
+    /// fall back to the screen layer.
+    fn layer(&mut self, index: usize) -> &mut Layer {
+        let popups = self.layers.len() + self.popups.len();
+        let leaving = self.leaving_screen().map_or(0, |entry| entry.layers.len());
+        if index == 0 {
+            &mut self.screen_layer
+        } else if index <= self.layers.len() {
+            &mut self.layers[index - 1]
+        } else if index <= popups {
+            &mut self.popups[index - self.layers.len() - 1].layer
+        } else if index == popups + 1 && self.transition.is_some() {
+            &mut self.transition_layer
+        } else if index > popups + 1 && index - popups - 2 < leaving {
+            &mut self.leaving_screen().unwrap().layers[index - popups - 2]
         } else {
-            self.layers.get_mut(self.current_layer_pass.1 - 1).unwrap().mark_dirty(&self.current_layer_pass.0, area);
+            &mut self.screen_layer
+        }
+    }
+
+    fn leaving_screen(&mut self) -> Option<&mut ScreenEntry> {
+        match &mut self.transition {
+            Some(ActiveTransition { leaving: Leaving::Dropped(entry), .. }) => Some(entry),
+            Some(ActiveTransition { leaving: Leaving::Below, .. }) => self.history.last_mut(),
+            None => None,
         }
     }
 
     pub fn set_styles(&mut self, style: UnchangingRegistry) {
         self.style = style;
+        self.theme = None;
+        self.theme_error = None;
+    }
+
+    /// Loads the styles from a [`Theme`] file.
+    ///
+    /// In debug builds, the file is watched and the styles are reloaded when it changes.
+    pub fn load_theme(&mut self, path: impl AsRef<std::path::Path>) -> Result<(), String> {
+        let theme = Theme::load(path)?;
+        self.style = theme.styles().clone();
+        self.set_style_sheet(theme.style_sheet().clone());
+        self.theme = Some(theme);
+        self.theme_error = None;
+        Ok(())
+    }
+
+    /// Sets the rules that [`Element`]s and [`Container`]s resolve their styles from
+    ///
+    /// [`Element`]: element::Element
+    /// [`Container`]: element::container::Container
+    pub fn set_style_sheet(&mut self, style_sheet: StyleSheet) {
+        self.style_sheet = style_sheet;
+        self.style_sheet_version += 1;
+    }
+
+    pub fn style_sheet(&self) -> &StyleSheet {
+        &self.style_sheet
+    }
+
+    /// Incremented every time the style sheet is replaced
+    pub fn style_sheet_version(&self) -> u64 {
+        self.style_sheet_version
+    }
+
+    pub fn theme(&mut self) -> Option<&mut Theme> {
+        self.theme.as_mut()
+    }
+
+    /// The error of the last reload of the theme file, if it failed and the file was not fixed since.
+    /// The styles stay the ones from before the failed reload.
+    pub fn theme_error(&self) -> Option<&str> {
+        self.theme_error.as_deref()
+    }
+
+    fn poll_theme(&mut self) {
+        if let Some(theme) = &mut self.theme {
+            match theme.poll() {
+                None => {}
+                Some(Ok(())) => {
+                    self.style = theme.styles().clone();
+                    self.style_sheet = theme.style_sheet().clone();
+                    self.style_sheet_version += 1;
+                    // Redraws everything with the new styles
+                    self.created_at = Instant::now();
+                    self.theme_error = None;
+                }
+                Some(Err(err)) => self.theme_error = Some(err),
+            }
+        }
     }
 
     pub fn states(&mut self) -> &mut ChangingRegistry {
@@ -85,7 +231,7 @@ impl Framework {
     }
 
     pub unsafe fn tick_render_pass(&mut self, render_pass: &RenderPass) -> TickResult {
-        if self.created_at_elapsed() || self.screen_animations.has_changed() {
+        if self.created_at_elapsed() || self.screen_animations.has_changed() || self.transition.is_some() {
             return TickResult::RedrawLayout
         } else {
             let v = self.current_screen.tick(render_pass);
@@ -99,6 +245,12 @@ impl Framework {
                 return res;
             }
         }
+        for popup in &mut self.popups {
+            let res = popup.layer.tick(render_pass);
+            if !res.is_valid() {
+                return res;
+            }
+        }
         TickResult::Valid
     }
 
@@ -107,7 +259,8 @@ impl Framework {
     }
 
     pub unsafe fn tick_render_all(&mut self) -> TickResult {
-        if self.created_at_elapsed() || self.screen_animations.has_changed() {
+        // Both screens move during a transition, so everything is redrawn
+        if self.created_at_elapsed() || self.screen_animations.has_changed() || self.transition.is_some() {
             return TickResult::RedrawLayout
         }
         for rp in RenderPass::all() {
@@ -121,6 +274,10 @@ impl Framework {
 
     fn reset(&mut self) {
         self.layers = Vec::new();
+        self.transition = None;
+        self.popups = Vec::new();
+        self.opening = Vec::new();
+        self.closing = Vec::new();
         self.created_at = Instant::now();
         self.screen_animations = AnimationRegistry::new();
     }
@@ -137,102 +294,412 @@ impl Framework {
     // }
 
     pub unsafe fn current_framebuffer(&mut self) -> &mut Framebuffer {
-        self.layers.get_mut(self.current_layer_pass.1 - 1).unwrap().fb(&self.current_layer_pass.0)
+        let pass = self.current_layer_pass.0.clone();
+        self.layer(self.current_layer_pass.1).fb(&pass)
     }
 
-    pub unsafe fn event(&mut self, event: Event) {
-        match &event {
-            Event::PreRender => {
-                self.pre_delta = self.last_pre_render.elapsed().as_secs_f64() as f32;
-                self.last_pre_render = Instant::now();
-                self.current_screen.handle(&event);
+    /// Shows a [`Popup`] above every layer, starting with the next event
+    pub fn open_popup(&mut self, popup: Popup) -> PopupId {
+        let id = popup.id();
+        self.opening.push(popup);
+        id
+    }
+
+    /// Closes a popup and the ones opened from it, before the next event
+    pub fn close_popup(&mut self, id: PopupId) {
+        self.closing.push(id);
+    }
+
+    pub fn close_popups(&mut self) {
+        let ids: Vec<PopupId> = self.popups.iter().map(|p| p.id).collect();
+        self.closing.extend(ids);
+        self.opening.clear();
+    }
+
+    pub fn is_popup_open(&self, id: PopupId) -> bool {
+        !self.closing.contains(&id) && (
+            self.popups.iter().any(|p| p.id == id) || self.opening.iter().any(|p| p.id() == id)
+        )
+    }
+
+    /// The popup that was opened last, which receives keyboard navigation
+    pub fn top_popup(&self) -> Option<PopupId> {
+        self.popups.last().map(|p| p.id)
+    }
+
+    /// Shows `content` centered over a dimmed window, blocking the input to everything else until it is closed
+    pub fn open_modal<H: UIHandler + 'static>(&mut self, content: H) -> PopupId {
+        self.open_popup(Popup::new(content, Vec4::xywh(0.0, 0.0, 0.0, 0.0)).modal(true))
+    }
+
+    pub fn has_modal(&self) -> bool {
+        self.popups.iter().any(|p| p.modal)
+    }
+
+    /// The index of the top-most popup under `pos`
+    fn popup_at(&self, pos: &Vec2<f32>) -> Option<usize> {
+        self.popups.iter().rposition(|p| pos.intersects(&p.popup.borrow().bounds()))
+    }
+
+    unsafe fn update_popups(&mut self) {
+        if self.closing.is_empty() && self.opening.is_empty() {
+            return;
+        }
+        let mut closing = mem::take(&mut self.closing);
+        // Popups close along with the ones they were opened from
+        loop {
+            let children: Vec<PopupId> = self.popups.iter()
+                .filter(|p| p.parent.is_some_and(|parent| closing.contains(&parent)) && !closing.contains(&p.id))
+                .map(|p| p.id)
+                .collect();
+            if children.is_empty() {
+                break;
             }
-            Event::Render(pass) => {
-                self.current_layer_pass = (pass.clone(), 0);
-                let (parent_fb, parent_tex) = self.screen_layer.fb(pass).bind();
-
-                let v = self.current_screen.tick(pass);
-                let render = !v.is_valid() || self.created_at_elapsed() || self.screen_animations.has_changed();
-                if render {
-                    Framebuffer::clear_current();
-                    self.screen_layer.pre_render_pass(pass);
-                    // if parent != 0 {
-                    //     context().fb_manager().fb(parent as u32).copy(fb.id());
-                    // }
-
-                    self.current_screen.handle(&event);
-
-                    self.screen_layer.fb(pass).unbind();
+            closing.extend(children);
+        }
+        self.opening.retain(|p| !closing.contains(&p.id()) && !p.parent_id().is_some_and(|parent| closing.contains(&parent)));
+
+        let (closed, open): (Vec<_>, Vec<_>) = mem::take(&mut self.popups).into_iter()
+            .partition(|p| closing.contains(&p.id));
+        self.popups = open;
+        for popup in mem::take(&mut self.opening) {
+            let (id, parent, modal) = (popup.id(), popup.parent_id(), popup.is_modal());
+            let (handler, popup) = UIHandlerRef::new(popup);
+            let mut layer = Layer::new((32, 32));
+            layer.add(handler);
+            self.popups.push(OpenPopup { id, parent, modal, layer, popup });
+        }
+        for open in closed {
+            open.popup.borrow_mut().closed();
+        }
+        // Redraws everything under the popups that changed
+        self.created_at = Instant::now();
+    }
+
+    /// Lets the popups take input before the screen, returns `true` if only the popups should get the event
+    unsafe fn popup_input(&mut self, event: &Event) -> bool {
+        let pos = *context().window().mouse().pos();
+        let modal = self.has_modal();
+        let only_popups = match event {
+            Event::MouseClick(_, Action::Press) => {
+                if self.popup_at(&pos).is_some() {
+                    true
+                } else {
+                    let outside: Vec<PopupId> = self.popups.iter()
+                        .filter(|p| p.popup.borrow().closes_on_outside())
+                        .map(|p| p.id)
+                        .collect();
+                    // The click that closes popups is not passed on
+                    self.closing.extend(&outside);
+                    return modal || !outside.is_empty();
                 }
+            }
+            Event::MouseClick(_, _) | Event::Scroll(_, _) => modal || self.popup_at(&pos).is_some(),
+            Event::Keyboard(Key::Escape, Action::Press, _) => {
+                match self.popups.iter().rev().find(|p| p.popup.borrow().closes_on_escape()) {
+                    None => modal,
+                    Some(open) => {
+                        self.closing.push(open.id);
+                        return true;
+                    }
+                }
+            }
+            Event::Keyboard(..) | Event::Char(_) => modal || self.popups.iter().any(|p| p.popup.borrow().grabs_keyboard()),
+            _ => false,
+        };
+        if only_popups {
+            for open in &mut self.popups {
+                for e in open.layer.elements() {
+                    e.handle(event);
+                }
+            }
+        }
+        only_popups
+    }
 
-                self.screen_layer.copy_bind_rects(pass, parent_fb as u32, parent_tex as u32, render);
-                BindFramebuffer(FRAMEBUFFER, parent_fb as u32);
-            },
-            _ => self.current_screen.handle(&event),
-        }
-        let force = self.created_at_elapsed();
-        let mut i = 1;
-        for layer in &mut self.layers {
-            match &event {
-                Event::Render(pass) => {
-                    self.current_layer_pass = (pass.clone(), i);
-                    let mut rendered = false;
-                    let (mut parent_fb, mut parent_tex) = (0, 0);
-                    {
-                        let layer_fb = layer.fb(pass);
-                        (parent_fb, parent_tex) = layer_fb.bind();
+    pub unsafe fn event(&mut self, event: Event) {
+        self.update_navigation();
+        self.update_popups();
+        if !self.popups.is_empty() && self.popup_input(&event) {
+            return;
+        }
+        // Screens do not take input while they move
+        if self.transition.is_some() && matches!(event, Event::MouseClick(..) | Event::Scroll(..) | Event::Keyboard(..) | Event::Char(_)) {
+            return;
+        }
+        // Elements under a popup, or anywhere behind a modal, should not be hovered
+        let mouse = *context().window().mouse().pos();
+        let covered = event.is_prerender() && (self.popup_at(&mouse).is_some() || self.has_modal());
+        context().window().mouse.covered = covered;
+        match &event {
+            Event::Render(pass) => self.render(pass, &event),
+            _ => {
+                if let Event::PreRender = event {
+                    self.pre_delta = self.clock.tick();
+                    self.frame += 1;
+                    animation::begin_frame(self.frame, self.pre_delta);
+                    if cfg!(debug_assertions) {
+                        self.poll_theme();
                     }
-                    {
-                        // println!("{}", layer.should_render(pass));
-                        let res = layer.tick(pass);
-                        if !res.is_valid() || force {
-                            rendered = true;
-                            layer.pre_render_pass(pass);
-                            // println!("did render layer {:?}", pass);
-                            Framebuffer::clear_current();
-
-                            for e in layer.elements() {
-                                e.handle(&event);
-                            }
-                        }
-                        let layer_fb = layer.fb(pass);
-                        layer_fb.unbind();
+                    self.update_transition();
+                }
+                self.current_screen.handle(&event);
+                for i in 0..self.layers.len() + self.popups.len() {
+                    if covered && i == self.layers.len() {
+                        context().window().mouse.covered = false;
                     }
-                    // Finish();
-                    // let st = Instant::now();
-                    layer.copy_bind_rects(pass, parent_fb as u32, parent_tex as u32, rendered);
-                    // Finish();
-                    // let et = Instant::now();
-                    // println!("l {:?}", et - st);
-                    // layer_fb.copy_bind(parent_fb as u32, parent_tex as u32);
-                },
-                _ => {
+                    let layer = if i < self.layers.len() {
+                        &mut self.layers[i]
+                    } else {
+                        &mut self.popups[i - self.layers.len()].layer
+                    };
                     for e in layer.elements() {
                         e.handle(&event);
-                        match event {
-                            Event::PostRender => {
-                                match e.animations() {
-                                    None => {}
-                                    Some(mut reg) => { reg.post(); }
-                                }
-                            },
-                            _ => {}
+                        if let Event::PostRender = event {
+                            if let Some(mut reg) = e.animations() {
+                                reg.post();
+                            }
                         }
                     }
                 }
             }
-            i += 1;
         }
-        match &event {
-            Event::PostRender => {
-                self.screen_animations.post();
-                self.states.update();
+        context().window().mouse.covered = false;
+        if let Event::PostRender = event {
+            self.screen_animations.post();
+            self.states.update();
+        }
+    }
+
+    unsafe fn render(&mut self, pass: &RenderPass, event: &Event) {
+        let force = self.created_at_elapsed() || self.transition.is_some();
+        match &self.transition {
+            None => self.render_current(pass, event, force, Vec2::zero()),
+            Some(transition) => {
+                let progress = transition.progress.value();
+                let size = context().window().bounds().wh();
+                let (leaving, entering) = transition.transition.offsets(progress, transition.reverse, size);
+                if transition.entering_on_top() {
+                    self.render_leaving(pass, event, leaving);
+                    self.render_current(pass, event, force, entering);
+                } else {
+                    self.render_current(pass, event, force, entering);
+                    self.render_leaving(pass, event, leaving);
+                }
+            }
+        }
+        // Popups are above every screen
+        for i in 0..self.popups.len() {
+            self.current_layer_pass = (pass.clone(), self.layers.len() + i + 1);
+            Self::render_layer(&mut self.popups[i].layer, pass, event, force, Vec2::zero());
+        }
+    }
+
+    unsafe fn render_current(&mut self, pass: &RenderPass, event: &Event, force: bool, offset: Vec2<f32>) {
+        self.current_layer_pass = (pass.clone(), 0);
+        let force_screen = force || self.screen_animations.has_changed();
+        Self::render_screen(self.current_screen.as_mut(), &mut self.screen_layer, pass, event, force_screen, offset);
+        for i in 0..self.layers.len() {
+            self.current_layer_pass = (pass.clone(), i + 1);
+            Self::render_layer(&mut self.layers[i], pass, event, force, offset);
+        }
+    }
+
+    /// Renders the screen that is moving out, using its own layer as the screen layer is used by the current screen
+    unsafe fn render_leaving(&mut self, pass: &RenderPass, event: &Event, offset: Vec2<f32>) {
+        let first = self.layers.len() + self.popups.len();
+        let leaving = match &mut self.transition {
+            Some(ActiveTransition { leaving: Leaving::Dropped(entry), .. }) => entry,
+            Some(ActiveTransition { leaving: Leaving::Below, .. }) => match self.history.last_mut() {
+                Some(entry) => entry,
+                None => return,
             },
-            _ => {}
+            None => return,
+        };
+        self.current_layer_pass = (pass.clone(), first + 1);
+        Self::render_screen(leaving.screen.as_mut(), &mut self.transition_layer, pass, event, true, offset);
+        for (i, layer) in leaving.layers.iter_mut().enumerate() {
+            self.current_layer_pass = (pass.clone(), first + i + 2);
+            Self::render_layer(layer, pass, event, true, offset);
+        }
+    }
+
+    /// Renders a screen into `screen_layer` if needed, then draws the layer over the bound framebuffer
+    unsafe fn render_screen(screen: &mut dyn ScreenTrait, screen_layer: &mut Layer, pass: &RenderPass, event: &Event, force: bool, offset: Vec2<f32>) {
+        let (parent_fb, parent_tex) = screen_layer.fb(pass).bind();
+
+        let v = screen.tick(pass);
+        let render = !v.is_valid() || force;
+        if render {
+            Framebuffer::clear_current();
+            screen_layer.pre_render_pass(pass);
+            Self::with_offset(offset, || screen.handle(event));
+            screen_layer.fb(pass).unbind();
         }
+
+        screen_layer.copy_bind_rects(pass, parent_fb as u32, parent_tex as u32, render);
+        BindFramebuffer(FRAMEBUFFER, parent_fb as u32);
+    }
+
+    /// Renders the elements of a layer if any changed, then draws the layer over the bound framebuffer
+    unsafe fn render_layer(layer: &mut Layer, pass: &RenderPass, event: &Event, force: bool, offset: Vec2<f32>) {
+        let (parent_fb, parent_tex) = layer.fb(pass).bind();
+        let mut rendered = false;
+        let res = layer.tick(pass);
+        if !res.is_valid() || force {
+            rendered = true;
+            layer.pre_render_pass(pass);
+            Framebuffer::clear_current();
+            Self::with_offset(offset, || {
+                for e in layer.elements() {
+                    e.handle(event);
+                }
+            });
+        }
+        layer.fb(pass).unbind();
+        layer.copy_bind_rects(pass, parent_fb as u32, parent_tex as u32, rendered);
+    }
+
+    unsafe fn with_offset<F: FnOnce()>(offset: Vec2<f32>, render: F) {
+        let moved = offset != Vec2::zero();
+        if moved {
+            context().renderer().stack().push(State::Translate(offset.x, offset.y));
+        }
+        render();
+        if moved {
+            context().renderer().stack().pop();
+        }
+    }
+
+    /// Shows `screen` over the current one, which is kept alive until `screen` is popped.
+    ///
+    /// Like every change to the screen stack, it happens before the next event.
+    pub fn push_screen<S>(&mut self, screen: S, transition: Transition) where S: ScreenTrait + 'static {
+        self.navigating.push(Navigation::Push(Box::new(screen), transition));
+    }
+
+    /// Goes back to the previous screen, dropping the current one and playing its transition in reverse.
+    ///
+    /// Returns `false` if there is no previous screen.
+    pub fn pop_screen(&mut self) -> bool {
+        if self.queued_depth() == 0 {
+            return false;
+        }
+        self.navigating.push(Navigation::Pop);
+        true
+    }
+
+    /// Shows `screen` instead of the current one, which is dropped, keeping the screens below
+    pub fn replace_screen<S>(&mut self, screen: S, transition: Transition) where S: ScreenTrait + 'static {
+        self.navigating.push(Navigation::Replace(Box::new(screen), transition));
+    }
+
+    /// The number of screens below the current one once the queued navigation is applied
+    fn queued_depth(&self) -> usize {
+        self.navigating.iter().fold(self.history.len(), |depth, navigation| match navigation {
+            Navigation::Push(..) => depth + 1,
+            Navigation::Pop => depth.saturating_sub(1),
+            Navigation::Replace(..) => depth,
+        })
     }
 
+    /// Applies the queued changes to the screen stack, while no screen is handling an event
+    unsafe fn update_navigation(&mut self) {
+        if self.navigating.is_empty() {
+            return;
+        }
+        let mut popped = false;
+        for navigation in mem::take(&mut self.navigating) {
+            match navigation {
+                Navigation::Push(screen, transition) => {
+                    let previous = self.swap_screen(screen, transition);
+                    self.history.push(previous);
+                    self.start_transition(transition, false, Leaving::Below);
+                }
+                Navigation::Pop => {
+                    let Some(previous) = self.history.pop() else {
+                        continue;
+                    };
+                    let current = self.swap_entry(previous);
+                    self.start_transition(current.transition, true, Leaving::Dropped(current));
+                    popped = true;
+                }
+                Navigation::Replace(screen, transition) => {
+                    let previous = self.swap_screen(screen, transition);
+                    self.start_transition(transition, false, Leaving::Dropped(previous));
+                }
+            }
+        }
+        // The screen may have missed a resize while it was covered
+        if popped {
+            let size = context().window().bounds().wh();
+            self.event(Event::Resize(size.x, size.y));
+        }
+    }
+
+    /// The number of screens below the current one
+    pub fn screen_depth(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// How long screen transitions take in seconds, and how they ease
+    pub fn set_transition_timing(&mut self, duration: f32, easing: Easing) {
+        self.transition_duration = duration;
+        self.transition_easing = easing;
+    }
+
+    unsafe fn swap_screen(&mut self, screen: Box<dyn ScreenTrait>, transition: Transition) -> ScreenEntry {
+        let previous = self.swap_entry(ScreenEntry {
+            screen,
+            layers: vec![],
+            animations: AnimationRegistry::new(),
+            transition,
+        });
+        self.layers = self.current_screen.init();
+        previous
+    }
+
+    /// Makes `entry` the current screen, returning the one it replaced.
+    /// Popups belong to the screen they were opened on, so they are closed.
+    unsafe fn swap_entry(&mut self, entry: ScreenEntry) -> ScreenEntry {
+        self.transition = None;
+        self.close_popups();
+        self.update_popups();
+        self.created_at = Instant::now();
+        ScreenEntry {
+            screen: mem::replace(&mut self.current_screen, entry.screen),
+            layers: mem::replace(&mut self.layers, entry.layers),
+            animations: mem::replace(&mut self.screen_animations, entry.animations),
+            transition: mem::replace(&mut self.screen_transition, entry.transition),
+        }
+    }
+
+    fn start_transition(&mut self, transition: Transition, reverse: bool, leaving: Leaving) {
+        if transition != Transition::None && self.transition_duration > 0.0 {
+            self.transition = Some(ActiveTransition::new(transition, reverse, leaving, self.transition_duration, self.transition_easing));
+        }
+    }
+
+    fn update_transition(&mut self) {
+        if let Some(transition) = &mut self.transition {
+            // A finished transition is kept for one more frame, which draws the screens where they stop
+            if transition.progress.is_finished() {
+                self.transition = None;
+            } else {
+                transition.progress.advance(self.pre_delta);
+            }
+        }
+    }
+
+    /// Replaces every screen with `screen`, dropping the navigation history
     pub unsafe fn set_screen<S>(&mut self, screen: S) where S: ScreenTrait + 'static {
+        self.history.clear();
+        self.navigating.clear();
+        self.screen_transition = Transition::None;
         self.reset();
         self.current_screen = Box::new(screen);
         self.layers = self.current_screen.init();
@@ -242,7 +709,23 @@ impl Framework {
         &mut self.screen_animations
     }
 
+    /// Switches how frame deltas are measured, see [`ClockMode`]
+    pub fn set_clock(&mut self, mode: ClockMode) {
+        self.clock.set_mode(mode);
+    }
+
+    /// The clock that [`pre_delta`](Framework::pre_delta) is taken from,
+    /// use [`Clock::advance`] to step a manual clock
+    pub fn clock(&mut self) -> &mut Clock {
+        &mut self.clock
+    }
+
     pub fn pre_delta(&self) -> f32 {
         self.pre_delta
     }
+
+    /// The number of frames started so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
 }
\ No newline at end of file
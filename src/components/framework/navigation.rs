@@ -0,0 +1,122 @@
+use crate::components::framework::animation::{Animation, AnimationRegistry, Easing};
+use crate::components::framework::layer::Layer;
+use crate::components::framework::screen::ScreenTrait;
+use crate::components::spatial::vec2::Vec2;
+
+/// The side of the window a screen comes in from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Edge {
+    /// The offset that puts a screen of `size` just outside of the window on this side
+    fn outside(&self, size: Vec2<f32>) -> Vec2<f32> {
+        match self {
+            Edge::Left => Vec2::new(-size.x, 0.0),
+            Edge::Right => Vec2::new(size.x, 0.0),
+            Edge::Top => Vec2::new(0.0, -size.y),
+            Edge::Bottom => Vec2::new(0.0, size.y),
+        }
+    }
+}
+
+/// How screens move when navigating between them.
+///
+/// Popping a screen plays the transition it was pushed with in reverse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transition {
+    None,
+    /// Both screens move together, the new one coming in from the edge and pushing the old one out
+    Slide(Edge),
+    /// The new screen moves in from the edge over the old one, which stays in place
+    Cover(Edge),
+}
+
+impl Transition {
+    /// The offsets of the leaving and the entering screen after `progress`, from 0 to 1, of the transition.
+    ///
+    /// When `reverse`, the leaving screen is the one that was pushed and goes back where it came from.
+    pub fn offsets(&self, progress: f32, reverse: bool, size: Vec2<f32>) -> (Vec2<f32>, Vec2<f32>) {
+        let zero = Vec2::new(0.0, 0.0);
+        let scaled = |v: Vec2<f32>, t: f32| Vec2::new(v.x * t, v.y * t);
+        match (self, reverse) {
+            (Transition::None, _) => (zero, zero),
+            (Transition::Slide(edge), false) => (scaled(edge.outside(size), -progress), scaled(edge.outside(size), 1.0 - progress)),
+            (Transition::Slide(edge), true) => (scaled(edge.outside(size), progress), scaled(edge.outside(size), progress - 1.0)),
+            (Transition::Cover(edge), false) => (zero, scaled(edge.outside(size), 1.0 - progress)),
+            (Transition::Cover(edge), true) => (scaled(edge.outside(size), progress), zero),
+        }
+    }
+}
+
+/// A screen with everything that is kept alive while other screens are pushed over it
+pub(super) struct ScreenEntry {
+    pub screen: Box<dyn ScreenTrait>,
+    pub layers: Vec<Layer>,
+    pub animations: AnimationRegistry,
+    /// The transition the screen was shown with, played in reverse when it is popped
+    pub transition: Transition,
+}
+
+/// A change to the screen stack, queued until the next event so no screen is dropped while it handles one
+pub(super) enum Navigation {
+    Push(Box<dyn ScreenTrait>, Transition),
+    Pop,
+    Replace(Box<dyn ScreenTrait>, Transition),
+}
+
+/// Where the screen that is moving out during a transition is kept
+pub(super) enum Leaving {
+    /// The last screen of the history, which was covered by a pushed screen
+    Below,
+    /// A screen that was popped or replaced, dropped once the transition ends
+    Dropped(ScreenEntry),
+}
+
+/// A transition that is playing, the entering screen is always the current one
+pub(super) struct ActiveTransition {
+    pub transition: Transition,
+    pub reverse: bool,
+    pub leaving: Leaving,
+    pub progress: Animation,
+}
+
+impl ActiveTransition {
+    pub fn new(transition: Transition, reverse: bool, leaving: Leaving, duration: f32, easing: Easing) -> Self {
+        ActiveTransition {
+            transition,
+            reverse,
+            leaving,
+            progress: Animation::timed(0.0, 1.0, duration, easing),
+        }
+    }
+
+    /// If the entering screen is drawn over the leaving one, which is not the case when uncovering a screen
+    pub fn entering_on_top(&self) -> bool {
+        !self.reverse
+    }
+}
+
+#[test]
+pub fn transition_offsets() {
+    let size = Vec2::new(800.0, 600.0);
+
+    let (leaving, entering) = Transition::Slide(Edge::Right).offsets(0.25, false, size);
+    assert_eq!((leaving.x, entering.x), (-200.0, 600.0));
+
+    // Going back, the pushed screen leaves to the right and the old one comes back from the left
+    let (leaving, entering) = Transition::Slide(Edge::Right).offsets(0.25, true, size);
+    assert_eq!((leaving.x, entering.x), (200.0, -600.0));
+
+    let (leaving, entering) = Transition::Cover(Edge::Bottom).offsets(0.5, false, size);
+    assert_eq!((leaving.y, entering.y), (0.0, 300.0));
+    let (leaving, entering) = Transition::Cover(Edge::Bottom).offsets(1.0, true, size);
+    assert_eq!((leaving.y, entering.y), (600.0, 0.0));
+
+    let (leaving, entering) = Transition::None.offsets(0.5, false, size);
+    assert_eq!((leaving.x, entering.y), (0.0, 0.0));
+}
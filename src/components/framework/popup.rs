@@ -0,0 +1,323 @@
+use crate::components::context::context;
+use crate::components::framework::animation::{Animated, AnimationRegistry, Easing};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::{LayoutContext, LayoutEvent};
+use crate::components::framework::ui_traits::{random_id, TickResult, UIHandler};
+use crate::components::render::color::{solid, Color};
+use crate::components::render::renderer::Renderable;
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+pub type PopupId = u64;
+
+/// Which side of its anchor a popup opens on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placement {
+    Below,
+    Above,
+    Right,
+    Left,
+    /// Centered over the anchor, like a dialog over the window
+    Over,
+}
+
+impl Placement {
+    pub fn opposite(&self) -> Placement {
+        match self {
+            Placement::Below => Placement::Above,
+            Placement::Above => Placement::Below,
+            Placement::Right => Placement::Left,
+            Placement::Left => Placement::Right,
+            Placement::Over => Placement::Over,
+        }
+    }
+}
+
+/// Positions something of `size` next to `anchor` on the side given by `placement`, `gap` away from it.
+///
+/// `align` places it along the anchor's edge, 0 lines up the left or top edges and 0.5 centers them.
+/// When it does not fit in `window` on that side but fits better on the opposite one, it is flipped,
+/// and it is then moved to stay inside the window. Returns the bounds and the side that was used.
+pub fn place(anchor: Vec4, size: Vec2<f32>, placement: Placement, align: f32, gap: f32, window: Vec4) -> (Vec4, Placement) {
+    let room = |placement: Placement| match placement {
+        Placement::Below => window.bottom() - anchor.bottom() - gap,
+        Placement::Above => anchor.top() - window.top() - gap,
+        Placement::Right => window.right() - anchor.right() - gap,
+        Placement::Left => anchor.left() - window.left() - gap,
+        Placement::Over => f32::INFINITY,
+    };
+    let needed = match placement {
+        Placement::Below | Placement::Above => size.y,
+        Placement::Right | Placement::Left => size.x,
+        Placement::Over => 0.0,
+    };
+    let placement = if room(placement) < needed && room(placement.opposite()) > room(placement) {
+        placement.opposite()
+    } else {
+        placement
+    };
+
+    let (x, y) = match placement {
+        Placement::Below => (anchor.left() + (anchor.width() - size.x) * align, anchor.bottom() + gap),
+        Placement::Above => (anchor.left() + (anchor.width() - size.x) * align, anchor.top() - gap - size.y),
+        Placement::Right => (anchor.right() + gap, anchor.top() + (anchor.height() - size.y) * align),
+        Placement::Left => (anchor.left() - gap - size.x, anchor.top() + (anchor.height() - size.y) * align),
+        Placement::Over => (anchor.left() + (anchor.width() - size.x) * align, anchor.top() + (anchor.height() - size.y) * align),
+    };
+    let x = x.min(window.right() - size.x).max(window.left());
+    let y = y.min(window.bottom() - size.y).max(window.top());
+    (Vec4::xywh(x, y, size.x, size.y), placement)
+}
+
+/// Converts bounds that are relative to the containers currently handling an event into window coordinates,
+/// which is what popups are anchored with
+pub unsafe fn window_bounds(bounds: Vec4) -> Vec4 {
+    let mut bounds = bounds;
+    bounds.offset(*context().renderer().stack().current_translate());
+    bounds
+}
+
+/// Content shown above everything else, on its own [`Layer`], next to an anchor.
+///
+/// Opened with [`Framework::open_popup()`]. It closes when clicking outside of it or pressing Escape,
+/// unless disabled, and closing it also closes the popups opened from it.
+///
+/// A modal popup is centered in the window over a dimmed backdrop, and while it is open
+/// the input only goes to the popups until it is closed.
+///
+/// [`Layer`]: super::layer::Layer
+/// [`Framework::open_popup()`]: super::Framework::open_popup
+pub struct Popup {
+    id: PopupId,
+    parent: Option<PopupId>,
+    anchor: Vec4,
+    placement: Placement,
+    align: f32,
+    gap: f32,
+    close_on_outside: bool,
+    close_on_escape: bool,
+    grab_keyboard: bool,
+    modal: bool,
+    backdrop: Color,
+
+    content: Box<dyn UIHandler>,
+    dim: Animated<f32>,
+    backdrop_rect: Option<Rect>,
+    on_close: Option<Box<dyn FnMut()>>,
+}
+
+impl Popup {
+    /// A popup opening below `anchor`, which is in window coordinates, see [`window_bounds()`]
+    pub fn new<H: UIHandler + 'static>(content: H, anchor: Vec4) -> Self {
+        Popup {
+            id: random_id(),
+            parent: None,
+            anchor,
+            placement: Placement::Below,
+            align: 0.0,
+            gap: 2.0,
+            close_on_outside: true,
+            close_on_escape: true,
+            grab_keyboard: true,
+            modal: false,
+            backdrop: Color::from_u32(0x99000000),
+            content: Box::new(content),
+            dim: Animated::new(0.0),
+            backdrop_rect: None,
+            on_close: None,
+        }
+    }
+
+    /// Uses an id reserved with [`random_id()`], for content that has to know the popup it is in
+    pub fn with_id(mut self, id: PopupId) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Where along the anchor the popup is, see [`place()`]
+    pub fn align(mut self, align: f32) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Closes this popup along with `parent`
+    pub fn parent(mut self, parent: PopupId) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    pub fn close_on_outside(mut self, close: bool) -> Self {
+        self.close_on_outside = close;
+        self
+    }
+
+    pub fn close_on_escape(mut self, close: bool) -> Self {
+        self.close_on_escape = close;
+        self
+    }
+
+    /// If keyboard events only go to the popups while it is open, `true` by default
+    pub fn grab_keyboard(mut self, grab: bool) -> Self {
+        self.grab_keyboard = grab;
+        self
+    }
+
+    /// Centers the popup in the window and blocks the input to everything below it, see [`Framework::open_modal()`]
+    ///
+    /// [`Framework::open_modal()`]: super::Framework::open_modal
+    pub fn modal(mut self, modal: bool) -> Self {
+        self.modal = modal;
+        if modal {
+            self.placement = Placement::Over;
+            self.align = 0.5;
+            self.close_on_outside = false;
+        }
+        self
+    }
+
+    /// The color the window is dimmed with behind a modal popup
+    pub fn backdrop(mut self, backdrop: Color) -> Self {
+        self.backdrop = backdrop;
+        self
+    }
+
+    pub fn on_close<F: FnMut() + 'static>(mut self, on_close: F) -> Self {
+        self.on_close = Some(Box::new(on_close));
+        self
+    }
+
+    pub fn id(&self) -> PopupId {
+        self.id
+    }
+
+    pub fn parent_id(&self) -> Option<PopupId> {
+        self.parent
+    }
+
+    pub fn anchor(&self) -> Vec4 {
+        self.anchor
+    }
+
+    pub fn set_anchor(&mut self, anchor: Vec4) {
+        self.anchor = anchor;
+    }
+
+    pub fn closes_on_outside(&self) -> bool {
+        self.close_on_outside
+    }
+
+    pub fn closes_on_escape(&self) -> bool {
+        self.close_on_escape
+    }
+
+    pub fn grabs_keyboard(&self) -> bool {
+        self.grab_keyboard
+    }
+
+    pub fn is_modal(&self) -> bool {
+        self.modal
+    }
+
+    pub fn content(&mut self) -> &mut Box<dyn UIHandler> {
+        &mut self.content
+    }
+
+    pub(super) fn closed(&mut self) {
+        if let Some(on_close) = &mut self.on_close {
+            on_close();
+        }
+    }
+}
+
+impl UIHandler for Popup {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        match event {
+            Event::Layout(LayoutEvent::Position(_)) => {
+                let window = context().window().bounds();
+                if self.modal {
+                    self.anchor = window;
+                }
+                let size = self.content.bounds().wh();
+                let (bounds, _) = place(self.anchor, size, self.placement, self.align, self.gap, window);
+                self.content.handle(&Event::Layout(LayoutEvent::Position(bounds.xy())))
+            }
+            Event::PreRender if self.modal => {
+                self.dim.animate_to(1.0, 8.0, Easing::Sin);
+                self.content.handle(event)
+            }
+            Event::Render(RenderPass::Main) if self.modal => {
+                let window = context().window().bounds();
+                let color = self.backdrop.set_alpha_f32(self.backdrop.alpha() * self.dim.value());
+                let rect = match &mut self.backdrop_rect {
+                    Some(rect) => rect,
+                    None => self.backdrop_rect.insert(Rect::new(window, solid(color))),
+                };
+                rect.set_bounds(&window);
+                rect.set_colors(solid(color));
+                rect.pre_render();
+                rect.render();
+                self.content.handle(event)
+            }
+            _ => self.content.handle(event),
+        }
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        // The backdrop is still fading in
+        if self.modal && self.dim.value() < 1.0 {
+            return TickResult::Redraw;
+        }
+        self.content.tick(render_pass)
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.content.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.content.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.content.layout_context()
+    }
+}
+
+#[test]
+pub fn placement() {
+    let window = Vec4::xywh(0, 0, 800, 600);
+    let size = Vec2::new(100.0, 50.0);
+
+    let (bounds, side) = place(Vec4::xywh(10, 10, 80, 20), size, Placement::Below, 0.0, 2.0, window);
+    assert_eq!(side, Placement::Below);
+    assert_eq!(bounds, Vec4::xywh(10, 32, 100, 50));
+
+    // No room below, so it flips above
+    let (bounds, side) = place(Vec4::xywh(10, 570, 80, 20), size, Placement::Below, 0.0, 2.0, window);
+    assert_eq!(side, Placement::Above);
+    assert_eq!(bounds.y(), 518.0);
+
+    // Centered and pushed back inside on the left
+    let (bounds, side) = place(Vec4::xywh(0, 100, 20, 20), size, Placement::Above, 0.5, 4.0, window);
+    assert_eq!(side, Placement::Above);
+    assert_eq!(bounds, Vec4::xywh(0, 46, 100, 50));
+
+    let (bounds, side) = place(Vec4::xywh(750, 100, 40, 20), size, Placement::Right, 0.0, 0.0, window);
+    assert_eq!(side, Placement::Left);
+    assert_eq!(bounds.x(), 650.0);
+
+    let (bounds, side) = place(window, size, Placement::Over, 0.5, 0.0, window);
+    assert_eq!(side, Placement::Over);
+    assert_eq!(bounds, Vec4::xywh(350, 275, 100, 50));
+}
@@ -1,4 +1,9 @@
+use std::any::{type_name, Any};
 use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver};
 
 use crate::components::framework::changing::Changing;
 use crate::components::render::color::{Color, ToColor};
@@ -16,11 +21,195 @@ pub enum State {
     None,
 }
 
+impl State {
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            State::Color(_) => "Color",
+            State::Vec4(_) => "Vec4",
+            State::Vec2(_) => "Vec2",
+            State::Number(_) => "Number",
+            State::String(_) => "String",
+            State::Bool(_) => "Bool",
+            State::None => "None",
+        }
+    }
+}
+
+/// Converts a value to and from a [`State`], allowing it to be stored in a [`StateRegistry`]
+/// while still being accessed as its own type
+pub trait StateValue: Sized + Clone {
+    fn into_state(self) -> State;
+
+    /// Returns [`None`] if the [`State`] is not of this type
+    fn from_state(state: &State) -> Option<Self>;
+}
+
+impl StateValue for Color {
+    fn into_state(self) -> State { State::Color(self) }
+    fn from_state(state: &State) -> Option<Self> {
+        match state {
+            State::Color(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl StateValue for Vec4 {
+    fn into_state(self) -> State { State::Vec4(self) }
+    fn from_state(state: &State) -> Option<Self> {
+        match state {
+            State::Vec4(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl StateValue for Vec2<f32> {
+    fn into_state(self) -> State { State::Vec2(self) }
+    fn from_state(state: &State) -> Option<Self> {
+        match state {
+            State::Vec2(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl StateValue for f32 {
+    fn into_state(self) -> State { State::Number(self) }
+    fn from_state(state: &State) -> Option<Self> {
+        match state {
+            State::Number(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl StateValue for String {
+    fn into_state(self) -> State { State::String(self) }
+    fn from_state(state: &State) -> Option<Self> {
+        match state {
+            State::String(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl StateValue for bool {
+    fn into_state(self) -> State { State::Bool(self) }
+    fn from_state(state: &State) -> Option<Self> {
+        match state {
+            State::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+/// A typed key for a [`StateRegistry`].
+///
+/// Unlike the string keys, the type of the value is checked at compile time, and any
+/// `Clone + PartialEq` type can be stored, not only the [`State`] variants.
+///
+/// Values set through the string methods are stored as [`State`], so they can be accessed
+/// with a `StateKey<State>`.
+pub struct StateKey<T> {
+    name: String,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Clone + PartialEq + 'static> StateKey<T> {
+    pub fn new(name: impl ToString) -> Self {
+        StateKey {
+            name: name.to_string(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get<'a>(&self, registry: &'a impl StateRegistry) -> Option<&'a T> {
+        registry.get_key(self)
+    }
+
+    pub fn try_get<'a>(&self, registry: &'a impl StateRegistry) -> Result<&'a T, StateError> {
+        registry.try_get_key(self)
+    }
+
+    pub fn set(&self, registry: &mut impl StateRegistry, value: T) {
+        registry.set_key(self, value);
+    }
+}
+
+impl<T> Clone for StateKey<T> {
+    fn clone(&self) -> Self {
+        StateKey {
+            name: self.name.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for StateKey<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StateKey<{}>({:?})", type_name::<T>(), self.name)
+    }
+}
+
+/// Why a value could not be retrieved from a [`StateRegistry`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateError {
+    /// Nothing was set for the key
+    Missing(String),
+    /// A value was set for the key, but with a different type
+    Mismatch {
+        key: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl Display for StateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::Missing(key) => write!(f, "no state was set for '{}'", key),
+            StateError::Mismatch { key, expected, found } => write!(f, "state '{}' is a {}, but a {} was expected", key, found, expected),
+        }
+    }
+}
+
 pub trait StateRegistry {
-    fn set(&mut self, key: impl ToString, value: State);
+    fn set_key<T: Clone + PartialEq + 'static>(&mut self, key: &StateKey<T>, value: T);
 
-    fn get(&self, key: impl ToString) -> &State;
+    fn try_get_key<T: Clone + PartialEq + 'static>(&self, key: &StateKey<T>) -> Result<&T, StateError>;
 
+    fn get_key<T: Clone + PartialEq + 'static>(&self, key: &StateKey<T>) -> Option<&T> {
+        self.try_get_key(key).ok()
+    }
+
+    fn set(&mut self, key: impl ToString, value: State) {
+        self.set_key(&StateKey::new(key), value);
+    }
+
+    /// Returns [`State::None`] if the key is missing, or if it was set with a [`StateKey`] of another type
+    fn get(&self, key: impl ToString) -> &State {
+        self.try_get(key).unwrap_or(&State::None)
+    }
+
+    fn try_get(&self, key: impl ToString) -> Result<&State, StateError> {
+        self.try_get_key(&StateKey::new(key))
+    }
+
+    /// Gets a value set through the string methods as its own type
+    fn try_get_as<T: StateValue>(&self, key: impl ToString) -> Result<T, StateError> {
+        let key = key.to_string();
+        let state = self.try_get(&key)?;
+        T::from_state(state).ok_or_else(|| StateError::Mismatch {
+            key,
+            expected: type_name::<T>(),
+            found: state.variant_name(),
+        })
+    }
 
     fn set_vec4(&mut self, key: impl ToString, value: Vec4) {
         self.set(key, State::Vec4(value));
@@ -89,9 +278,85 @@ pub trait StateRegistry {
     }
 }
 
+/// A value stored in an [`UnchangingRegistry`]
+trait Entry: Any {
+    fn clone_entry(&self) -> Box<dyn Entry>;
+    fn type_name(&self) -> &'static str;
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Clone + PartialEq + 'static> Entry for T {
+    fn clone_entry(&self) -> Box<dyn Entry> {
+        Box::new(self.clone())
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Clone for Box<dyn Entry> {
+    fn clone(&self) -> Self {
+        self.clone_entry()
+    }
+}
+
+impl Debug for dyn Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.as_any().downcast_ref::<State>() {
+            Some(state) => state.fmt(f),
+            None => write!(f, "{}", self.type_name()),
+        }
+    }
+}
+
+/// A value stored in a [`ChangingRegistry`]
+trait ChangingEntry: Any {
+    fn changed(&self) -> bool;
+    fn update(&mut self);
+    fn type_name(&self) -> &'static str;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Clone + PartialEq + 'static> ChangingEntry for Changing<T> {
+    fn changed(&self) -> bool {
+        Changing::changed(self)
+    }
+
+    fn update(&mut self) {
+        Changing::update(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Debug for dyn ChangingEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.as_any().downcast_ref::<Changing<State>>() {
+            Some(state) => state.current().fmt(f),
+            None => write!(f, "{}", self.type_name()),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct UnchangingRegistry {
-    states: HashMap<String, State>,
+    states: HashMap<String, Box<dyn Entry>>,
 }
 
 impl UnchangingRegistry {
@@ -103,21 +368,64 @@ impl UnchangingRegistry {
 }
 
 impl StateRegistry for UnchangingRegistry {
-    fn set(&mut self, key: impl ToString, value: State) {
-        let key = key.to_string();
-        self.states.insert(key, value);
+    fn set_key<T: Clone + PartialEq + 'static>(&mut self, key: &StateKey<T>, value: T) {
+        self.states.insert(key.name.clone(), Box::new(value));
     }
 
-    fn get(&self, key: impl ToString) -> &State {
-        let key = key.to_string();
-        self.states.get(&key).unwrap_or(&State::None)
+    fn try_get_key<T: Clone + PartialEq + 'static>(&self, key: &StateKey<T>) -> Result<&T, StateError> {
+        let entry = self.states.get(&key.name).ok_or_else(|| StateError::Missing(key.name.clone()))?;
+        entry.as_any().downcast_ref::<T>().ok_or_else(|| StateError::Mismatch {
+            key: key.name.clone(),
+            expected: type_name::<T>(),
+            found: entry.type_name(),
+        })
+    }
+}
+
+/// What a subscriber of a [`ChangingRegistry`] is notified about
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subscription {
+    /// A single key of the registry
+    Key(String),
+    /// Any key of the sub registry with this name
+    Sub(String),
+    /// Any key of the registry, or any of its sub registries
+    All,
+}
+
+type SubscriberFn = Box<dyn FnMut(&ChangingRegistry, &str)>;
+
+struct Subscriber {
+    id: u64,
+    target: Subscription,
+    callback: SubscriberFn,
+}
+
+impl Debug for Subscriber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Subscriber({}, {:?})", self.id, self.target)
+    }
+}
+
+struct Computed {
+    dependencies: Vec<String>,
+    dirty: bool,
+    compute: Rc<dyn Fn(&mut ChangingRegistry)>,
+}
+
+impl Debug for Computed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Computed({:?}, dirty: {})", self.dependencies, self.dirty)
     }
 }
 
 #[derive(Debug)]
 pub struct ChangingRegistry {
-    states: HashMap<String, Changing<State>>,
+    states: HashMap<String, Box<dyn ChangingEntry>>,
     sub_registries: HashMap<String, ChangingRegistry>,
+    subscribers: Vec<Subscriber>,
+    computed: HashMap<String, Computed>,
+    next_subscriber: u64,
 }
 
 impl ChangingRegistry {
@@ -125,10 +433,47 @@ impl ChangingRegistry {
         ChangingRegistry {
             states: HashMap::new(),
             sub_registries: HashMap::new(),
+            subscribers: Vec::new(),
+            computed: HashMap::new(),
+            next_subscriber: 0,
         }
     }
 
+    /// Notifies subscribers of everything that changed, then clears the change flags.
+    ///
+    /// Called by the [`Framework`] once every frame, after rendering.
+    ///
+    /// [`Framework`]: crate::components::framework::Framework
     pub fn update(&mut self) {
+        self.refresh_computed();
+
+        if !self.subscribers.is_empty() {
+            let changed: Vec<String> = self.states.iter().filter(|(_, s)| s.changed()).map(|(k, _)| k.clone()).collect();
+            let changed_subs: Vec<String> = self.sub_registries.iter().filter(|(_, s)| s.changed_any()).map(|(k, _)| k.clone()).collect();
+
+            if !changed.is_empty() || !changed_subs.is_empty() {
+                // Taken so that subscribers can be given the registry
+                let mut subscribers = std::mem::take(&mut self.subscribers);
+                for subscriber in &mut subscribers {
+                    match &subscriber.target {
+                        Subscription::Key(key) => if changed.contains(key) {
+                            (subscriber.callback)(self, key);
+                        },
+                        Subscription::Sub(sub) => if changed_subs.contains(sub) {
+                            (subscriber.callback)(self, sub);
+                        },
+                        Subscription::All => {
+                            for key in changed.iter().chain(changed_subs.iter()) {
+                                (subscriber.callback)(self, key);
+                            }
+                        }
+                    }
+                }
+                subscribers.append(&mut self.subscribers);
+                self.subscribers = subscribers;
+            }
+        }
+
         for state in self.states.values_mut() {
             if state.changed() {
                 state.update()
@@ -139,6 +484,104 @@ impl ChangingRegistry {
         }
     }
 
+    /// Calls `callback` with the changed key (or sub registry name) whenever the target changes.
+    ///
+    /// Subscribers are notified once per frame, from [`update()`]. Returns an ID that can be used to [`unsubscribe()`].
+    ///
+    /// [`update()`]: ChangingRegistry::update
+    /// [`unsubscribe()`]: ChangingRegistry::unsubscribe
+    pub fn subscribe<F: FnMut(&ChangingRegistry, &str) + 'static>(&mut self, target: Subscription, callback: F) -> u64 {
+        let id = self.next_subscriber;
+        self.next_subscriber += 1;
+        self.subscribers.push(Subscriber {
+            id,
+            target,
+            callback: Box::new(callback),
+        });
+        id
+    }
+
+    /// Same as [`subscribe()`], but sends the changed key through a channel instead
+    ///
+    /// [`subscribe()`]: ChangingRegistry::subscribe
+    pub fn subscribe_channel(&mut self, target: Subscription) -> Receiver<String> {
+        let (sender, receiver) = channel();
+        self.subscribe(target, move |_, key| {
+            let _ = sender.send(key.to_string());
+        });
+        receiver
+    }
+
+    pub fn unsubscribe(&mut self, id: u64) {
+        self.subscribers.retain(|s| s.id != id);
+    }
+
+    /// Creates an entry whose value is derived from other keys of this registry.
+    ///
+    /// The value is computed again when it is read through [`computed()`] after one of its
+    /// `dependencies` changed, and at the latest in [`update()`], so subscribers are still notified.
+    ///
+    /// [`computed()`]: ChangingRegistry::computed
+    /// [`update()`]: ChangingRegistry::update
+    pub fn compute<T, F>(&mut self, key: &StateKey<T>, dependencies: &[&str], compute: F)
+        where T: Clone + PartialEq + 'static,
+              F: Fn(&ChangingRegistry) -> T + 'static,
+    {
+        let c_key = key.clone();
+        let compute: Rc<dyn Fn(&mut ChangingRegistry)> = Rc::new(move |registry| {
+            let value = compute(registry);
+            registry.set_key(&c_key, value);
+        });
+        compute(self);
+        self.computed.insert(key.name.clone(), Computed {
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            dirty: false,
+            compute,
+        });
+    }
+
+    /// Returns the value of a computed entry, computing it again first if any of its dependencies changed
+    pub fn computed<T: Clone + PartialEq + 'static>(&mut self, key: &StateKey<T>) -> Option<&T> {
+        self.refresh(&key.name);
+        self.get_key(key)
+    }
+
+    fn refresh(&mut self, key: &str) {
+        let compute = match self.computed.get_mut(key) {
+            Some(computed) if computed.dirty => {
+                computed.dirty = false;
+                computed.compute.clone()
+            }
+            _ => return,
+        };
+        // Dependencies that are computed themselves must be current first
+        let dependencies = self.computed.get(key).unwrap().dependencies.clone();
+        for dependency in &dependencies {
+            self.refresh(dependency);
+        }
+        compute(self);
+    }
+
+    fn refresh_computed(&mut self) {
+        let dirty: Vec<String> = self.computed.iter().filter(|(_, c)| c.dirty).map(|(k, _)| k.clone()).collect();
+        for key in dirty {
+            self.refresh(&key);
+        }
+    }
+
+    fn invalidate_dependents(&mut self, key: &str) {
+        for computed in self.computed.values_mut() {
+            if computed.dependencies.iter().any(|d| d == key) {
+                computed.dirty = true;
+            }
+        }
+    }
+
+    /// Returns whether any key of this registry, or any sub registry, has changed
+    pub fn changed_any(&self) -> bool {
+        self.changed_all() || self.sub_registries.values().any(|s| s.changed_any())
+    }
+
     pub fn changed_all(&self) -> bool {
         for state in self.states.values() {
             if state.changed() {
@@ -156,6 +599,17 @@ impl ChangingRegistry {
         false
     }
 
+    pub fn changed_key<T>(&self, key: &StateKey<T>) -> bool {
+        self.changed(&key.name)
+    }
+
+    /// Returns the value the key had before it was last changed
+    pub fn last_key<T: Clone + PartialEq + 'static>(&self, key: &StateKey<T>) -> Option<&T> {
+        self.states.get(&key.name)
+            .and_then(|entry| entry.as_any().downcast_ref::<Changing<T>>())
+            .map(|state| state.last())
+    }
+
     pub fn sub(&mut self, key: impl ToString) -> &mut ChangingRegistry {
         let key = key.to_string();
         if !self.sub_registries.contains_key(&key) {
@@ -166,24 +620,86 @@ impl ChangingRegistry {
 }
 
 impl StateRegistry for ChangingRegistry {
-    fn set(&mut self, key: impl ToString, value: State) {
-        let key = key.to_string();
-        if !self.states.contains_key(&key) {
-            self.states.insert(key.clone(), Changing::new(value.clone()));
+    fn set_key<T: Clone + PartialEq + 'static>(&mut self, key: &StateKey<T>, value: T) {
+        match self.states.get_mut(&key.name).and_then(|entry| entry.as_any_mut().downcast_mut::<Changing<T>>()) {
+            Some(state) => state.set(value),
+            // Missing, or previously set as another type
+            None => {
+                self.states.insert(key.name.clone(), Box::new(Changing::new(value)));
+            }
+        }
+        if self.changed(&key.name) {
+            self.invalidate_dependents(&key.name);
         }
-        let v = self.states.get_mut(&key).unwrap();
-        v.set(value);
     }
 
-    fn get(&self, key: impl ToString) -> &State {
-        let key = key.to_string();
-        match self.states.get(&key) {
-            None => &State::None,
-            Some(state) => state.current(),
+    fn try_get_key<T: Clone + PartialEq + 'static>(&self, key: &StateKey<T>) -> Result<&T, StateError> {
+        let entry = self.states.get(&key.name).ok_or_else(|| StateError::Missing(key.name.clone()))?;
+        match entry.as_any().downcast_ref::<Changing<T>>() {
+            Some(state) => Ok(state.current()),
+            None => Err(StateError::Mismatch {
+                key: key.name.clone(),
+                expected: type_name::<T>(),
+                found: entry.type_name(),
+            }),
         }
     }
 }
 
 pub trait StyleTrait {
     fn styles(&mut self) -> &mut UnchangingRegistry;
-}
\ No newline at end of file
+}
+#[test]
+pub fn typed_keys() {
+    let mut registry = ChangingRegistry::new();
+    let count: StateKey<u32> = StateKey::new("count");
+    count.set(&mut registry, 1);
+    count.set(&mut registry, 2);
+    assert_eq!(count.get(&registry), Some(&2));
+    assert!(registry.changed_key(&count));
+    assert_eq!(registry.last_key(&count), Some(&1));
+
+    registry.set_number("count_str", 1.0);
+    assert!(matches!(registry.try_get_key(&StateKey::<u32>::new("count_str")), Err(StateError::Mismatch { .. })));
+    assert_eq!(registry.try_get_as::<f32>("count_str"), Ok(1.0));
+    assert_eq!(registry.try_get("missing"), Err(StateError::Missing("missing".to_string())));
+}
+
+#[test]
+pub fn computed_and_subscriptions() {
+    use std::cell::RefCell;
+
+    let mut registry = ChangingRegistry::new();
+    let total: StateKey<f32> = StateKey::new("total");
+    registry.set_number("price", 2.0);
+    registry.set_number("amount", 3.0);
+    registry.compute(&total, &["price", "amount"], |r| r.get_number("price") * r.get_number("amount"));
+    assert_eq!(registry.computed(&total), Some(&6.0));
+    registry.update();
+
+    let notified = Rc::new(RefCell::new(Vec::new()));
+    let (c_notified, c_total) = (notified.clone(), total.clone());
+    registry.subscribe(Subscription::Key("total".to_string()), move |registry, key| {
+        c_notified.borrow_mut().push((key.to_string(), c_total.get(registry).copied()));
+    });
+    let all = registry.subscribe_channel(Subscription::All);
+
+    // Read right away after a dependency changed
+    registry.set_number("amount", 4.0);
+    assert_eq!(registry.computed(&total), Some(&8.0));
+
+    // Or computed at the end of the frame, notifying subscribers once for all changes
+    registry.set_number("price", 3.0);
+    registry.update();
+    assert_eq!(total.get(&registry), Some(&12.0));
+    assert_eq!(*notified.borrow(), vec![("total".to_string(), Some(12.0))]);
+    let mut changed: Vec<String> = all.try_iter().collect();
+    changed.sort();
+    assert_eq!(changed, vec!["amount", "price", "total"]);
+
+    // Frames without changes notify nobody
+    registry.update();
+    assert_eq!(notified.borrow().len(), 1);
+    assert_eq!(all.try_iter().count(), 0);
+}
+
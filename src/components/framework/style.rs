@@ -0,0 +1,723 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::components::context::context;
+use crate::components::framework::animation::{Animation, AnimationRef, AnimationRegistry, Easing};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::state::{State, StateRegistry, StateValue};
+use crate::components::render::color::Color;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// Properties that are passed on from a parent to its children when they don't set them themselves
+pub const INHERITED: &[&str] = &["color", "font", "font_size"];
+
+/// The interaction states an element can be in, which selectors can match with `:hover`, `:active`,
+/// `:focused` and `:disabled`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StateFlags(u8);
+
+impl StateFlags {
+    pub const NONE: StateFlags = StateFlags(0);
+    pub const HOVER: StateFlags = StateFlags(1);
+    pub const ACTIVE: StateFlags = StateFlags(1 << 1);
+    pub const FOCUSED: StateFlags = StateFlags(1 << 2);
+    pub const DISABLED: StateFlags = StateFlags(1 << 3);
+
+    pub fn contains(&self, flags: StateFlags) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    pub fn set(&mut self, flags: StateFlags, value: bool) {
+        if value {
+            self.0 |= flags.0;
+        } else {
+            self.0 &= !flags.0;
+        }
+    }
+
+    fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "hover" => Some(StateFlags::HOVER),
+            "active" => Some(StateFlags::ACTIVE),
+            "focused" | "focus" => Some(StateFlags::FOCUSED),
+            "disabled" => Some(StateFlags::DISABLED),
+            _ => None,
+        }
+    }
+}
+
+/// What a selector is matched against: the type name, classes and state of an element
+#[derive(Debug, Clone, Default, PartialEq, Hash)]
+pub struct StyleTarget {
+    pub type_name: Option<String>,
+    pub classes: Vec<String>,
+    pub flags: StateFlags,
+}
+
+impl StyleTarget {
+    pub fn new(type_name: impl ToString) -> Self {
+        StyleTarget {
+            type_name: Some(type_name.to_string()),
+            classes: Vec::new(),
+            flags: StateFlags::NONE,
+        }
+    }
+
+    pub fn has_class(&self, class: &str) -> bool {
+        self.classes.iter().any(|c| c == class)
+    }
+}
+
+/// A single compound selector, such as `Button.primary:hover`
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Compound {
+    type_name: Option<String>,
+    classes: Vec<String>,
+    flags: StateFlags,
+}
+
+impl Compound {
+    fn matches(&self, target: &StyleTarget) -> bool {
+        if let Some(type_name) = &self.type_name {
+            if target.type_name.as_ref() != Some(type_name) {
+                return false;
+            }
+        }
+        self.classes.iter().all(|c| target.has_class(c)) && target.flags.contains(self.flags)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    /// `A B`, B is anywhere inside of A
+    Descendant,
+    /// `A > B`, B is a direct child of A
+    Child,
+}
+
+/// A selector of compound selectors joined by descendant (` `) or child (`>`) combinators,
+/// such as `.sidebar > Button.primary:hover`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    /// The compound selectors from right to left, each with the combinator to its left
+    parts: Vec<(Compound, Combinator)>,
+}
+
+impl Selector {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut combinator = Combinator::Descendant;
+        for token in source.replace('>', " > ").split_whitespace() {
+            if token == ">" {
+                if parts.is_empty() || combinator == Combinator::Child {
+                    return Err(format!("Unexpected '>' in selector '{}'", source));
+                }
+                combinator = Combinator::Child;
+                continue;
+            }
+            parts.push((Self::parse_compound(token).map_err(|e| format!("{} in selector '{}'", e, source))?, combinator));
+            combinator = Combinator::Descendant;
+        }
+        if parts.is_empty() || combinator == Combinator::Child {
+            return Err(format!("Incomplete selector '{}'", source));
+        }
+        parts.reverse();
+        Ok(Selector { parts })
+    }
+
+    fn parse_compound(token: &str) -> Result<Compound, String> {
+        let mut compound = Compound::default();
+        // Split before each '.' and ':', keeping the separator
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        for (i, c) in token.char_indices() {
+            if (c == '.' || c == ':') && i != start {
+                pieces.push(&token[start..i]);
+                start = i;
+            }
+        }
+        pieces.push(&token[start..]);
+
+        for (i, piece) in pieces.into_iter().enumerate() {
+            if let Some(class) = piece.strip_prefix('.') {
+                if class.is_empty() {
+                    return Err("Empty class".to_string());
+                }
+                compound.classes.push(class.to_string());
+            } else if let Some(flag) = piece.strip_prefix(':') {
+                let flag = StateFlags::parse(flag).ok_or_else(|| format!("Unknown state ':{}'", flag))?;
+                compound.flags.set(flag, true);
+            } else if i == 0 {
+                if piece != "*" {
+                    compound.type_name = Some(piece.to_string());
+                }
+            } else {
+                return Err(format!("Unexpected '{}'", piece));
+            }
+        }
+        Ok(compound)
+    }
+
+    /// Returns the (classes and states, types) counts, where selectors with a higher specificity take priority.
+    ///
+    /// The counts are compared in order, so more types only decide between equal classes and states.
+    pub fn specificity(&self) -> (u32, u32) {
+        self.parts.iter().fold((0, 0), |(a, b), (c, _)| {
+            (a + c.classes.len() as u32 + c.flags.count(), b + c.type_name.is_some() as u32)
+        })
+    }
+
+    /// Matches against the path from the outermost ancestor to the element itself
+    pub fn matches(&self, path: &[&StyleTarget]) -> bool {
+        match path.split_last() {
+            Some((last, ancestors)) => self.parts[0].0.matches(last) && Self::matches_ancestors(&self.parts[1..], self.parts[0].1, ancestors),
+            None => false,
+        }
+    }
+
+    fn matches_ancestors(parts: &[(Compound, Combinator)], combinator: Combinator, ancestors: &[&StyleTarget]) -> bool {
+        let ((compound, next), rest) = match parts.split_first() {
+            None => return true,
+            Some(p) => p,
+        };
+        match combinator {
+            Combinator::Child => match ancestors.split_last() {
+                Some((parent, ancestors)) => compound.matches(parent) && Self::matches_ancestors(rest, *next, ancestors),
+                None => false,
+            },
+            Combinator::Descendant => (0..ancestors.len()).rev().any(|i| {
+                compound.matches(ancestors[i]) && Self::matches_ancestors(rest, *next, &ancestors[..i])
+            }),
+        }
+    }
+}
+
+/// How a property animates to its new value when it changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    /// In seconds
+    pub duration: f32,
+    pub easing: Easing,
+    /// In seconds
+    pub delay: f32,
+}
+
+impl Transition {
+    pub fn new(duration: f32, easing: Easing) -> Self {
+        Transition {
+            duration,
+            easing,
+            delay: 0.0,
+        }
+    }
+
+    pub fn delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StyleRule {
+    pub selector: Selector,
+    pub properties: HashMap<String, State>,
+    pub transitions: HashMap<String, Transition>,
+}
+
+/// An ordered list of [`StyleRule`]s, which are applied to the elements they match.
+///
+/// When several rules set the same property, the one with the higher [`Selector::specificity()`] wins,
+/// or the one added last if they are equal.
+#[derive(Debug, Clone, Default)]
+pub struct StyleSheet {
+    rules: Vec<StyleRule>,
+}
+
+impl StyleSheet {
+    pub fn new() -> Self {
+        StyleSheet {
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn add_rule<K: ToString>(&mut self, selector: &str, properties: impl IntoIterator<Item=(K, State)>) -> Result<(), String> {
+        self.rules.push(StyleRule {
+            selector: Selector::parse(selector)?,
+            properties: properties.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+            transitions: HashMap::new(),
+        });
+        Ok(())
+    }
+
+    /// Adds a rule that makes `properties` transition when they change on the elements matching `selector`.
+    ///
+    /// Transitions cascade like properties, and the one of the state being entered is used.
+    pub fn add_transitions<K: ToString>(&mut self, selector: &str, transitions: impl IntoIterator<Item=(K, Transition)>) -> Result<(), String> {
+        self.rules.push(StyleRule {
+            selector: Selector::parse(selector)?,
+            properties: HashMap::new(),
+            transitions: transitions.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+        });
+        Ok(())
+    }
+
+    pub fn add(&mut self, rule: StyleRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn rules(&self) -> &Vec<StyleRule> {
+        &self.rules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Resolves the style of the last target of `path`, inheriting from the `parent` style
+    pub fn resolve(&self, path: &[&StyleTarget], parent: Option<&ComputedStyle>) -> ComputedStyle {
+        let mut properties = HashMap::new();
+        if let Some(parent) = parent {
+            for p in INHERITED {
+                if let Some(v) = parent.properties.get(*p) {
+                    properties.insert(p.to_string(), v.clone());
+                }
+            }
+        }
+
+        let mut matching: Vec<(usize, &StyleRule)> = self.rules.iter().enumerate().filter(|(_, r)| r.selector.matches(path)).collect();
+        // Stable, so rules added later stay after earlier ones of the same specificity
+        matching.sort_by_key(|(_, r)| r.selector.specificity());
+        let mut transitions = HashMap::new();
+        for (_, rule) in matching {
+            for (k, v) in &rule.properties {
+                properties.insert(k.clone(), v.clone());
+            }
+            for (k, t) in &rule.transitions {
+                transitions.insert(k.clone(), *t);
+            }
+        }
+        ComputedStyle { properties, transitions }
+    }
+}
+
+/// The properties that apply to an element after resolving a [`StyleSheet`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComputedStyle {
+    properties: HashMap<String, State>,
+    transitions: HashMap<String, Transition>,
+}
+
+impl ComputedStyle {
+    pub fn get(&self, property: &str) -> Option<&State> {
+        self.properties.get(property)
+    }
+
+    pub fn transition(&self, property: &str) -> Option<&Transition> {
+        self.transitions.get(property)
+    }
+
+    pub fn get_as<T: StateValue>(&self, property: &str) -> Option<T> {
+        self.get(property).and_then(T::from_state)
+    }
+
+    pub fn color(&self, property: &str) -> Option<Color> {
+        self.get_as(property)
+    }
+
+    pub fn color_or(&self, property: &str, default: Color) -> Color {
+        self.color(property).unwrap_or(default)
+    }
+
+    pub fn number(&self, property: &str) -> Option<f32> {
+        self.get_as(property)
+    }
+
+    /// A [`Vec4`], or a number that is used for all four sides
+    pub fn sides(&self, property: &str) -> Option<Vec4> {
+        match self.get(property)? {
+            State::Vec4(v) => Some(*v),
+            State::Number(n) => Some(Vec4::ltrb(*n, *n, *n, *n)),
+            _ => None,
+        }
+    }
+
+    /// A [`Vec2`], or a number that is used for both axes
+    pub fn axes(&self, property: &str) -> Option<Vec2<f32>> {
+        match self.get(property)? {
+            State::Vec2(v) => Some(*v),
+            State::Number(n) => Some(Vec2::new(*n, *n)),
+            _ => None,
+        }
+    }
+}
+
+/// The parts of a [`LayoutContext`] that styles can override
+#[derive(Debug, Clone)]
+struct BaseLayout {
+    padding: Vec4,
+    margin: Vec4,
+    spacing: Vec2<f32>,
+}
+
+impl BaseLayout {
+    fn of(layout: &LayoutContext) -> Self {
+        BaseLayout {
+            padding: layout.padding,
+            margin: layout.margin,
+            spacing: layout.spacing,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct StyleEntry {
+    version: u64,
+    target: StyleTarget,
+    computed: ComputedStyle,
+}
+
+thread_local! {
+    /// The styled ancestors of the element currently handling an event
+    static ANCESTORS: RefCell<Vec<Rc<StyleEntry>>> = const { RefCell::new(Vec::new()) };
+    static VERSION: Cell<u64> = const { Cell::new(0) };
+}
+
+fn next_version() -> u64 {
+    VERSION.with(|v| {
+        v.set(v.get() + 1);
+        v.get()
+    })
+}
+
+/// A property that is moving towards its new value
+#[derive(Debug)]
+struct Running {
+    from: State,
+    to: State,
+    transition: Transition,
+    waited: f32,
+    progress: AnimationRef,
+}
+
+/// The style of an [`Element`] or [`Container`], resolved against the [`Framework`]'s [`StyleSheet`]
+/// through the containers it is in.
+///
+/// When a property with a [`Transition`] changes, [`computed()`] returns the animated value until it
+/// reaches the new one. The animations are in [`animations()`], so that the owner is redrawn while they run.
+///
+/// [`Element`]: crate::components::framework::element::Element
+/// [`Container`]: crate::components::framework::element::container::Container
+/// [`Framework`]: crate::components::framework::Framework
+/// [`computed()`]: Styled::computed
+/// [`animations()`]: Styled::animations
+#[derive(Debug)]
+pub struct Styled {
+    entry: Rc<StyleEntry>,
+    base: Option<BaseLayout>,
+    base_changed: bool,
+    /// Hash of what the style was last resolved with
+    resolved_with: u64,
+    changed: bool,
+
+    /// Properties that follow a key of the [`Framework`]'s states instead of the style sheet
+    ///
+    /// [`Framework`]: crate::components::framework::Framework
+    bindings: HashMap<String, String>,
+    /// The values being moved towards, from the style sheet and bindings
+    targets: HashMap<String, State>,
+    displayed: ComputedStyle,
+    running: HashMap<String, Running>,
+    animations: AnimationRegistry,
+}
+
+impl Styled {
+    pub fn new(target: StyleTarget) -> Self {
+        Styled {
+            entry: Rc::new(StyleEntry {
+                version: next_version(),
+                target,
+                computed: ComputedStyle::default(),
+            }),
+            base: None,
+            base_changed: false,
+            resolved_with: 0,
+            changed: false,
+            bindings: HashMap::new(),
+            targets: HashMap::new(),
+            displayed: ComputedStyle::default(),
+            running: HashMap::new(),
+            animations: AnimationRegistry::new(),
+        }
+    }
+
+    pub fn target(&self) -> &StyleTarget {
+        &self.entry.target
+    }
+
+    /// Changes the target. Children will resolve their style again, as selectors can match on their ancestors.
+    pub fn set_target(&mut self, target: StyleTarget) {
+        if target != self.entry.target {
+            self.entry = Rc::new(StyleEntry {
+                version: next_version(),
+                target,
+                computed: self.entry.computed.clone(),
+            });
+        }
+    }
+
+    pub fn set_flags(&mut self, flags: StateFlags) {
+        if flags != self.entry.target.flags {
+            let mut target = self.entry.target.clone();
+            target.flags = flags;
+            self.set_target(target);
+        }
+    }
+
+    /// Takes the type name, classes and bindings of `other`, keeping the current state flags and transitions
+    pub fn patch(&mut self, other: Styled) {
+        let mut target = other.entry.target.clone();
+        target.flags = self.entry.target.flags;
+        self.set_target(target);
+        self.bindings = other.bindings;
+        self.resolved_with = 0;
+    }
+
+    /// Makes `property` follow the value of `key` in the [`Framework`]'s states, taking priority over the style sheet.
+    ///
+    /// The property still uses the transition the style sheet declares for it.
+    ///
+    /// [`Framework`]: crate::components::framework::Framework
+    pub fn bind(&mut self, property: impl ToString, key: impl ToString) {
+        self.bindings.insert(property.to_string(), key.to_string());
+        self.resolved_with = 0;
+    }
+
+    pub fn unbind(&mut self, property: &str) {
+        self.bindings.remove(property);
+        self.resolved_with = 0;
+    }
+
+    /// The current style, including the values of running transitions
+    pub fn computed(&self) -> &ComputedStyle {
+        &self.displayed
+    }
+
+    /// The style resolved from the style sheet, without transitions or bindings
+    pub fn resolved(&self) -> &ComputedStyle {
+        &self.entry.computed
+    }
+
+    /// Whether the current style changed the last time it was resolved
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    pub fn animations(&mut self) -> &mut AnimationRegistry {
+        &mut self.animations
+    }
+
+    /// Uses the current layout as the one that styles are applied over
+    pub fn reset_base(&mut self, layout: &LayoutContext) {
+        self.base = Some(BaseLayout::of(layout));
+        self.base_changed = true;
+        self.resolved_with = 0;
+    }
+
+    /// Resolves the style again if the style sheet, the ancestors or the target changed since last time,
+    /// advances transitions, and applies padding, margin and spacing onto `layout`.
+    ///
+    /// Should be called once every frame, on [`Event::PreRender`].
+    ///
+    /// [`Event::PreRender`]: crate::components::framework::event::Event::PreRender
+    pub unsafe fn resolve(&mut self, layout: &mut LayoutContext) {
+        let framework = context().framework();
+        let sheet = framework.style_sheet();
+        let mut hasher = DefaultHasher::new();
+        framework.style_sheet_version().hash(&mut hasher);
+        self.entry.version.hash(&mut hasher);
+        ANCESTORS.with_borrow(|a| {
+            for entry in a {
+                entry.version.hash(&mut hasher);
+            }
+        });
+        let hash = hasher.finish();
+
+        self.changed = false;
+        // The targets only change with the resolved style or the bound states
+        let mut outdated = hash != self.resolved_with;
+        if outdated {
+            self.resolved_with = hash;
+            let computed = ANCESTORS.with_borrow(|ancestors| {
+                let mut path: Vec<&StyleTarget> = ancestors.iter().map(|e| &e.target).collect();
+                path.push(&self.entry.target);
+                sheet.resolve(&path, ancestors.last().map(|e| &e.computed))
+            });
+            if computed != self.entry.computed {
+                self.displayed.transitions = computed.transitions.clone();
+                self.entry = Rc::new(StyleEntry {
+                    version: next_version(),
+                    target: self.entry.target.clone(),
+                    computed,
+                });
+            }
+        }
+
+        let states = framework.states();
+        outdated = outdated || self.bindings.iter().any(|(property, key)| {
+            let target = match states.get(key) {
+                State::None => self.entry.computed.properties.get(property),
+                state => Some(state),
+            };
+            target != self.targets.get(property)
+        });
+        if outdated {
+            let mut targets = self.entry.computed.properties.clone();
+            for (property, key) in &self.bindings {
+                match states.get(key) {
+                    State::None => {}
+                    state => { targets.insert(property.clone(), state.clone()); }
+                }
+            }
+            if targets != self.targets {
+                self.retarget(targets);
+            }
+        }
+        self.advance();
+
+        if !self.changed && !self.base_changed {
+            return;
+        }
+        self.base_changed = false;
+
+        let base = self.base.get_or_insert_with(|| BaseLayout::of(layout));
+        let displayed = &self.displayed;
+        layout.padding = displayed.sides("padding").unwrap_or(base.padding);
+        layout.margin = displayed.sides("margin").unwrap_or(base.margin);
+        layout.spacing = displayed.axes("spacing").unwrap_or(base.spacing);
+    }
+
+    /// Starts transitions for the properties that have one, and sets the others right away
+    fn retarget(&mut self, targets: HashMap<String, State>) {
+        for (property, to) in &targets {
+            if self.targets.get(property) == Some(to) {
+                continue;
+            }
+            let from = self.displayed.properties.get(property);
+            let transition = self.displayed.transitions.get(property);
+            match (from, transition) {
+                (Some(from), Some(transition)) if from.try_interpolate(to, 0.0).is_some() && transition.duration > 0.0 => {
+                    let progress: AnimationRef = Animation::new(1.0, 0.0).into();
+                    self.animations.register(progress.clone());
+                    if let Some(previous) = self.running.insert(property.clone(), Running {
+                        from: from.clone(),
+                        to: to.clone(),
+                        transition: *transition,
+                        waited: 0.0,
+                        progress,
+                    }) {
+                        self.animations.unregister(&previous.progress);
+                    }
+                }
+                _ => {
+                    if let Some(previous) = self.running.remove(property) {
+                        self.animations.unregister(&previous.progress);
+                    }
+                    self.displayed.properties.insert(property.clone(), to.clone());
+                    self.changed = true;
+                }
+            }
+        }
+
+        let removed: Vec<String> = self.displayed.properties.keys().filter(|k| !targets.contains_key(*k)).cloned().collect();
+        for property in removed {
+            if let Some(previous) = self.running.remove(&property) {
+                self.animations.unregister(&previous.progress);
+            }
+            self.displayed.properties.remove(&property);
+            self.changed = true;
+        }
+        self.targets = targets;
+    }
+
+    unsafe fn advance(&mut self) {
+        if self.running.is_empty() {
+            return;
+        }
+        let delta = context().framework().pre_delta();
+        let mut finished = Vec::new();
+        for (property, running) in &mut self.running {
+            if running.waited < running.transition.delay {
+                running.waited += delta;
+                continue;
+            }
+            let mut progress = running.progress.borrow_mut();
+            let t = progress.animate(1.0 / running.transition.duration, running.transition.easing);
+            if let Some(value) = running.from.try_interpolate(&running.to, t) {
+                self.displayed.properties.insert(property.clone(), value);
+                self.changed = true;
+            }
+            if progress.state() >= 1.0 {
+                finished.push(property.clone());
+            }
+        }
+        for property in finished {
+            if let Some(running) = self.running.remove(&property) {
+                self.animations.unregister(&running.progress);
+                self.displayed.properties.insert(property, running.to);
+            }
+        }
+    }
+
+    /// Makes this the parent style of everything handled until [`pop()`]
+    ///
+    /// [`pop()`]: Styled::pop
+    pub fn push(&self) {
+        ANCESTORS.with_borrow_mut(|a| a.push(self.entry.clone()));
+    }
+
+    pub fn pop(&self) {
+        ANCESTORS.with_borrow_mut(|a| a.pop());
+    }
+}
+
+#[test]
+pub fn selectors() {
+    let mut sheet = StyleSheet::new();
+    sheet.add_rule("Button", [("color", State::Number(1.0)), ("padding", State::Number(2.0))]).unwrap();
+    sheet.add_rule(".sidebar Button.primary", [("color", State::Number(2.0))]).unwrap();
+    sheet.add_rule("Button:hover", [("color", State::Number(3.0))]).unwrap();
+    sheet.add_rule("Container > Button", [("margin", State::Number(4.0))]).unwrap();
+    sheet.add_rule(".sidebar", [("font_size", State::Number(12.0))]).unwrap();
+
+    let mut sidebar = StyleTarget::new("Container");
+    sidebar.classes.push("sidebar".to_string());
+    let inner = StyleTarget::new("Panel");
+    let mut button = StyleTarget::new("Button");
+    button.classes.push("primary".to_string());
+
+    let parent = sheet.resolve(&[&sidebar], None);
+    let style = sheet.resolve(&[&sidebar, &inner, &button], Some(&parent));
+    assert_eq!(style.number("color"), Some(2.0));
+    assert_eq!(style.sides("padding"), Some(Vec4::ltrb(2.0, 2.0, 2.0, 2.0)));
+    assert_eq!(style.number("margin"), None);
+    assert_eq!(style.number("font_size"), Some(12.0));
+
+    // The rule with more classes and states wins, even though the hover rule was added later
+    button.flags.set(StateFlags::HOVER, true);
+    assert_eq!(sheet.resolve(&[&sidebar, &button], None).number("color"), Some(2.0));
+    button.classes.clear();
+    assert_eq!(sheet.resolve(&[&sidebar, &button], None).number("color"), Some(3.0));
+    assert_eq!(sheet.resolve(&[&sidebar, &button], None).number("margin"), Some(4.0));
+
+    assert!(Selector::parse("> Button").is_err());
+    assert!(Selector::parse("Button:pressed").is_err());
+    assert_eq!(Selector::parse("* .a:hover > B.c").unwrap().specificity(), (3, 1));
+}
@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use toml::{Table, Value};
+
+use crate::components::framework::state::{State, StateRegistry, UnchangingRegistry};
+use crate::components::framework::animation::Easing;
+use crate::components::framework::style::{Selector, StyleRule, StyleSheet, Transition};
+use crate::components::render::color::Color;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// How often a watched theme file is checked for changes
+const WATCH_INTERVAL: f32 = 0.25;
+
+/// Loads an [`UnchangingRegistry`] of styles from a TOML theme file.
+///
+/// Every key of the file becomes a style, with tables being flattened into dotted keys, so
+/// `[button] background = ...` is stored as `button.background`. Values are converted as follows:
+///
+/// - `"#rgb"`, `"#rgba"`, `"#rrggbb"` and `"#rrggbbaa"` become a [`State::Color`]
+/// - `"$name"` is replaced by the entry `name` of the active palette
+/// - `"@key"` is replaced by the value of another style key
+/// - `[x, y]` becomes a [`State::Vec2`], and `[x, y, width, height]` a [`State::Vec4`]
+/// - numbers, strings and bools become [`State::Number`], [`State::String`] and [`State::Bool`]
+///
+/// A string starting with `\` is kept as is, without the `\`, so `"\#tag"` is the string `#tag`.
+///
+/// Tables under `[rules]` are not styles, but [`StyleSheet`] rules keyed by their selector,
+/// and their values are converted in the same way. A rule's `transition` table declares a
+/// [`Transition`] per property, such as `transition.background = { duration = 0.2, easing = "Sin", delay = 0 }`.
+///
+/// Palettes are declared under `[palettes.<name>]`, and the active one is chosen with the
+/// top-level `palette` key, or with [`Theme::set_palette()`]:
+///
+/// ```toml
+/// palette = "dark"
+///
+/// [palettes.dark]
+/// background = "#16161a"
+/// accent = "#3b82f6"
+///
+/// [button]
+/// background = "$accent"
+/// hovered = "@button.background"
+/// padding = [6, 4, 6, 4]
+/// radius = 4
+///
+/// [rules."Button.primary:hover"]
+/// background = "@button.hovered"
+/// ```
+#[derive(Debug)]
+pub struct Theme {
+    path: Option<PathBuf>,
+    palette: Option<String>,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+    styles: UnchangingRegistry,
+    style_sheet: StyleSheet,
+}
+
+impl Theme {
+    /// Loads and parses the theme file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut theme = Theme {
+            path: Some(path),
+            palette: None,
+            modified: None,
+            last_check: Instant::now(),
+            styles: UnchangingRegistry::new(),
+            style_sheet: StyleSheet::new(),
+        };
+        theme.reload()?;
+        Ok(theme)
+    }
+
+    /// Parses a theme that is not backed by a file, which can not be watched
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let (styles, style_sheet) = parse_theme(source, None)?;
+        Ok(Theme {
+            path: None,
+            palette: None,
+            modified: None,
+            last_check: Instant::now(),
+            styles,
+            style_sheet,
+        })
+    }
+
+    /// Overrides the palette selected by the file, and reloads it
+    pub fn set_palette(&mut self, palette: impl ToString) -> Result<(), String> {
+        self.palette = Some(palette.to_string());
+        self.reload()
+    }
+
+    pub fn styles(&self) -> &UnchangingRegistry {
+        &self.styles
+    }
+
+    pub fn style_sheet(&self) -> &StyleSheet {
+        &self.style_sheet
+    }
+
+    /// Reads the file again. On error, the previously loaded styles are kept.
+    pub fn reload(&mut self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        self.modified = modified(path);
+        let source = fs::read_to_string(path).map_err(|e| format!("Couldn't read theme '{}': {}", path.display(), e))?;
+        (self.styles, self.style_sheet) = parse_theme(&source, self.palette.as_deref()).map_err(|e| format!("Invalid theme '{}': {}", path.display(), e))?;
+        Ok(())
+    }
+
+    /// Checks whether the file was modified since it was last loaded, and reloads it if so.
+    ///
+    /// The file is only checked every [`WATCH_INTERVAL`] seconds. Returns `Some` if a reload was attempted.
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        let path = self.path.as_ref()?;
+        if self.last_check.elapsed().as_secs_f32() < WATCH_INTERVAL {
+            return None;
+        }
+        self.last_check = Instant::now();
+        if modified(path) == self.modified {
+            return None;
+        }
+        Some(self.reload())
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Parses the source of a theme into its styles and rules, using `palette` instead of the one selected in the file if given
+pub fn parse_theme(source: &str, palette: Option<&str>) -> Result<(UnchangingRegistry, StyleSheet), String> {
+    let mut table = source.parse::<Table>().map_err(|e| e.to_string())?;
+
+    let selected = match (palette, table.remove("palette")) {
+        (Some(palette), _) => Some(palette.to_string()),
+        (None, Some(Value::String(palette))) => Some(palette),
+        (None, Some(_)) => return Err("'palette' must be the name of a palette".to_string()),
+        (None, None) => None,
+    };
+    let palettes = match table.remove("palettes") {
+        Some(Value::Table(palettes)) => palettes,
+        Some(_) => return Err("'palettes' must be a table of palettes".to_string()),
+        None => Table::new(),
+    };
+    let palette = match selected {
+        Some(name) => match palettes.get(&name) {
+            Some(Value::Table(palette)) => palette.clone(),
+            _ => return Err(format!("Palette '{}' not found", name)),
+        },
+        // Without a selection, a single palette is used by default
+        None if palettes.len() == 1 => match palettes.values().next() {
+            Some(Value::Table(palette)) => palette.clone(),
+            _ => Table::new(),
+        },
+        None => Table::new(),
+    };
+    let rules = match table.remove("rules") {
+        Some(Value::Table(rules)) => rules,
+        Some(_) => return Err("'rules' must be a table of selectors".to_string()),
+        None => Table::new(),
+    };
+
+    let mut raw = HashMap::new();
+    flatten(&table, "", &mut raw);
+
+    let mut resolver = Resolver {
+        raw: &raw,
+        palette: &palette,
+        resolved: HashMap::new(),
+        resolving: Vec::new(),
+    };
+    let mut registry = UnchangingRegistry::new();
+    for key in raw.keys() {
+        let state = resolver.resolve(key)?;
+        registry.set(key, state);
+    }
+
+    let mut style_sheet = StyleSheet::new();
+    for (selector, properties) in &rules {
+        let properties = match properties {
+            Value::Table(properties) => properties,
+            _ => return Err(format!("Rule '{}' must be a table", selector)),
+        };
+        let mut properties = properties.clone();
+        let transitions = match properties.remove("transition") {
+            Some(Value::Table(transitions)) => transitions,
+            Some(_) => return Err(format!("'transition' of rule '{}' must be a table of properties", selector)),
+            None => Table::new(),
+        };
+        let mut rule = StyleRule {
+            selector: Selector::parse(selector)?,
+            properties: HashMap::new(),
+            transitions: HashMap::new(),
+        };
+
+        let mut flattened = HashMap::new();
+        flatten(&properties, "", &mut flattened);
+        for (property, value) in flattened {
+            let state = resolver.convert(value).map_err(|e| format!("'{}' of rule '{}': {}", property, selector, e))?;
+            rule.properties.insert(property, state);
+        }
+        for (property, value) in &transitions {
+            let transition = parse_transition(value).map_err(|e| format!("Transition '{}' of rule '{}': {}", property, selector, e))?;
+            rule.transitions.insert(property.clone(), transition);
+        }
+        style_sheet.add(rule);
+    }
+    Ok((registry, style_sheet))
+}
+
+/// Parses `{ duration = 0.2, easing = "Sin", delay = 0.1 }`, where only the duration is required
+fn parse_transition(value: &Value) -> Result<Transition, String> {
+    let table = match value {
+        Value::Table(table) => table,
+        _ => return Err("Expected a table with a duration".to_string()),
+    };
+    let number = |key: &str| match table.get(key) {
+        None => Ok(None),
+        Some(Value::Integer(i)) => Ok(Some(*i as f32)),
+        Some(Value::Float(f)) => Ok(Some(*f as f32)),
+        Some(_) => Err(format!("'{}' must be a number of seconds", key)),
+    };
+    let duration = number("duration")?.ok_or_else(|| "Missing duration".to_string())?;
+    let easing = match table.get("easing") {
+        None => Easing::Linear,
+        Some(Value::String(name)) => Easing::from_name(name).ok_or_else(|| format!("Unknown easing '{}'", name))?,
+        Some(_) => return Err("'easing' must be the name of an easing".to_string()),
+    };
+    Ok(Transition::new(duration, easing).delay(number("delay")?.unwrap_or(0.0)))
+}
+
+fn flatten<'a>(table: &'a Table, prefix: &str, out: &mut HashMap<String, &'a Value>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::Table(table) => flatten(table, &key, out),
+            value => { out.insert(key, value); }
+        }
+    }
+}
+
+struct Resolver<'a> {
+    raw: &'a HashMap<String, &'a Value>,
+    palette: &'a Table,
+    resolved: HashMap<String, State>,
+    /// The keys currently being resolved, used to detect reference cycles
+    resolving: Vec<String>,
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, key: &str) -> Result<State, String> {
+        if let Some(state) = self.resolved.get(key) {
+            return Ok(state.clone());
+        }
+        if self.resolving.iter().any(|k| k == key) {
+            return Err(format!("Reference cycle: {} -> {}", self.resolving.join(" -> "), key));
+        }
+        let value = *self.raw.get(key).ok_or_else(|| format!("Unknown key '@{}'", key))?;
+
+        self.resolving.push(key.to_string());
+        let state = self.convert(value).map_err(|e| format!("'{}': {}", key, e));
+        self.resolving.pop();
+
+        let state = state?;
+        self.resolved.insert(key.to_string(), state.clone());
+        Ok(state)
+    }
+
+    fn convert(&mut self, value: &Value) -> Result<State, String> {
+        match value {
+            Value::String(s) => {
+                if let Some(key) = s.strip_prefix('@') {
+                    self.resolve(key)
+                } else if let Some(name) = s.strip_prefix('$') {
+                    let value = self.palette.get(name).ok_or_else(|| format!("Palette has no entry '{}'", name))?;
+                    // Palette entries can not refer to other keys
+                    match value {
+                        Value::String(s) if s.starts_with('@') || s.starts_with('$') => {
+                            Err(format!("Palette entry '{}' can not be a reference", name))
+                        }
+                        value => self.convert(value),
+                    }
+                } else if let Some(hex) = s.strip_prefix('#') {
+                    parse_hex(hex).map(State::Color).ok_or_else(|| format!("Invalid color '{}'", s))
+                } else if let Some(s) = s.strip_prefix('\\') {
+                    Ok(State::String(s.to_string()))
+                } else {
+                    Ok(State::String(s.clone()))
+                }
+            }
+            Value::Integer(i) => Ok(State::Number(*i as f32)),
+            Value::Float(f) => Ok(State::Number(*f as f32)),
+            Value::Boolean(b) => Ok(State::Bool(*b)),
+            Value::Array(values) => {
+                let mut numbers = Vec::with_capacity(values.len());
+                for v in values {
+                    numbers.push(match v {
+                        Value::Integer(i) => *i as f32,
+                        Value::Float(f) => *f as f32,
+                        _ => return Err("Arrays can only contain numbers".to_string()),
+                    });
+                }
+                match numbers[..] {
+                    [x, y] => Ok(State::Vec2(Vec2::new(x, y))),
+                    [x, y, width, height] => Ok(State::Vec4(Vec4::xywh(x, y, width, height))),
+                    _ => Err(format!("Expected 2 or 4 numbers, found {}", numbers.len())),
+                }
+            }
+            Value::Datetime(_) => Err("Dates are not supported".to_string()),
+            Value::Table(_) => unreachable!("tables are flattened"),
+        }
+    }
+}
+
+/// Parses `rgb`, `rgba`, `rrggbb` or `rrggbbaa`
+pub fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17);
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => Some(Color::from_u8(digit(0)?, digit(1)?, digit(2)?, 255)),
+        4 => Some(Color::from_u8(digit(0)?, digit(1)?, digit(2)?, digit(3)?)),
+        6 => Some(Color::from_u8(byte(0)?, byte(2)?, byte(4)?, 255)),
+        8 => Some(Color::from_u8(byte(0)?, byte(2)?, byte(4)?, byte(6)?)),
+        _ => None,
+    }
+}
+
+#[test]
+pub fn theme() {
+    let theme = Theme::parse(r##"
+        palette = "dark"
+        size = [10, 20]
+
+        [palettes.dark]
+        accent = "#ff000080"
+
+        [palettes.light]
+        accent = "#fff"
+
+        [button]
+        background = "$accent"
+        hovered = "@button.background"
+        padding = [1, 2, 3, 4]
+        label = "\\#tag"
+        bold = true
+
+        [rules."Button:hover"]
+        background = "@button.background"
+        transition.background = { duration = 0.2, easing = "Sin" }
+    "##).unwrap();
+    let styles = theme.styles();
+    assert_eq!(styles.get("button.hovered"), &State::Color(Color::from_u8(255, 0, 0, 128)));
+    assert_eq!(styles.get("button.padding"), &State::Vec4(Vec4::xywh(1, 2, 3, 4)));
+    assert_eq!(styles.get("size"), &State::Vec2(Vec2::new(10.0, 20.0)));
+    assert_eq!(styles.get("button.label"), &State::String("#tag".to_string()));
+    assert_eq!(styles.get("button.bold"), &State::Bool(true));
+    let rule = &theme.style_sheet().rules()[0];
+    assert_eq!(rule.properties.get("background"), Some(&State::Color(Color::from_u8(255, 0, 0, 128))));
+    assert_eq!(rule.transitions.get("background"), Some(&Transition::new(0.2, Easing::Sin)));
+
+    let (light, _) = parse_theme("palette = 'dark'\n[palettes.light]\nc = '#fff'\n[palettes.dark]\nc = '#000'\n[a]\nc = '$c'", Some("light")).unwrap();
+    assert_eq!(light.get("a.c"), &State::Color(Color::from_u8(255, 255, 255, 255)));
+
+    assert!(Theme::parse("a = '@b'\nb = '@a'").is_err());
+}
@@ -54,6 +54,14 @@ impl UIHandlerRef {
     }
 }
 
+impl<H: UIHandler + 'static> From<Rc<RefCell<H>>> for UIHandlerRef {
+    fn from(handler: Rc<RefCell<H>>) -> Self {
+        UIHandlerRef {
+            handler,
+        }
+    }
+}
+
 impl UIHandler for UIHandlerRef {
     unsafe fn handle(&mut self, event: &Event) -> EventResult {
         self.handler.borrow_mut().handle(event)
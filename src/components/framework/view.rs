@@ -0,0 +1,462 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::container::Container;
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::state::{ChangingRegistry, State, StateRegistry, StateValue};
+use crate::components::framework::ui_traits::{TickResult, UIHandler, UIHandlerRef};
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// Builds a [`View`] container in the same way as [`container!`], except that children can be
+/// anything that is `Into<View>`, such as an [`ElementBuilder`] from [`element!`], another [`View`],
+/// or a `Vec<View>` created from an iterator.
+///
+/// [`container!`]: crate::container
+/// [`element!`]: crate::element
+#[macro_export]
+macro_rules! view {
+    (
+        layout: { $($layout:tt)* },
+        $( $expr:expr ),* $(,)?
+    ) => {
+        View::container(
+            LayoutContext {
+                $($layout)*
+                ..Default::default()
+            },
+            vec![$( View::from($expr) ),*]
+        )
+    };
+
+    (
+        $( $expr:expr ),* $(,)?
+    ) => {
+        View::container(LayoutContext::default(), vec![$( View::from($expr) ),*])
+    };
+}
+
+/// The scope and key of a [`Binding`]
+type BindingKey = (Option<String>, String);
+
+thread_local! {
+    /// The bindings read while a [`Component`]'s view is being built
+    static READS: RefCell<Option<Vec<BindingKey>>> = const { RefCell::new(None) };
+}
+
+/// A typed handle to a value in the [`Framework`]'s [`states()`], or a sub registry of it.
+///
+/// Reading a [`Binding`] while [`Component::view()`] is running subscribes that view to it,
+/// so the view is built again once the value changes.
+///
+/// [`Framework`]: crate::components::framework::Framework
+/// [`states()`]: crate::components::framework::Framework::states
+#[derive(Debug, Clone)]
+pub struct Binding<T> {
+    scope: Option<String>,
+    key: String,
+    default: T,
+}
+
+impl<T: StateValue> Binding<T> {
+    /// Creates a binding to `key` in the top-level registry
+    pub fn new(key: impl ToString, default: T) -> Self {
+        Binding {
+            scope: None,
+            key: key.to_string(),
+            default,
+        }
+    }
+
+    /// Creates a binding to `key` in the sub registry `scope`
+    pub fn scoped(scope: impl ToString, key: impl ToString, default: T) -> Self {
+        Binding {
+            scope: Some(scope.to_string()),
+            key: key.to_string(),
+            default,
+        }
+    }
+
+    /// Returns the current value, or the default if it has not been set or is of a different type
+    pub unsafe fn get(&self) -> T {
+        READS.with_borrow_mut(|reads| {
+            if let Some(reads) = reads {
+                reads.push((self.scope.clone(), self.key.clone()));
+            }
+        });
+        T::from_state(registry(&self.scope).get(&self.key)).unwrap_or(self.default.clone())
+    }
+
+    pub unsafe fn set(&self, value: T) {
+        registry(&self.scope).set(&self.key, value.into_state());
+    }
+
+    pub unsafe fn update<F: FnOnce(T) -> T>(&self, f: F) {
+        let current = T::from_state(registry(&self.scope).get(&self.key)).unwrap_or(self.default.clone());
+        self.set(f(current));
+    }
+
+    pub unsafe fn changed(&self) -> bool {
+        registry(&self.scope).changed(&self.key)
+    }
+}
+
+unsafe fn registry(scope: &Option<String>) -> &'static mut ChangingRegistry {
+    let states = context().framework().states();
+    match scope {
+        None => states,
+        Some(scope) => states.sub(scope),
+    }
+}
+
+/// A description of a UI tree, which a [`ViewHost`] turns into [`Container`]s and [`Element`]s.
+///
+/// Each time the view is built, the new description is compared against the previous one.
+/// Nodes are matched by key when one is given, otherwise by their position among the unkeyed
+/// nodes of the same parent. Matched nodes are updated in place, keeping their bounds,
+/// scroll and hover state.
+pub enum View {
+    Container {
+        key: Option<u64>,
+        layout: LayoutContext,
+        children: Vec<View>,
+    },
+    Element {
+        key: Option<u64>,
+        element: Box<ElementBuilder>,
+    },
+    /// Any other [`UIHandler`], which is only created again if its key changes
+    Handler {
+        key: u64,
+        construct: Box<dyn FnOnce() -> UIHandlerRef>,
+    },
+    /// A list of views that are added directly to the parent
+    Fragment(Vec<View>),
+}
+
+impl View {
+    pub fn container(layout: LayoutContext, children: Vec<View>) -> Self {
+        View::Container {
+            key: None,
+            layout,
+            children,
+        }
+    }
+
+    pub fn handler<H: UIHandler + 'static, F: FnOnce() -> H + 'static>(key: u64, construct: F) -> Self {
+        View::Handler {
+            key,
+            construct: Box::new(move || UIHandlerRef::new(construct()).0),
+        }
+    }
+
+    /// Sets the key used to match this view against the previous build
+    pub fn key(mut self, new_key: u64) -> Self {
+        match &mut self {
+            View::Container { key, .. } | View::Element { key, .. } => *key = Some(new_key),
+            View::Handler { key, .. } => *key = new_key,
+            View::Fragment(_) => {}
+        }
+        self
+    }
+
+    fn get_key(&self) -> Option<u64> {
+        match self {
+            View::Container { key, .. } | View::Element { key, .. } => *key,
+            View::Handler { key, .. } => Some(*key),
+            View::Fragment(_) => None,
+        }
+    }
+
+    fn flatten(self, out: &mut Vec<View>) {
+        match self {
+            View::Fragment(views) => {
+                for v in views {
+                    v.flatten(out);
+                }
+            }
+            v => out.push(v),
+        }
+    }
+}
+
+impl From<ElementBuilder> for View {
+    fn from(element: ElementBuilder) -> Self {
+        View::Element {
+            key: None,
+            element: Box::new(element),
+        }
+    }
+}
+
+impl From<Vec<View>> for View {
+    fn from(views: Vec<View>) -> Self {
+        View::Fragment(views)
+    }
+}
+
+/// Something that builds its UI from its state.
+///
+/// [`view()`] is called again whenever a [`Binding`] it read has changed.
+///
+/// [`view()`]: Component::view
+pub trait Component {
+    unsafe fn view(&mut self) -> View;
+}
+
+/// A node of the UI tree that was built from a [`View`]
+enum Mounted {
+    Container {
+        key: Option<u64>,
+        container: Rc<RefCell<Container>>,
+        children: Vec<Mounted>,
+    },
+    Element {
+        key: Option<u64>,
+        element: Rc<RefCell<Element>>,
+    },
+    Handler {
+        key: u64,
+        handler: UIHandlerRef,
+    },
+}
+
+impl Mounted {
+    fn mount(view: View) -> Self {
+        match view {
+            View::Container { key, layout, children } => {
+                let container = Rc::new(RefCell::new(Container::new(layout)));
+                let children = Self::reconcile_children(&container, Vec::new(), children);
+                Mounted::Container { key, container, children }
+            }
+            View::Element { key, element } => {
+                let mut element = (*element).build();
+                let pref_size = element.layout_mut().pref_size;
+                if pref_size != Vec2::zero() {
+                    Element::bounds(&mut element).set_wh(pref_size);
+                }
+                Mounted::Element { key, element: Rc::new(RefCell::new(element)) }
+            }
+            View::Handler { key, construct } => Mounted::Handler {
+                key,
+                handler: construct(),
+            },
+            View::Fragment(_) => unreachable!("fragments are flattened before mounting"),
+        }
+    }
+
+    fn key(&self) -> Option<u64> {
+        match self {
+            Mounted::Container { key, .. } | Mounted::Element { key, .. } => *key,
+            Mounted::Handler { key, .. } => Some(*key),
+        }
+    }
+
+    fn handler_ref(&self) -> UIHandlerRef {
+        match self {
+            Mounted::Container { container, .. } => container.clone().into(),
+            Mounted::Element { element, .. } => element.clone().into(),
+            Mounted::Handler { handler, .. } => handler.clone(),
+        }
+    }
+
+    /// Updates this node to match the view, or replaces it if they are not of the same kind
+    fn patch(self, view: View) -> Self {
+        match (self, view) {
+            (Mounted::Container { key, container, children }, View::Container { layout, children: new_children, .. }) => {
+                container.borrow_mut().layout = layout;
+                let children = Self::reconcile_children(&container, children, new_children);
+                Mounted::Container { key, container, children }
+            }
+            (Mounted::Element { key, element }, View::Element { element: builder, .. }) => {
+                element.borrow_mut().patch((*builder).build());
+                Mounted::Element { key, element }
+            }
+            (Mounted::Handler { key, handler }, View::Handler { key: new_key, .. }) if key == new_key => {
+                Mounted::Handler { key, handler }
+            }
+            (_, view) => Self::mount(view),
+        }
+    }
+
+    fn reconcile_children(container: &Rc<RefCell<Container>>, old: Vec<Mounted>, new: Vec<View>) -> Vec<Mounted> {
+        let mut keyed = HashMap::new();
+        let mut unkeyed = VecDeque::new();
+        for m in old {
+            match m.key() {
+                Some(key) => { keyed.insert(key, m); }
+                None => unkeyed.push_back(m),
+            }
+        }
+
+        let mut flattened = Vec::new();
+        for v in new {
+            v.flatten(&mut flattened);
+        }
+
+        let mut children = Vec::with_capacity(flattened.len());
+        for view in flattened {
+            let previous = match view.get_key() {
+                Some(key) => keyed.remove(&key),
+                None => unkeyed.pop_front(),
+            };
+            children.push(match previous {
+                Some(previous) => previous.patch(view),
+                None => Self::mount(view),
+            });
+        }
+
+        let mut container = container.borrow_mut();
+        let handlers = container.children();
+        handlers.clear();
+        for c in &children {
+            handlers.push(Box::new(c.handler_ref()));
+        }
+
+        children
+    }
+}
+
+/// Hosts a [`Component`], building its [`View`] and applying it onto the existing tree
+/// whenever the state it depends on changes.
+pub struct ViewHost<C> {
+    component: C,
+    root: Option<(Mounted, UIHandlerRef)>,
+    /// The bindings read by the last build, and the value they had at the time
+    dependencies: Vec<(Option<String>, String, State)>,
+    invalidated: bool,
+    rebuilt: bool,
+}
+
+impl<C: Component> ViewHost<C> {
+    pub fn new(component: C) -> Self {
+        ViewHost {
+            component,
+            root: None,
+            dependencies: Vec::new(),
+            invalidated: false,
+            rebuilt: false,
+        }
+    }
+
+    pub fn component(&mut self) -> &mut C {
+        &mut self.component
+    }
+
+    /// Forces the view to be built again on the next frame
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+
+    unsafe fn should_rebuild(&self) -> bool {
+        if self.root.is_none() || self.invalidated {
+            return true;
+        }
+        for (scope, key, value) in &self.dependencies {
+            let registry = registry(scope);
+            if registry.changed(key) || registry.get(key) != value {
+                return true;
+            }
+        }
+        false
+    }
+
+    unsafe fn rebuild(&mut self) {
+        READS.with_borrow_mut(|reads| *reads = Some(Vec::new()));
+        let view = self.component.view();
+        let reads = READS.with_borrow_mut(|reads| reads.take()).unwrap_or_default();
+
+        self.dependencies = reads.into_iter().map(|(scope, key)| {
+            let value = registry(&scope).get(&key).clone();
+            (scope, key, value)
+        }).collect();
+
+        let root = match self.root.take() {
+            None => Mounted::mount(view),
+            Some((root, _)) => root.patch(view),
+        };
+        let handler = root.handler_ref();
+        self.root = Some((root, handler));
+        self.invalidated = false;
+        self.rebuilt = true;
+    }
+}
+
+impl<C: Component> UIHandler for ViewHost<C> {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        if let Event::PreRender = event {
+            if self.should_rebuild() {
+                self.rebuild();
+            }
+        }
+
+        let result = match &mut self.root {
+            None => EventResult::Ok,
+            Some((_, handler)) => handler.handle(event),
+        };
+
+        if let Event::PostRender = event {
+            self.rebuilt = false;
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        if self.rebuilt {
+            return TickResult::RedrawLayout;
+        }
+        match &mut self.root {
+            None => TickResult::RedrawLayout,
+            Some((_, handler)) => handler.tick(render_pass),
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.root.as_mut().and_then(|(_, handler)| handler.animations())
+    }
+
+    fn bounds(&self) -> Vec4 {
+        match &self.root {
+            None => *Vec4::zero(),
+            Some((_, handler)) => handler.bounds(),
+        }
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        match &self.root {
+            None => LayoutContext::new(),
+            Some((_, handler)) => handler.layout_context(),
+        }
+    }
+}
+
+#[test]
+pub fn mounting_and_patching() {
+    use crate::components::framework::animation::Animation;
+
+    let (first, stale) = (Animation::zero_ref(), Animation::zero_ref());
+    let builder = ElementBuilder::new()
+        .layout_context(LayoutContext { pref_size: Vec2::new(40.0, 20.0), ..LayoutContext::new() })
+        .register_animations(vec![first.clone(), stale.clone()]);
+    let mounted = Mounted::mount(builder.into());
+    let Mounted::Element { element, .. } = &mounted else { panic!("an element view mounts an element") };
+    assert_eq!(element.borrow_mut().bounds().width(), 40.0);
+    assert_eq!(element.borrow_mut().bounds().height(), 20.0);
+
+    // Patching keeps the bounds, and only the animations the new view registers
+    let second = Animation::zero_ref();
+    let builder = ElementBuilder::new().register_animations(vec![first.clone(), second.clone()]);
+    let mounted = mounted.patch(builder.into());
+    let Mounted::Element { element, .. } = &mounted else { panic!("an element view patches an element") };
+    assert_eq!(element.borrow_mut().bounds().width(), 40.0);
+    let mut ids: Vec<u32> = element.borrow_mut().animations().unwrap().all().iter().map(|a| a.borrow().id()).collect();
+    ids.sort();
+    let mut expected = vec![first.borrow().id(), second.borrow().id()];
+    expected.sort();
+    assert_eq!(ids, expected);
+}
@@ -0,0 +1,451 @@
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::image::{fit_rect, Fit};
+use crate::components::render::color::Color;
+use crate::components::render::image::{Frame, SharedTexture};
+use crate::components::render::stack::State::Blend;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+use crate::components::wrapper::texture::Texture;
+
+/// The uv region, from 0 to 1, of cell `index` in a sheet of `columns` by `rows` equally sized cells,
+/// counted row by row from the top left
+pub fn sprite_uv(columns: u32, rows: u32, index: u32) -> Vec4 {
+    let (columns, rows) = (columns.max(1), rows.max(1));
+    let index = index % (columns * rows);
+    let (width, height) = (1.0 / columns as f32, 1.0 / rows as f32);
+    let (column, row) = ((index % columns) as f32, (index / columns) as f32);
+    Vec4::xywh(column * width, row * height, width, height)
+}
+
+/// A texture split into a grid of equally sized animation frames
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    texture: Rc<SharedTexture>,
+    columns: u32,
+    rows: u32,
+    count: u32,
+}
+
+impl SpriteSheet {
+    pub fn new(texture: Rc<SharedTexture>, columns: u32, rows: u32) -> Self {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        SpriteSheet {
+            texture,
+            columns,
+            rows,
+            count: columns * rows,
+        }
+    }
+
+    /// Uses only the first `count` cells, for sheets whose last row is not full
+    pub fn count(mut self, count: u32) -> Self {
+        self.count = count.clamp(1, self.columns * self.rows);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// The uv region of frame `index`
+    pub fn uv(&self, index: usize) -> Vec4 {
+        sprite_uv(self.columns, self.rows, index as u32 % self.count)
+    }
+
+    /// The size of one frame in pixels
+    pub fn frame_size(&self) -> Vec2<f32> {
+        Vec2::new(self.texture.width as f32 / self.columns as f32, self.texture.height as f32 / self.rows as f32)
+    }
+}
+
+/// Which frame of an animation is shown, stepped by the frame delta.
+///
+/// Every frame is shown for its own delay divided by the speed. Without looping, playback stops on the last frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playback {
+    delays: Vec<f32>,
+    frame: usize,
+    /// How long the current frame has been shown
+    elapsed: f32,
+    playing: bool,
+    looping: bool,
+    speed: f32,
+}
+
+impl Playback {
+    /// A looping playback showing frame `i` for `delays[i]` seconds
+    pub fn new(delays: Vec<f32>) -> Self {
+        Playback {
+            delays,
+            frame: 0,
+            elapsed: 0.0,
+            playing: true,
+            looping: true,
+            speed: 1.0,
+        }
+    }
+
+    /// Moves the playback forward by `delta` seconds and returns if the shown frame changed
+    pub fn advance(&mut self, delta: f32) -> bool {
+        let total: f32 = self.delays.iter().sum();
+        if !self.playing || self.delays.len() < 2 || total <= 0.0 {
+            return false;
+        }
+        let start = self.frame;
+        self.elapsed += delta * self.speed;
+        if self.looping && self.elapsed > total {
+            // A whole cycle ends on the same frame, so long pauses between frames skip straight there
+            self.elapsed %= total;
+        }
+        while self.elapsed >= self.delays[self.frame] {
+            if self.frame + 1 == self.delays.len() {
+                if !self.looping {
+                    self.playing = false;
+                    self.elapsed = 0.0;
+                    break;
+                }
+                self.elapsed -= self.delays[self.frame];
+                self.frame = 0;
+            } else {
+                self.elapsed -= self.delays[self.frame];
+                self.frame += 1;
+            }
+        }
+        self.frame != start
+    }
+
+    pub fn play(&mut self) {
+        if !self.looping && self.is_finished() {
+            self.frame = 0;
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Plays from the first frame
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.elapsed = 0.0;
+        self.playing = true;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// If a playback that does not loop has stopped on its last frame
+    pub fn is_finished(&self) -> bool {
+        !self.playing && self.frame + 1 >= self.delays.len()
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// How many times faster than its delays the animation plays
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Shows frame `index` from its start
+    pub fn set_frame(&mut self, index: usize) {
+        self.frame = index.min(self.delays.len().saturating_sub(1));
+        self.elapsed = 0.0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.delays.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.delays.is_empty()
+    }
+}
+
+enum Source {
+    Frames(Vec<Frame>),
+    Sheet(SpriteSheet),
+}
+
+/// An animated GIF, APNG or sprite sheet with playback controls, styled as `AnimatedImage`.
+///
+/// It advances by the frame delta every frame and asks for a redraw only when the shown frame changes.
+/// Without an explicit [`AnimatedImage::size`] it takes the size of one frame.
+pub struct AnimatedImage {
+    element: Element,
+    source: Source,
+    playback: Playback,
+    fit: Fit,
+    tint: Color,
+    size: Option<Vec2<f32>>,
+    changed: bool,
+    /// If the shown frame changed since the last render
+    redraw: bool,
+}
+
+impl AnimatedImage {
+    fn with_source(source: Source, delays: Vec<f32>) -> Self {
+        AnimatedImage {
+            element: ElementBuilder::new()
+                .style_type("AnimatedImage")
+                .build(),
+            source,
+            playback: Playback::new(delays),
+            fit: Fit::default(),
+            tint: Color::from_u32(0xffffffff),
+            size: None,
+            changed: true,
+            redraw: false,
+        }
+    }
+
+    /// Plays decoded frames, each for its own delay
+    pub fn new(frames: Vec<Frame>) -> Self {
+        let delays = frames.iter().map(|frame| frame.delay).collect();
+        AnimatedImage::with_source(Source::Frames(frames), delays)
+    }
+
+    /// Plays the frames of a sprite sheet at `fps` frames per second
+    pub fn sprites(sheet: SpriteSheet, fps: f32) -> Self {
+        let delays = vec![1.0 / fps.max(0.001); sheet.len()];
+        AnimatedImage::with_source(Source::Sheet(sheet), delays)
+    }
+
+    /// The frames of the GIF or APNG file at `path`, decoded once and shared through [`ImageManager`]
+    ///
+    /// [`ImageManager`]: crate::components::render::image::ImageManager
+    pub unsafe fn from_path(path: impl AsRef<Path>) -> Result<Self, String> {
+        Ok(AnimatedImage::new(context().images().load_frames_path(path)?))
+    }
+
+    /// The frames of encoded GIF or APNG bytes, decoded once and shared through [`ImageManager`]
+    ///
+    /// [`ImageManager`]: crate::components::render::image::ImageManager
+    pub unsafe fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Ok(AnimatedImage::new(context().images().load_frames_bytes(bytes)?))
+    }
+
+    pub fn fit(mut self, fit: Fit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// The color every pixel is multiplied with, white to draw the frames unchanged
+    pub fn tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// The size the animation is laid out at instead of the size of a frame
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Some(Vec2::new(width, height));
+        self
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.playback.set_speed(speed);
+        self
+    }
+
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.playback.set_looping(looping);
+        self
+    }
+
+    /// Starts paused on the first frame
+    pub fn paused(mut self) -> Self {
+        self.playback.pause();
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn play(&mut self) {
+        self.playback.play();
+    }
+
+    pub fn pause(&mut self) {
+        self.playback.pause();
+    }
+
+    pub fn restart(&mut self) {
+        self.playback.restart();
+        self.redraw = true;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_playing()
+    }
+
+    /// The playback state, to change the speed, looping or shown frame
+    pub fn playback(&mut self) -> &mut Playback {
+        self.redraw = true;
+        &mut self.playback
+    }
+
+    pub fn set_tint(&mut self, tint: Color) {
+        self.tint = tint;
+        self.redraw = true;
+    }
+
+    pub fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+        self.redraw = true;
+    }
+
+    /// The size of one frame in pixels
+    pub fn frame_size(&self) -> Vec2<f32> {
+        match &self.source {
+            Source::Frames(frames) => frames.first().map_or(Vec2::zero(), |frame| {
+                Vec2::new(frame.texture.width as f32, frame.texture.height as f32)
+            }),
+            Source::Sheet(sheet) => sheet.frame_size(),
+        }
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    /// The texture of the shown frame and the region of it to draw
+    fn current(&self) -> Option<(&Texture, Vec4)> {
+        let index = self.playback.frame();
+        match &self.source {
+            Source::Frames(frames) => frames.get(index).map(|frame| (&**frame.texture, Vec4::ltrb(0.0, 0.0, 1.0, 1.0))),
+            Source::Sheet(sheet) => Some((sheet.texture(), sheet.uv(index))),
+        }
+    }
+}
+
+impl UIHandler for AnimatedImage {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+
+        match event {
+            Event::PreRender => {
+                if self.changed {
+                    self.element.layout_mut().min_size = self.size.unwrap_or(self.frame_size());
+                }
+                if self.playback.advance(context().framework().pre_delta()) {
+                    self.redraw = true;
+                }
+            }
+            Event::Render(RenderPass::Main) => {
+                let Some((texture, cell)) = self.current() else {
+                    return result;
+                };
+                let Some((bounds, uv)) = fit_rect(self.fit, self.element.bounds(), self.frame_size()) else {
+                    return result;
+                };
+                // The fitted region is relative to the frame, which is only part of a sprite sheet
+                let uv = Vec4::ltrb(
+                    cell.left() + uv.left() * cell.width(),
+                    cell.top() + uv.top() * cell.height(),
+                    cell.left() + uv.right() * cell.width(),
+                    cell.top() + uv.bottom() * cell.height(),
+                );
+                let renderer = context().renderer();
+                renderer.stack().push(Blend(true));
+                texture.bind();
+                renderer.draw_texture_rect_uv(bounds, uv, self.tint);
+                Texture::unbind();
+                renderer.stack().pop();
+            }
+            Event::PostRender => {
+                self.changed = false;
+                self.redraw = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else if self.redraw {
+            TickResult::Redraw
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+#[test]
+pub fn playback() {
+    let mut playback = Playback::new(vec![0.1, 0.2, 0.1]);
+    assert!(!playback.advance(0.05));
+    assert!(playback.advance(0.05));
+    assert_eq!(playback.frame(), 1);
+    assert!(playback.advance(0.3));
+    assert_eq!(playback.frame(), 0);
+
+    // A whole cycle and a bit lands on the same frame as the bit alone
+    playback.set_speed(2.0);
+    assert!(playback.advance(0.2 + 0.06));
+    assert_eq!(playback.frame(), 1);
+
+    playback.set_looping(false);
+    playback.set_speed(1.0);
+    playback.advance(1.0);
+    assert_eq!(playback.frame(), 2);
+    assert!(playback.is_finished());
+    playback.play();
+    assert_eq!(playback.frame(), 0);
+
+    playback.pause();
+    assert!(!playback.advance(1.0));
+
+    let uv = sprite_uv(4, 2, 5);
+    assert_eq!((uv.left(), uv.top(), uv.right(), uv.bottom()), (0.25, 0.5, 0.5, 1.0));
+}
@@ -0,0 +1,166 @@
+use crate::components::framework::animation::{Animated, AnimationRegistry, Easing};
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::{draw_label, draw_rect, measure, shade, Interaction, Palette, ANIMATION_SPEED};
+use crate::components::render::color::Color;
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// A clickable label, styled as `Button`.
+///
+/// Activated by clicking it, or with Space or Enter while focused.
+pub struct Button {
+    element: Element,
+    interaction: Interaction,
+    label: String,
+    min_width: f32,
+    changed: bool,
+
+    background: Animated<Color>,
+    rect: Option<Rect>,
+
+    on_click: Option<Box<dyn FnMut()>>,
+}
+
+impl Button {
+    pub fn new(label: impl ToString) -> Self {
+        let background = Animated::new(Color::from_u32(0));
+        Button {
+            element: ElementBuilder::new()
+                .style_type("Button")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(80.0, 28.0),
+                    padding: Vec4::ltrb(12.0, 6.0, 12.0, 6.0),
+                    ..LayoutContext::new()
+                })
+                .register_animations(vec![background.animation()])
+                .build(),
+            interaction: Interaction::new(),
+            label: label.to_string(),
+            min_width: 80.0,
+            changed: true,
+            background,
+            rect: None,
+            on_click: None,
+        }
+    }
+
+    pub fn on_click<F: FnMut() + 'static>(mut self, on_click: F) -> Self {
+        self.on_click = Some(Box::new(on_click));
+        self
+    }
+
+    /// The width the button keeps when its label is shorter
+    pub fn min_width(mut self, min_width: f32) -> Self {
+        self.min_width = min_width;
+        self.changed = true;
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.set_disabled(disabled);
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn set_label(&mut self, label: impl ToString) {
+        self.label = label.to_string();
+        self.changed = true;
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.element.disabled()
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.element.set_disabled(disabled);
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    /// Calls the click callback, as if the button was clicked
+    pub fn click(&mut self) {
+        if let Some(on_click) = &mut self.on_click {
+            on_click();
+        }
+    }
+}
+
+impl UIHandler for Button {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+        if self.interaction.handle(&mut self.element, event) {
+            self.click();
+        }
+
+        match event {
+            Event::PreRender => {
+                let style = self.element.style().clone();
+                if self.changed {
+                    let padding = self.element.layout_mut().padding;
+                    let width = measure(&style, &self.label).x + padding.left() + padding.right();
+                    self.element.layout_mut().min_size.x = self.min_width.max(width);
+                }
+
+                let palette = Palette::of(&style);
+                let pressed = self.interaction.is_pressed() && self.element.hovering();
+                let target = shade(palette.background, self.element.hovering(), pressed, self.is_disabled());
+                if self.rect.is_none() {
+                    self.background.set(target);
+                }
+                self.background.animate_to(target, ANIMATION_SPEED, Easing::Sin);
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.element.style().clone();
+                let palette = Palette::of(&style);
+                let bounds = self.element.bounds();
+                let foreground = shade(palette.foreground, false, false, self.is_disabled());
+
+                draw_rect(&mut self.rect, bounds, self.background.value(), style.number("radius").unwrap_or(4.0));
+                draw_label(&style, &self.label, bounds, 0.5, foreground);
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
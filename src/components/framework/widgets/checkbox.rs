@@ -0,0 +1,168 @@
+use crate::components::framework::animation::{Animated, AnimationRegistry, Easing, Interpolate};
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::{draw_label, draw_rect, inset, measure, shade, Interaction, Palette, ANIMATION_SPEED};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+const BOX_SIZE: f32 = 18.0;
+const LABEL_GAP: f32 = 8.0;
+
+/// A box that is checked and unchecked by clicking it or its label, styled as `Checkbox`
+pub struct Checkbox {
+    element: Element,
+    interaction: Interaction,
+    label: String,
+    checked: bool,
+    changed: bool,
+
+    check: Animated<f32>,
+    rects: [Option<Rect>; 3],
+
+    on_change: Option<Box<dyn FnMut(bool)>>,
+}
+
+impl Checkbox {
+    pub fn new(label: impl ToString, checked: bool) -> Self {
+        let check = Animated::new(if checked { 1.0 } else { 0.0 });
+        Checkbox {
+            element: ElementBuilder::new()
+                .style_type("Checkbox")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(BOX_SIZE, 24.0),
+                    ..LayoutContext::new()
+                })
+                .register_animations(vec![check.animation()])
+                .build(),
+            interaction: Interaction::new(),
+            label: label.to_string(),
+            checked,
+            changed: true,
+            check,
+            rects: [None, None, None],
+            on_change: None,
+        }
+    }
+
+    /// Called with the new value every time the box is checked or unchecked
+    pub fn on_change<F: FnMut(bool) + 'static>(mut self, on_change: F) -> Self {
+        self.on_change = Some(Box::new(on_change));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.set_disabled(disabled);
+        self
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    /// Sets the value without calling the change callback
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+        self.changed = true;
+    }
+
+    pub fn toggle(&mut self) {
+        self.set_checked(!self.checked);
+        if let Some(on_change) = &mut self.on_change {
+            on_change(self.checked);
+        }
+    }
+
+    pub fn set_label(&mut self, label: impl ToString) {
+        self.label = label.to_string();
+        self.changed = true;
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.element.disabled()
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.element.set_disabled(disabled);
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+}
+
+impl UIHandler for Checkbox {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+        if self.interaction.handle(&mut self.element, event) {
+            self.toggle();
+        }
+
+        match event {
+            Event::PreRender => {
+                if self.changed {
+                    let width = BOX_SIZE + LABEL_GAP + measure(self.element.style(), &self.label).x;
+                    self.element.layout_mut().min_size.x = width;
+                }
+                self.check.animate_to(if self.checked { 1.0 } else { 0.0 }, ANIMATION_SPEED, Easing::Sin);
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.element.style().clone();
+                let palette = Palette::of(&style);
+                let bounds = self.element.bounds();
+                let (hovering, disabled) = (self.element.hovering(), self.is_disabled());
+                let pressed = self.interaction.is_pressed() && hovering;
+                let check = self.check.value();
+
+                let outer = Vec4::xywh(bounds.x(), bounds.center_y() - BOX_SIZE / 2.0, BOX_SIZE, BOX_SIZE);
+                let border = shade(palette.border.interpolate(&palette.accent, check), hovering, pressed, disabled);
+                draw_rect(&mut self.rects[0], outer, border, 4.0);
+                let inner = inset(outer, 2.0);
+                draw_rect(&mut self.rects[1], inner, shade(palette.background, false, false, disabled), 3.0);
+                if check > 0.01 {
+                    let fill = inset(inner, 2.0 + (1.0 - check) * (inner.width() / 2.0 - 2.0));
+                    draw_rect(&mut self.rects[2], fill, shade(palette.accent, false, false, disabled), 2.0);
+                }
+
+                let label = Vec4::ltrb(outer.right() + LABEL_GAP, bounds.top(), bounds.right(), bounds.bottom());
+                draw_label(&style, &self.label, label, 0.0, shade(palette.foreground, false, false, disabled));
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
@@ -0,0 +1,221 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use glfw::{Action, Key};
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::Element;
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::popup::{window_bounds, PopupId};
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::menu::{MenuItem, MenuList};
+use crate::components::framework::widgets::text_input::TextInput;
+use crate::components::spatial::vec4::Vec4;
+
+/// The indices of the options containing `query`, ignoring case
+pub fn filter_options(options: &[String], query: &str) -> Vec<usize> {
+    let query = query.to_lowercase();
+    options.iter().enumerate()
+        .filter(|(_, option)| option.to_lowercase().contains(&query))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// A [`TextInput`] suggesting the options that contain what was typed in a list below it.
+///
+/// Typing opens the list, Up and Down move through it and Enter or a click picks an option,
+/// which replaces the text. Any text is allowed, not only the options.
+pub struct ComboBox {
+    input: TextInput,
+    options: Vec<String>,
+    last_text: String,
+
+    popup: Option<(PopupId, Rc<RefCell<MenuList>>)>,
+    /// Set by the items of the open list, as they run while the popup is handled
+    chosen: Rc<Cell<Option<usize>>>,
+
+    on_select: Option<Box<dyn FnMut(usize)>>,
+}
+
+impl ComboBox {
+    pub fn new<S: ToString>(options: impl IntoIterator<Item=S>, text: impl ToString) -> Self {
+        let text = text.to_string();
+        ComboBox {
+            input: TextInput::new(&text),
+            options: options.into_iter().map(|o| o.to_string()).collect(),
+            last_text: text,
+            popup: None,
+            chosen: Rc::new(Cell::new(None)),
+            on_select: None,
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl ToString) -> Self {
+        self.input = self.input.placeholder(placeholder);
+        self
+    }
+
+    /// Called with the index of the option every time one is picked from the list
+    pub fn on_select<F: FnMut(usize) + 'static>(mut self, on_select: F) -> Self {
+        self.on_select = Some(Box::new(on_select));
+        self
+    }
+
+    /// Called with the text every time it is typed
+    pub fn on_change<F: FnMut(&str) + 'static>(mut self, on_change: F) -> Self {
+        self.input = self.input.on_change(on_change);
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.input = self.input.class(class);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.input = self.input.disabled(disabled);
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        self.input.text()
+    }
+
+    /// Sets the text without opening the list or calling the change callback
+    pub fn set_text(&mut self, text: impl ToString) {
+        self.input.set_text(text);
+        self.last_text = self.input.text().to_string();
+    }
+
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    /// Replaces the options, refreshing the list if it is open
+    pub unsafe fn set_options<S: ToString>(&mut self, options: impl IntoIterator<Item=S>) {
+        self.options = options.into_iter().map(|o| o.to_string()).collect();
+        // A choice from the old list may not point at the same option anymore
+        self.chosen.set(None);
+        if self.popup.is_some() {
+            self.update_list();
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.popup.is_some()
+    }
+
+    pub fn input(&mut self) -> &mut TextInput {
+        &mut self.input
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        self.input.element()
+    }
+
+    fn items(&self) -> Vec<MenuItem> {
+        filter_options(&self.options, self.input.text()).into_iter().map(|i| {
+            let chosen = self.chosen.clone();
+            MenuItem::new(&self.options[i], move || chosen.set(Some(i)))
+        }).collect()
+    }
+
+    /// Shows the options matching the text, or closes the list when there are none
+    pub unsafe fn update_list(&mut self) {
+        let items = self.items();
+        if items.is_empty() {
+            self.close();
+            return;
+        }
+        match &self.popup {
+            Some((_, list)) => list.borrow_mut().set_items(items),
+            None => {
+                let bounds = window_bounds(self.input.bounds());
+                let (popup, list) = MenuList::new(items)
+                    .min_width(bounds.width())
+                    .keyboard(false)
+                    .into_shared_popup(bounds);
+                // The text input keeps the keyboard while the list is open
+                let id = context().framework().open_popup(popup.grab_keyboard(false));
+                self.popup = Some((id, list));
+            }
+        }
+    }
+
+    pub unsafe fn close(&mut self) {
+        if let Some((id, _)) = self.popup.take() {
+            context().framework().close_popup(id);
+        }
+    }
+}
+
+impl UIHandler for ComboBox {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        // The list takes the arrow keys and Enter before the text input sees them
+        if let (Event::Keyboard(key, Action::Press | Action::Repeat, _), Some((_, list))) = (event, &self.popup) {
+            let list = list.clone();
+            match key {
+                Key::Down => list.borrow_mut().move_highlight(1),
+                Key::Up => list.borrow_mut().move_highlight(-1),
+                Key::Enter if list.borrow().highlighted_index().is_some() => list.borrow_mut().choose(),
+                _ => return self.input.handle(event),
+            }
+            return EventResult::Used;
+        }
+        if let (Event::Keyboard(Key::Down, Action::Press, _), None) = (event, &self.popup) {
+            if self.input.element().focused() {
+                self.update_list();
+            }
+        }
+
+        let result = self.input.handle(event);
+
+        if let Event::PreRender = event {
+            if self.popup.as_ref().is_some_and(|(id, _)| !context().framework().is_popup_open(*id)) {
+                self.popup = None;
+            }
+            if let Some((index, option)) = self.chosen.take().and_then(|i| Some((i, self.options.get(i)?.clone()))) {
+                self.close();
+                self.input.set_text(option);
+                self.last_text = self.input.text().to_string();
+                if let Some(on_select) = &mut self.on_select {
+                    on_select(index);
+                }
+            }
+            if self.input.text() != self.last_text {
+                self.last_text = self.input.text().to_string();
+                if self.input.element().focused() {
+                    self.update_list();
+                }
+            }
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        self.input.tick(render_pass)
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.input.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.input.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.input.layout_context()
+    }
+}
+
+#[test]
+pub fn filtering() {
+    let options: Vec<String> = ["Apple", "Banana", "Pineapple", "Cherry"].iter().map(|o| o.to_string()).collect();
+    assert_eq!(filter_options(&options, "apple"), vec![0, 2]);
+    assert_eq!(filter_options(&options, "AN"), vec![1]);
+    assert_eq!(filter_options(&options, ""), vec![0, 1, 2, 3]);
+    assert!(filter_options(&options, "kiwi").is_empty());
+}
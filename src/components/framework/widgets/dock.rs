@@ -0,0 +1,280 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::popup::window_bounds;
+use crate::components::framework::ui_traits::{TickResult, UIHandler, UIHandlerRef};
+use crate::components::framework::widgets::split_pane::{Pane, SplitPane};
+use crate::components::framework::widgets::tab_view::{Tab, TabView};
+use crate::components::framework::widgets::{draw_rect, Palette};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// How close to an edge of a [`DockSpace`] a tab has to be dropped to dock into a collapsed region
+const EDGE_MARGIN: f32 = 40.0;
+
+/// The regions of a [`DockSpace`] panels can be docked in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DockRegion {
+    Left,
+    Center,
+    Right,
+    Bottom,
+}
+
+impl DockRegion {
+    pub const ALL: [DockRegion; 4] = [DockRegion::Left, DockRegion::Center, DockRegion::Right, DockRegion::Bottom];
+
+    fn index(&self) -> usize {
+        match self {
+            DockRegion::Left => 0,
+            DockRegion::Center => 1,
+            DockRegion::Right => 2,
+            DockRegion::Bottom => 3,
+        }
+    }
+}
+
+/// The side region whose edge of `bounds` is within `margin` of `pos`, the center never has an edge
+pub fn edge_region(bounds: Vec4, pos: Vec2<f32>, margin: f32) -> Option<DockRegion> {
+    if !pos.intersects(&bounds) {
+        None
+    } else if pos.x < bounds.left() + margin {
+        Some(DockRegion::Left)
+    } else if pos.x > bounds.right() - margin {
+        Some(DockRegion::Right)
+    } else if pos.y > bounds.bottom() - margin {
+        Some(DockRegion::Bottom)
+    } else {
+        None
+    }
+}
+
+/// A tool layout of a center region surrounded by side panels, where the tabs of every region can
+/// be dragged into the others.
+///
+/// Each region is a [`TabView`], with [`SplitPane`]s between them. The left panel spans the whole
+/// height and the bottom panel is below the center and right ones. Side regions collapse when their
+/// last tab leaves, and dropping a tab close to the edge of a collapsed region brings it back.
+pub struct DockSpace {
+    root: SplitPane,
+    middle: Rc<RefCell<SplitPane>>,
+    inner: Rc<RefCell<SplitPane>>,
+    regions: [Rc<RefCell<TabView>>; 4],
+
+    /// Where the tab being dragged out of its region would be docked
+    preview: Option<DockRegion>,
+    /// The bounds relative to the window, as of the last [`Event::PreRender`]
+    window_bounds: Vec4,
+    rect: Option<Rect>,
+}
+
+impl DockSpace {
+    pub fn new() -> Self {
+        let regions = DockRegion::ALL.map(|_| Rc::new(RefCell::new(TabView::new([]).detachable(true))));
+        let region = |r: DockRegion| UIHandlerRef::from(regions[r.index()].clone());
+
+        let inner = Rc::new(RefCell::new(
+            SplitPane::horizontal(region(DockRegion::Center), region(DockRegion::Right))
+                .ratio(0.75)
+                .min_sizes(200.0, 120.0)
+                .collapsible(true)
+        ));
+        let middle = Rc::new(RefCell::new(
+            SplitPane::vertical(UIHandlerRef::from(inner.clone()), region(DockRegion::Bottom))
+                .ratio(0.75)
+                .min_sizes(120.0, 80.0)
+                .collapsible(true)
+        ));
+        let root = SplitPane::horizontal(region(DockRegion::Left), UIHandlerRef::from(middle.clone()))
+            .ratio(0.2)
+            .min_sizes(120.0, 200.0)
+            .collapsible(true)
+            .class("DockSpace");
+
+        let mut dock = DockSpace {
+            root,
+            middle,
+            inner,
+            regions,
+            preview: None,
+            window_bounds: *Vec4::zero(),
+            rect: None,
+        };
+        for region in DockRegion::ALL {
+            dock.update_collapsed(region);
+        }
+        dock
+    }
+
+    /// Docks a tab in `region`
+    pub fn with_tab(mut self, region: DockRegion, tab: Tab) -> Self {
+        self.add_tab(region, tab);
+        self
+    }
+
+    /// Docks a tab in `region`, showing the region if it was collapsed
+    pub fn add_tab(&mut self, region: DockRegion, tab: Tab) {
+        self.regions[region.index()].borrow_mut().add_tab(tab);
+        self.update_collapsed(region);
+    }
+
+    /// The tab view of a region, to change its tabs or react to them
+    pub fn region(&self, region: DockRegion) -> Rc<RefCell<TabView>> {
+        self.regions[region.index()].clone()
+    }
+
+    /// Moves the tab at `index` of `from` to the end of `to`
+    pub fn move_tab(&mut self, from: DockRegion, index: usize, to: DockRegion) {
+        if from == to {
+            return;
+        }
+        let Some(tab) = self.regions[from.index()].borrow_mut().remove_tab(index) else {
+            return;
+        };
+        self.regions[to.index()].borrow_mut().add_tab(tab);
+        self.update_collapsed(from);
+        self.update_collapsed(to);
+    }
+
+    /// The region a tab dropped at `pos`, relative to the window, is docked in
+    pub fn region_at(&self, pos: Vec2<f32>) -> Option<DockRegion> {
+        // Edges first, so that collapsed regions can be docked into
+        let bounds = self.window_bounds;
+        if let Some(region) = edge_region(bounds, pos, EDGE_MARGIN) {
+            if self.is_collapsed(region) {
+                return Some(region);
+            }
+        }
+        DockRegion::ALL.into_iter().find(|r| pos.intersects(&self.regions[r.index()].borrow().window_bounds()))
+    }
+
+    /// The split that shows or hides a side region
+    fn split_of(&self, region: DockRegion) -> Option<(Pane, Option<Rc<RefCell<SplitPane>>>)> {
+        match region {
+            DockRegion::Left => Some((Pane::First, None)),
+            DockRegion::Right => Some((Pane::Second, Some(self.inner.clone()))),
+            DockRegion::Bottom => Some((Pane::Second, Some(self.middle.clone()))),
+            DockRegion::Center => None,
+        }
+    }
+
+    pub fn is_collapsed(&self, region: DockRegion) -> bool {
+        match self.split_of(region) {
+            Some((pane, Some(split))) => split.borrow().collapsed() == Some(pane),
+            Some((pane, None)) => self.root.collapsed() == Some(pane),
+            None => false,
+        }
+    }
+
+    /// Collapses a side region without tabs and shows one with tabs again
+    fn update_collapsed(&mut self, region: DockRegion) {
+        let empty = self.regions[region.index()].borrow().is_empty();
+        if empty == self.is_collapsed(region) {
+            return;
+        }
+        let Some((pane, split)) = self.split_of(region) else {
+            return;
+        };
+        let update = |split: &mut SplitPane| if empty { split.collapse(pane) } else { split.expand() };
+        match split {
+            Some(split) => update(&mut split.borrow_mut()),
+            None => update(&mut self.root),
+        }
+    }
+
+    /// The area highlighted while a tab would be docked in `region`
+    fn preview_bounds(&self, region: DockRegion) -> Vec4 {
+        let bounds = self.window_bounds;
+        let target = self.regions[region.index()].borrow().window_bounds();
+        if !self.is_collapsed(region) {
+            return target;
+        }
+        let (width, height) = (bounds.width() / 4.0, bounds.height() / 4.0);
+        match region {
+            DockRegion::Left => Vec4::xywh(bounds.x(), bounds.y(), width, bounds.height()),
+            DockRegion::Right => Vec4::xywh(bounds.right() - width, bounds.y(), width, bounds.height()),
+            _ => Vec4::xywh(bounds.x(), bounds.bottom() - height, bounds.width(), height),
+        }
+    }
+}
+
+impl Default for DockSpace {
+    fn default() -> Self {
+        DockSpace::new()
+    }
+}
+
+impl UIHandler for DockSpace {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.root.handle(event);
+        let mouse = *context().window().mouse().pos();
+        let window_mouse = window_bounds(Vec4::xywh(mouse.x, mouse.y, 0.0, 0.0)).xy();
+
+        match event {
+            Event::PreRender => {
+                self.window_bounds = window_bounds(self.root.bounds());
+                // Regions emptied by closing their tabs collapse, the user can still collapse full ones
+                for region in DockRegion::ALL {
+                    if self.regions[region.index()].borrow().is_empty() {
+                        self.update_collapsed(region);
+                    }
+                }
+                let dragging = DockRegion::ALL.into_iter().any(|r| self.regions[r.index()].borrow().dragging_out().is_some());
+                self.preview = if dragging { self.region_at(window_mouse) } else { None };
+            }
+            Event::Render(RenderPass::Main) => {
+                if let Some(region) = self.preview {
+                    // Regions report window bounds, drawn relative to what is handling the dock space
+                    let offset = window_bounds(Vec4::xywh(0.0, 0.0, 0.0, 0.0)).xy();
+                    let mut bounds = self.preview_bounds(region);
+                    bounds.offset(Vec2::new(-offset.x, -offset.y));
+                    let accent = Palette::of(self.root.element().style()).accent;
+                    draw_rect(&mut self.rect, bounds, accent.set_alpha_f32(0.3), 4.0);
+                }
+            }
+            _ => {}
+        }
+
+        for from in DockRegion::ALL {
+            let dropped = self.regions[from.index()].borrow_mut().take_dropped();
+            if let Some((index, pos)) = dropped {
+                if let Some(to) = self.region_at(pos) {
+                    self.move_tab(from, index, to);
+                }
+                self.preview = None;
+            }
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        self.root.tick(render_pass)
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.root.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.root.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.root.layout_context()
+    }
+}
+
+#[test]
+pub fn edge_regions() {
+    let bounds = Vec4::xywh(0.0, 0.0, 400.0, 300.0);
+    assert_eq!(edge_region(bounds, Vec2::new(10.0, 150.0), 40.0), Some(DockRegion::Left));
+    assert_eq!(edge_region(bounds, Vec2::new(390.0, 150.0), 40.0), Some(DockRegion::Right));
+    assert_eq!(edge_region(bounds, Vec2::new(200.0, 290.0), 40.0), Some(DockRegion::Bottom));
+    assert_eq!(edge_region(bounds, Vec2::new(200.0, 150.0), 40.0), None);
+    assert_eq!(edge_region(bounds, Vec2::new(500.0, 150.0), 40.0), None);
+}
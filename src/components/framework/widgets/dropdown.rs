@@ -0,0 +1,241 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use glfw::{Action, Key};
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::popup::{window_bounds, PopupId};
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::menu::{MenuItem, MenuList};
+use crate::components::framework::widgets::{draw_label, draw_rect, inset, measure, shade, Interaction, Palette};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+const ARROW_WIDTH: f32 = 24.0;
+
+/// A field showing the selected option, which opens a list of all options below it, styled as `Dropdown`.
+///
+/// While focused and closed, the arrow keys change the selection and Space or Enter open the list.
+pub struct Dropdown {
+    element: Element,
+    interaction: Interaction,
+    options: Vec<String>,
+    selected: Option<usize>,
+    placeholder: String,
+    changed: bool,
+
+    popup: Option<PopupId>,
+    /// Set by the items of the open list, as they run while the popup is handled
+    chosen: Rc<Cell<Option<usize>>>,
+    rects: [Option<Rect>; 2],
+
+    on_change: Option<Box<dyn FnMut(usize)>>,
+}
+
+impl Dropdown {
+    pub fn new<S: ToString>(options: impl IntoIterator<Item=S>, selected: Option<usize>) -> Self {
+        let options: Vec<String> = options.into_iter().map(|o| o.to_string()).collect();
+        Dropdown {
+            element: ElementBuilder::new()
+                .style_type("Dropdown")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(120.0, 28.0),
+                    padding: Vec4::ltrb(10.0, 6.0, 10.0, 6.0),
+                    ..LayoutContext::new()
+                })
+                .build(),
+            interaction: Interaction::new(),
+            selected: selected.filter(|s| *s < options.len()),
+            options,
+            placeholder: String::new(),
+            changed: true,
+            popup: None,
+            chosen: Rc::new(Cell::new(None)),
+            rects: [None, None],
+            on_change: None,
+        }
+    }
+
+    /// Shown while nothing is selected
+    pub fn placeholder(mut self, placeholder: impl ToString) -> Self {
+        self.placeholder = placeholder.to_string();
+        self
+    }
+
+    /// Called with the index of the option every time the user selects a different one
+    pub fn on_change<F: FnMut(usize) + 'static>(mut self, on_change: F) -> Self {
+        self.on_change = Some(Box::new(on_change));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.set_disabled(disabled);
+        self
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn selected_label(&self) -> Option<&str> {
+        self.selected.map(|i| self.options[i].as_str())
+    }
+
+    /// Sets the selection without calling the change callback
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        let selected = selected.filter(|s| *s < self.options.len());
+        if selected != self.selected {
+            self.selected = selected;
+            self.changed = true;
+        }
+    }
+
+    pub fn set_options<S: ToString>(&mut self, options: impl IntoIterator<Item=S>) {
+        self.options = options.into_iter().map(|o| o.to_string()).collect();
+        self.selected = self.selected.filter(|s| *s < self.options.len());
+        self.changed = true;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.popup.is_some()
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.element.disabled()
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.element.set_disabled(disabled);
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.selected != Some(index) && index < self.options.len() {
+            self.set_selected(Some(index));
+            if let Some(on_change) = &mut self.on_change {
+                on_change(index);
+            }
+        }
+    }
+
+    unsafe fn open(&mut self) {
+        if self.options.is_empty() {
+            return;
+        }
+        let items = self.options.iter().enumerate().map(|(i, option)| {
+            let chosen = self.chosen.clone();
+            MenuItem::new(option, move || chosen.set(Some(i))).checked(self.selected == Some(i))
+        }).collect();
+        let bounds = window_bounds(self.element.bounds());
+        let popup = MenuList::new(items)
+            .min_width(bounds.width())
+            .highlighted(self.selected)
+            .into_popup(bounds);
+        self.popup = Some(context().framework().open_popup(popup));
+        self.changed = true;
+    }
+}
+
+impl UIHandler for Dropdown {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+        if self.interaction.handle(&mut self.element, event) && self.popup.is_none() {
+            self.open();
+        }
+
+        if self.element.focused() && self.element.interactive() && self.popup.is_none() && !self.options.is_empty() {
+            if let Event::Keyboard(key, Action::Press | Action::Repeat, _) = event {
+                let last = self.options.len() - 1;
+                match (key, self.selected) {
+                    (Key::Up, Some(s)) => self.select(s.saturating_sub(1)),
+                    (Key::Down, Some(s)) => self.select((s + 1).min(last)),
+                    (Key::Up | Key::Down, None) => self.select(0),
+                    _ => {}
+                }
+            }
+        }
+
+        match event {
+            Event::PreRender => {
+                if let Some(index) = self.chosen.take() {
+                    self.select(index);
+                }
+                if self.popup.is_some_and(|id| !context().framework().is_popup_open(id)) {
+                    self.popup = None;
+                    self.changed = true;
+                }
+                if self.changed {
+                    let style = self.element.style().clone();
+                    let widest = self.options.iter().chain([&self.placeholder])
+                        .map(|o| measure(&style, o).x)
+                        .fold(0.0, f32::max);
+                    let padding = self.element.layout_mut().padding;
+                    self.element.layout_mut().min_size.x = widest + ARROW_WIDTH + padding.left() + padding.right();
+                }
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.element.style().clone();
+                let palette = Palette::of(&style);
+                let bounds = self.element.bounds();
+                let padding = self.element.layout_mut().padding;
+                let (hovering, disabled) = (self.element.hovering(), self.is_disabled());
+                let pressed = self.interaction.is_pressed() && hovering;
+                let radius = style.number("radius").unwrap_or(4.0);
+
+                let border = if self.element.focused() || self.popup.is_some() { palette.accent } else { palette.border };
+                draw_rect(&mut self.rects[0], bounds, shade(border, false, false, disabled), radius);
+                draw_rect(&mut self.rects[1], inset(bounds, 1.0), shade(palette.background, hovering, pressed, disabled), radius - 1.0);
+
+                let text = Vec4::ltrb(bounds.left() + padding.left(), bounds.top(), bounds.right() - ARROW_WIDTH, bounds.bottom());
+                match self.selected {
+                    Some(index) => draw_label(&style, &self.options[index], text, 0.0, shade(palette.foreground, false, false, disabled)),
+                    None => draw_label(&style, &self.placeholder, text, 0.0, shade(palette.foreground, false, false, true)),
+                }
+                let arrow = Vec4::ltrb(bounds.right() - ARROW_WIDTH, bounds.top(), bounds.right(), bounds.bottom());
+                draw_label(&style, if self.popup.is_some() { "^" } else { "v" }, arrow, 0.5, shade(palette.foreground, false, false, disabled));
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
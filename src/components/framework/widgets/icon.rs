@@ -0,0 +1,212 @@
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::render::color::{solid, Color};
+use crate::components::render::image::SharedTexture;
+use crate::components::render::renderer::Renderable;
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::render::svg::Svg;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// How long, in seconds, the size of an icon has to stay the same before it is rasterized at the new size
+const SETTLE_TIME: f32 = 0.15;
+
+/// An SVG icon rasterized at the size of its bounds, styled as `Icon`.
+///
+/// Without a tint the icon is drawn in its own colors. With one, every shape is drawn in the tint
+/// at its own opacity, so single color icons can follow the theme.
+/// Without an explicit [`Icon::size`] it takes the size of the SVG.
+///
+/// While its size changes, the last rasterized texture is stretched over the bounds,
+/// and the icon is only rasterized again once the size stays the same for a moment.
+pub struct Icon {
+    element: Element,
+    svg: Rc<Svg>,
+    tint: Option<Color>,
+    size: Option<Vec2<f32>>,
+    changed: bool,
+
+    texture: Option<Rc<SharedTexture>>,
+    /// The pixel size and mask mode `texture` was rasterized with
+    rasterized: (u32, u32, bool),
+    /// The pixel size of the bounds in the last frame, and for how long it has not changed
+    last_size: (u32, u32),
+    settling: f32,
+    rect: Option<Rect>,
+}
+
+impl Icon {
+    pub fn new(svg: Rc<Svg>) -> Self {
+        Icon {
+            element: ElementBuilder::new()
+                .style_type("Icon")
+                .build(),
+            svg,
+            tint: None,
+            size: None,
+            changed: true,
+            texture: None,
+            rasterized: (0, 0, false),
+            last_size: (0, 0),
+            settling: 0.0,
+            rect: None,
+        }
+    }
+
+    /// An icon of the SVG file at `path`, parsed once and shared through [`ImageManager`]
+    ///
+    /// [`ImageManager`]: crate::components::render::image::ImageManager
+    pub unsafe fn from_path(path: impl AsRef<Path>) -> Result<Self, String> {
+        Ok(Icon::new(context().images().load_svg_path(path)?))
+    }
+
+    /// An icon of an SVG document, parsed once and shared through [`ImageManager`]
+    ///
+    /// [`ImageManager`]: crate::components::render::image::ImageManager
+    pub unsafe fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Ok(Icon::new(context().images().load_svg_bytes(bytes)?))
+    }
+
+    /// The color every shape is drawn in, keeping their opacity
+    pub fn tint(mut self, tint: Color) -> Self {
+        self.set_tint(Some(tint));
+        self
+    }
+
+    /// The size the icon is laid out and rasterized at instead of the size of the SVG
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Some(Vec2::new(width, height));
+        self.changed = true;
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn svg(&self) -> &Rc<Svg> {
+        &self.svg
+    }
+
+    pub fn set_svg(&mut self, svg: Rc<Svg>) {
+        self.svg = svg;
+        self.texture = None;
+        self.changed = true;
+    }
+
+    pub fn get_tint(&self) -> Option<Color> {
+        self.tint
+    }
+
+    pub fn set_tint(&mut self, tint: Option<Color>) {
+        self.tint = tint;
+        self.changed = true;
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    /// The bounds rounded to whole pixels, so the texture maps one to one onto the screen
+    fn pixel_bounds(&self) -> Vec4 {
+        let bounds = self.element.bounds();
+        Vec4::xywh(bounds.x().round(), bounds.y().round(), bounds.width().round(), bounds.height().round())
+    }
+
+    /// The pixel size and mask mode the icon should be rasterized with
+    fn wanted(&self) -> (u32, u32, bool) {
+        let bounds = self.pixel_bounds();
+        (bounds.width().max(0.0) as u32, bounds.height().max(0.0) as u32, self.tint.is_some())
+    }
+
+    /// Rasterizes the icon again when it has none, its tint mode changed, or its size settled
+    unsafe fn update_texture(&mut self) {
+        let wanted = self.wanted();
+        if wanted.0 == 0 || wanted.1 == 0 || (wanted == self.rasterized && self.texture.is_some()) {
+            self.settling = 0.0;
+            return;
+        }
+        if (wanted.0, wanted.1) == self.last_size {
+            self.settling += context().framework().pre_delta();
+        } else {
+            self.last_size = (wanted.0, wanted.1);
+            self.settling = 0.0;
+        }
+        if self.texture.is_none() || wanted.2 != self.rasterized.2 || self.settling >= SETTLE_TIME {
+            self.texture = Some(context().images().svg_texture(&self.svg, wanted.0, wanted.1, wanted.2));
+            self.rasterized = wanted;
+            self.settling = 0.0;
+        }
+    }
+}
+
+impl UIHandler for Icon {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+
+        match event {
+            Event::PreRender => {
+                if self.changed {
+                    self.element.layout_mut().min_size = self.size.unwrap_or(self.svg.size());
+                }
+                self.update_texture();
+            }
+            Event::Render(RenderPass::Main) => {
+                let Some(texture) = &self.texture else {
+                    return result;
+                };
+                let bounds = self.pixel_bounds();
+                let color = solid(self.tint.unwrap_or(Color::from_u32(0xffffffff)));
+                let rect = match &mut self.rect {
+                    Some(rect) => rect,
+                    None => self.rect.insert(Rect::new(bounds, color)),
+                };
+                rect.set_bounds(&bounds);
+                rect.set_colors(color);
+                rect.set_texture(Some(texture), Vec4::ltrb(0.0, 0.0, 1.0, 1.0));
+                rect.pre_render();
+                rect.render();
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else if self.texture.is_some() && self.wanted() != self.rasterized {
+            // Keeps frames coming until the new size settles
+            TickResult::Redraw
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
@@ -0,0 +1,271 @@
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::render::color::{solid, Color};
+use crate::components::render::image::SharedTexture;
+use crate::components::render::renderer::Renderable;
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+use crate::components::wrapper::texture::Texture;
+
+/// How an [`Image`] is sized into bounds with a different aspect ratio
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Fit {
+    /// Scaled to be fully visible, leaving empty space on two sides
+    #[default]
+    Contain,
+    /// Scaled to fill the bounds, cropping two sides
+    Cover,
+    /// Scaled to the bounds, ignoring the aspect ratio
+    Stretch,
+    /// Kept at its own size and centered, cropped if larger than the bounds
+    None,
+}
+
+/// Where an image of `size` is drawn in `bounds`, and the region of it, from 0 to 1, that is visible there.
+///
+/// Returns `None` when nothing of the image would be visible.
+pub fn fit_rect(fit: Fit, bounds: Vec4, size: Vec2<f32>) -> Option<(Vec4, Vec4)> {
+    if size.x <= 0.0 || size.y <= 0.0 || bounds.width() <= 0.0 || bounds.height() <= 0.0 {
+        return None;
+    }
+    let (scale_x, scale_y) = (bounds.width() / size.x, bounds.height() / size.y);
+    let scale = match fit {
+        Fit::Contain => scale_x.min(scale_y),
+        Fit::Cover => scale_x.max(scale_y),
+        Fit::Stretch | Fit::None => 1.0,
+    };
+    let scaled = match fit {
+        Fit::Stretch => bounds.wh(),
+        _ => Vec2::new(size.x * scale, size.y * scale),
+    };
+    let image = Vec4::xywh(
+        bounds.center_x() - scaled.x / 2.0,
+        bounds.center_y() - scaled.y / 2.0,
+        scaled.x,
+        scaled.y,
+    );
+
+    // Crop whatever sticks out of the bounds, both on screen and in the texture
+    let visible = Vec4::ltrb(
+        image.left().max(bounds.left()),
+        image.top().max(bounds.top()),
+        image.right().min(bounds.right()),
+        image.bottom().min(bounds.bottom()),
+    );
+    let uv = Vec4::ltrb(
+        (visible.left() - image.left()) / image.width(),
+        (visible.top() - image.top()) / image.height(),
+        (visible.right() - image.left()) / image.width(),
+        (visible.bottom() - image.top()) / image.height(),
+    );
+    Some((visible, uv))
+}
+
+/// A texture drawn with a [`Fit`], rounded corners and a tint, styled as `Image`.
+///
+/// Without an explicit [`Image::size`] it takes the size of its texture.
+pub struct Image {
+    element: Element,
+    texture: Option<Rc<SharedTexture>>,
+    fit: Fit,
+    radius: f32,
+    tint: Color,
+    size: Option<Vec2<f32>>,
+    changed: bool,
+
+    rect: Option<Rect>,
+}
+
+impl Image {
+    pub fn new(texture: Option<Rc<SharedTexture>>) -> Self {
+        Image {
+            element: ElementBuilder::new()
+                .style_type("Image")
+                .build(),
+            texture,
+            fit: Fit::default(),
+            radius: 0.0,
+            tint: Color::from_u32(0xffffffff),
+            size: None,
+            changed: true,
+            rect: None,
+        }
+    }
+
+    /// An image of the file at `path`, decoded once and shared through [`ImageManager`]
+    ///
+    /// [`ImageManager`]: crate::components::render::image::ImageManager
+    pub unsafe fn from_path(path: impl AsRef<Path>) -> Result<Self, String> {
+        Ok(Image::new(Some(context().images().load_path(path)?)))
+    }
+
+    /// An image of encoded PNG, JPEG or GIF bytes, decoded once and shared through [`ImageManager`]
+    ///
+    /// [`ImageManager`]: crate::components::render::image::ImageManager
+    pub unsafe fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Ok(Image::new(Some(context().images().load_bytes(bytes)?)))
+    }
+
+    pub fn fit(mut self, fit: Fit) -> Self {
+        self.set_fit(fit);
+        self
+    }
+
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.set_radius(radius);
+        self
+    }
+
+    /// The color every pixel is multiplied with, white to draw the image unchanged
+    pub fn tint(mut self, tint: Color) -> Self {
+        self.set_tint(tint);
+        self
+    }
+
+    /// The size the image is laid out at instead of the size of its texture
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Some(Vec2::new(width, height));
+        self.changed = true;
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn texture(&self) -> Option<&Texture> {
+        self.texture.as_deref().map(|texture| &**texture)
+    }
+
+    pub fn set_texture(&mut self, texture: Option<Rc<SharedTexture>>) {
+        self.texture = texture;
+        self.changed = true;
+    }
+
+    pub fn get_fit(&self) -> Fit {
+        self.fit
+    }
+
+    pub fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+        self.changed = true;
+    }
+
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius;
+        self.changed = true;
+    }
+
+    pub fn get_tint(&self) -> Color {
+        self.tint
+    }
+
+    pub fn set_tint(&mut self, tint: Color) {
+        self.tint = tint;
+        self.changed = true;
+    }
+
+    /// The size of the texture, zero without one
+    pub fn natural_size(&self) -> Vec2<f32> {
+        match &self.texture {
+            Some(texture) => Vec2::new(texture.width as f32, texture.height as f32),
+            None => Vec2::zero(),
+        }
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+}
+
+impl UIHandler for Image {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+
+        match event {
+            Event::PreRender if self.changed => {
+                self.element.layout_mut().min_size = self.size.unwrap_or(self.natural_size());
+            }
+            Event::Render(RenderPass::Main) => {
+                let Some(texture) = &self.texture else {
+                    return result;
+                };
+                let Some((bounds, uv)) = fit_rect(self.fit, self.element.bounds(), self.natural_size()) else {
+                    return result;
+                };
+                // Rounds the visible part of the image, which is smaller than the bounds when contained
+                let rect = match &mut self.rect {
+                    Some(rect) => rect,
+                    None => self.rect.insert(Rect::new(bounds, solid(self.tint))),
+                };
+                rect.set_bounds(&bounds);
+                rect.set_colors(solid(self.tint));
+                rect.set_radius(self.radius.min(bounds.width().min(bounds.height()) / 2.0));
+                rect.set_texture(Some(texture), uv);
+                rect.pre_render();
+                rect.render();
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+#[test]
+pub fn fitting() {
+    let bounds = Vec4::xywh(0.0, 0.0, 200.0, 100.0);
+    let size = Vec2::new(100.0, 100.0);
+
+    let (rect, uv) = fit_rect(Fit::Contain, bounds, size).unwrap();
+    assert_eq!((rect.x(), rect.y(), rect.width(), rect.height()), (50.0, 0.0, 100.0, 100.0));
+    assert_eq!((uv.left(), uv.top(), uv.right(), uv.bottom()), (0.0, 0.0, 1.0, 1.0));
+
+    let (rect, uv) = fit_rect(Fit::Cover, bounds, size).unwrap();
+    assert_eq!((rect.x(), rect.y(), rect.width(), rect.height()), (0.0, 0.0, 200.0, 100.0));
+    assert_eq!((uv.left(), uv.top(), uv.right(), uv.bottom()), (0.0, 0.25, 1.0, 0.75));
+
+    let (rect, _) = fit_rect(Fit::Stretch, bounds, size).unwrap();
+    assert_eq!((rect.width(), rect.height()), (200.0, 100.0));
+
+    let (rect, uv) = fit_rect(Fit::None, bounds, Vec2::new(400.0, 50.0)).unwrap();
+    assert_eq!((rect.x(), rect.y(), rect.width(), rect.height()), (0.0, 25.0, 200.0, 50.0));
+    assert_eq!((uv.left(), uv.right()), (0.25, 0.75));
+
+    assert_eq!(fit_rect(Fit::Contain, bounds, Vec2::zero()), None);
+}
@@ -0,0 +1,499 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use glfw::{Action, Key, MouseButton};
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::popup::{Placement, Popup, PopupId};
+use crate::components::framework::ui_traits::{random_id, TickResult, UIHandler, UIHandlerRef};
+use crate::components::framework::widgets::{draw_label, draw_rect, inset, measure, shade, Palette};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+const ROW_HEIGHT: f32 = 26.0;
+const SEPARATOR_HEIGHT: f32 = 9.0;
+const PADDING: f32 = 4.0;
+/// The space on the left of labels, where check marks are drawn
+const CHECK_WIDTH: f32 = 24.0;
+/// The space on the right of labels, where submenu arrows are drawn
+const ARROW_WIDTH: f32 = 20.0;
+
+/// An entry of a [`MenuList`]
+#[derive(Clone)]
+pub enum MenuItem {
+    Action {
+        label: String,
+        enabled: bool,
+        checked: bool,
+        action: Rc<RefCell<dyn FnMut()>>,
+    },
+    Submenu {
+        label: String,
+        enabled: bool,
+        items: Vec<MenuItem>,
+    },
+    Separator,
+}
+
+impl MenuItem {
+    /// An item running `action` when it is chosen
+    pub fn new<F: FnMut() + 'static>(label: impl ToString, action: F) -> Self {
+        MenuItem::Action {
+            label: label.to_string(),
+            enabled: true,
+            checked: false,
+            action: Rc::new(RefCell::new(action)),
+        }
+    }
+
+    /// An item opening another menu next to it
+    pub fn submenu(label: impl ToString, items: Vec<MenuItem>) -> Self {
+        MenuItem::Submenu {
+            label: label.to_string(),
+            enabled: true,
+            items,
+        }
+    }
+
+    pub fn separator() -> Self {
+        MenuItem::Separator
+    }
+
+    pub fn enabled(mut self, value: bool) -> Self {
+        match &mut self {
+            MenuItem::Action { enabled, .. } | MenuItem::Submenu { enabled, .. } => *enabled = value,
+            MenuItem::Separator => {}
+        }
+        self
+    }
+
+    /// Shows a check mark in front of an action
+    pub fn checked(mut self, value: bool) -> Self {
+        if let MenuItem::Action { checked, .. } = &mut self {
+            *checked = value;
+        }
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            MenuItem::Action { label, .. } | MenuItem::Submenu { label, .. } => label,
+            MenuItem::Separator => "",
+        }
+    }
+
+    /// If the item can be highlighted and chosen
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            MenuItem::Action { enabled, .. } | MenuItem::Submenu { enabled, .. } => *enabled,
+            MenuItem::Separator => false,
+        }
+    }
+
+    fn height(&self) -> f32 {
+        match self {
+            MenuItem::Separator => SEPARATOR_HEIGHT,
+            _ => ROW_HEIGHT,
+        }
+    }
+}
+
+/// The next enabled item from `from` in the direction of `step`, wrapping around the ends
+fn next_enabled(items: &[MenuItem], from: Option<usize>, step: isize) -> Option<usize> {
+    let len = items.len() as isize;
+    let mut index = match from {
+        Some(from) => from as isize,
+        None if step > 0 => -1,
+        None => len,
+    };
+    for _ in 0..len {
+        index = (index + step).rem_euclid(len);
+        if items[index as usize].is_enabled() {
+            return Some(index as usize);
+        }
+    }
+    None
+}
+
+/// A vertical list of [`MenuItem`]s shown in a [`Popup`], styled as `Menu`.
+///
+/// Hovering a submenu opens it to the side. Choosing an action runs it and closes every menu
+/// up to the first one. While it is the top-most popup, the arrow keys move the highlight,
+/// Enter and Space choose, Right opens a submenu and Left closes this one if it is a submenu.
+pub struct MenuList {
+    element: Element,
+    items: Vec<MenuItem>,
+    highlighted: Option<usize>,
+    min_width: f32,
+    keyboard: bool,
+    changed: bool,
+
+    popup: PopupId,
+    root: PopupId,
+    submenu: Option<(usize, PopupId)>,
+    last_mouse: Vec2<f32>,
+
+    background: [Option<Rect>; 2],
+    highlight: Option<Rect>,
+    rects: Vec<Option<Rect>>,
+}
+
+impl MenuList {
+    pub fn new(items: Vec<MenuItem>) -> Self {
+        MenuList {
+            element: ElementBuilder::new()
+                .style_type("Menu")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(120.0, PADDING * 2.0),
+                    ..LayoutContext::new()
+                })
+                .build(),
+            rects: items.iter().map(|_| None).collect(),
+            items,
+            highlighted: None,
+            min_width: 120.0,
+            keyboard: true,
+            changed: true,
+            popup: 0,
+            root: 0,
+            submenu: None,
+            last_mouse: Vec2::zero(),
+            background: [None, None],
+            highlight: None,
+        }
+    }
+
+    /// The width the menu keeps when its labels are shorter
+    pub fn min_width(mut self, min_width: f32) -> Self {
+        self.min_width = min_width;
+        self
+    }
+
+    /// Starts with an item highlighted, so the keyboard continues from it
+    pub fn highlighted(mut self, index: Option<usize>) -> Self {
+        self.highlighted = index.filter(|i| self.items.get(*i).is_some_and(MenuItem::is_enabled));
+        self
+    }
+
+    /// If the menu handles the keys itself while it is the top-most popup, `true` by default.
+    /// Without it, the owner can drive it with [`MenuList::move_highlight()`] and [`MenuList::choose()`].
+    pub fn keyboard(mut self, keyboard: bool) -> Self {
+        self.keyboard = keyboard;
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    /// Wraps the menu in a popup below `anchor`, which is in window coordinates
+    pub fn into_popup(mut self, anchor: Vec4) -> Popup {
+        let id = self.reserve_id();
+        Popup::new(self, anchor).with_id(id)
+    }
+
+    /// Like [`MenuList::into_popup()`], but keeps access to the menu so its items can change while it is open
+    pub fn into_shared_popup(mut self, anchor: Vec4) -> (Popup, Rc<RefCell<MenuList>>) {
+        let id = self.reserve_id();
+        let list = Rc::new(RefCell::new(self));
+        (Popup::new(UIHandlerRef::from(list.clone()), anchor).with_id(id), list)
+    }
+
+    fn reserve_id(&mut self) -> PopupId {
+        self.popup = random_id();
+        if self.root == 0 {
+            self.root = self.popup;
+        }
+        self.popup
+    }
+
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    /// Replaces the items, keeping the highlight if that item is still there
+    pub unsafe fn set_items(&mut self, items: Vec<MenuItem>) {
+        self.close_submenu();
+        self.rects = items.iter().map(|_| None).collect();
+        self.items = items;
+        self.highlighted = self.highlighted.filter(|i| self.items.get(*i).is_some_and(MenuItem::is_enabled));
+        self.changed = true;
+    }
+
+    pub fn highlighted_index(&self) -> Option<usize> {
+        self.highlighted
+    }
+
+    pub fn set_highlighted(&mut self, index: Option<usize>) {
+        let index = index.filter(|i| self.items.get(*i).is_some_and(MenuItem::is_enabled));
+        if index != self.highlighted {
+            self.highlighted = index;
+            self.changed = true;
+        }
+    }
+
+    /// Moves the highlight to the next enabled item, backwards when `step` is negative
+    pub fn move_highlight(&mut self, step: isize) {
+        self.set_highlighted(next_enabled(&self.items, self.highlighted, step));
+    }
+
+    /// Runs the highlighted action or opens the highlighted submenu
+    pub unsafe fn choose(&mut self) {
+        let Some(index) = self.highlighted else {
+            return;
+        };
+        match &self.items[index] {
+            MenuItem::Action { enabled: true, action, .. } => {
+                let action = action.clone();
+                context().framework().close_popup(self.root);
+                (action.borrow_mut())();
+            }
+            MenuItem::Submenu { enabled: true, .. } => self.open_submenu(index, true),
+            _ => {}
+        }
+    }
+
+    fn row_bounds(&self, index: usize) -> Vec4 {
+        let bounds = self.element.bounds();
+        let y = bounds.y() + PADDING + self.items[..index].iter().map(MenuItem::height).sum::<f32>();
+        Vec4::xywh(bounds.x() + PADDING, y, bounds.width() - PADDING * 2.0, self.items[index].height())
+    }
+
+    fn row_at(&self, pos: &Vec2<f32>) -> Option<usize> {
+        (0..self.items.len()).find(|i| pos.intersects(&self.row_bounds(*i)))
+    }
+
+    unsafe fn open_submenu(&mut self, index: usize, from_keyboard: bool) {
+        if self.submenu.is_some_and(|(i, _)| i == index) {
+            return;
+        }
+        self.close_submenu();
+        let MenuItem::Submenu { items, .. } = &self.items[index] else {
+            return;
+        };
+        let mut list = MenuList::new(items.clone());
+        list.root = self.root;
+        if from_keyboard {
+            list.highlighted = next_enabled(&list.items, None, 1);
+        }
+        let popup = list.into_popup(self.row_bounds(index))
+            .placement(Placement::Right)
+            .gap(PADDING)
+            .parent(self.popup);
+        self.submenu = Some((index, context().framework().open_popup(popup)));
+    }
+
+    unsafe fn close_submenu(&mut self) {
+        if let Some((_, id)) = self.submenu.take() {
+            context().framework().close_popup(id);
+        }
+    }
+}
+
+impl UIHandler for MenuList {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+
+        let on_top = self.keyboard && context().framework().top_popup() == Some(self.popup);
+        match event {
+            Event::Keyboard(key, Action::Press | Action::Repeat, _) if on_top => {
+                match key {
+                    Key::Down => self.move_highlight(1),
+                    Key::Up => self.move_highlight(-1),
+                    Key::Home => self.set_highlighted(next_enabled(&self.items, None, 1)),
+                    Key::End => self.set_highlighted(next_enabled(&self.items, None, -1)),
+                    Key::Enter | Key::Space => self.choose(),
+                    Key::Right => {
+                        if let Some(index) = self.highlighted {
+                            self.open_submenu(index, true);
+                        }
+                    }
+                    Key::Left if self.popup != self.root => context().framework().close_popup(self.popup),
+                    _ => {}
+                }
+            }
+            Event::MouseClick(MouseButton::Button1, Action::Release) => {
+                let pos = *context().window().mouse().pos();
+                if self.element.hovering() && self.row_at(&pos) == self.highlighted {
+                    self.choose();
+                }
+            }
+            Event::PreRender => {
+                // Submenus can also be closed by the framework, with Escape for example
+                if self.submenu.is_some_and(|(_, id)| !context().framework().is_popup_open(id)) {
+                    self.submenu = None;
+                }
+
+                let pos = *context().window().mouse().pos();
+                if pos != self.last_mouse {
+                    self.last_mouse = pos;
+                    if self.element.hovering() {
+                        let row = self.row_at(&pos).filter(|i| self.items[*i].is_enabled());
+                        self.set_highlighted(row);
+                        match row {
+                            Some(index) if matches!(self.items[index], MenuItem::Submenu { .. }) => self.open_submenu(index, false),
+                            Some(_) => self.close_submenu(),
+                            None => {}
+                        }
+                    }
+                }
+
+                if self.changed {
+                    let style = self.element.style().clone();
+                    let widest = self.items.iter().map(|i| measure(&style, i.label()).x).fold(0.0, f32::max);
+                    let layout = self.element.layout_mut();
+                    layout.min_size.x = (CHECK_WIDTH + widest + ARROW_WIDTH + PADDING * 2.0).max(self.min_width);
+                    layout.min_size.y = self.items.iter().map(MenuItem::height).sum::<f32>() + PADDING * 2.0;
+                }
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.element.style().clone();
+                let palette = Palette::of(&style);
+                let bounds = self.element.bounds();
+                draw_rect(&mut self.background[0], bounds, palette.border, 6.0);
+                draw_rect(&mut self.background[1], inset(bounds, 1.0), palette.background, 5.0);
+
+                if let Some(index) = self.highlighted {
+                    let row = self.row_bounds(index);
+                    draw_rect(&mut self.highlight, row, palette.accent, 4.0);
+                }
+                for i in 0..self.items.len() {
+                    let row = self.row_bounds(i);
+                    let open = self.submenu.is_some_and(|(s, _)| s == i);
+                    let color = shade(palette.foreground, open, false, !self.items[i].is_enabled());
+                    match &self.items[i] {
+                        MenuItem::Separator => {
+                            let line = Vec4::xywh(row.x() + PADDING, row.center_y(), row.width() - PADDING * 2.0, 1.0);
+                            draw_rect(&mut self.rects[i], line, palette.border, 0.0);
+                        }
+                        MenuItem::Action { label, checked, .. } => {
+                            if *checked {
+                                let check = Vec4::xywh(row.x() + CHECK_WIDTH / 2.0 - 3.0, row.center_y() - 3.0, 6.0, 6.0);
+                                draw_rect(&mut self.rects[i], check, color, 3.0);
+                            }
+                            let label_bounds = Vec4::ltrb(row.left() + CHECK_WIDTH, row.top(), row.right(), row.bottom());
+                            draw_label(&style, label, label_bounds, 0.0, color);
+                        }
+                        MenuItem::Submenu { label, .. } => {
+                            let label_bounds = Vec4::ltrb(row.left() + CHECK_WIDTH, row.top(), row.right() - ARROW_WIDTH, row.bottom());
+                            draw_label(&style, label, label_bounds, 0.0, color);
+                            let arrow = Vec4::ltrb(row.right() - ARROW_WIDTH, row.top(), row.right(), row.bottom());
+                            draw_label(&style, ">", arrow, 0.5, color);
+                        }
+                    }
+                }
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+/// Opens a menu at `pos` in window coordinates, usually where the mouse was right clicked
+pub unsafe fn open_context_menu(items: Vec<MenuItem>, pos: Vec2<f32>) -> PopupId {
+    let popup = MenuList::new(items)
+        .into_popup(Vec4::xywh(pos.x, pos.y, 0.0, 0.0))
+        .placement(Placement::Right)
+        .gap(0.0);
+    context().framework().open_popup(popup)
+}
+
+/// Opens a context menu when its child is right clicked, with the items `menu` returns at that moment
+pub struct ContextMenu<H: UIHandler> {
+    child: H,
+    menu: Box<dyn Fn() -> Vec<MenuItem>>,
+}
+
+impl<H: UIHandler> ContextMenu<H> {
+    pub fn new<F: Fn() -> Vec<MenuItem> + 'static>(child: H, menu: F) -> Self {
+        ContextMenu {
+            child,
+            menu: Box::new(menu),
+        }
+    }
+
+    pub fn child(&mut self) -> &mut H {
+        &mut self.child
+    }
+}
+
+impl<H: UIHandler> UIHandler for ContextMenu<H> {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        if let Event::MouseClick(MouseButton::Button2, Action::Press) = event {
+            let pos = *context().window().mouse().pos();
+            if pos.intersects(&self.child.bounds()) {
+                let items = (self.menu)();
+                if !items.is_empty() {
+                    let translate = *context().renderer().stack().current_translate();
+                    open_context_menu(items, pos + translate);
+                }
+            }
+        }
+        self.child.handle(event)
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        self.child.tick(render_pass)
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.child.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.child.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.child.layout_context()
+    }
+}
+
+#[test]
+pub fn menu_navigation() {
+    let items = vec![
+        MenuItem::new("Cut", || {}).enabled(false),
+        MenuItem::new("Copy", || {}),
+        MenuItem::separator(),
+        MenuItem::submenu("More", vec![]),
+    ];
+    assert_eq!(next_enabled(&items, None, 1), Some(1));
+    assert_eq!(next_enabled(&items, Some(1), 1), Some(3));
+    // Wraps around, skipping the disabled first item
+    assert_eq!(next_enabled(&items, Some(3), 1), Some(1));
+    assert_eq!(next_enabled(&items, None, -1), Some(3));
+    assert_eq!(next_enabled(&[MenuItem::separator()], None, 1), None);
+}
@@ -0,0 +1,182 @@
+use std::mem;
+
+use glfw::{Action, Key, MouseButton};
+
+use crate::components::context::context;
+use crate::components::framework::element::Element;
+use crate::components::framework::event::Event;
+use crate::components::framework::style::ComputedStyle;
+use crate::components::render::color::{solid, Color};
+use crate::components::render::renderer::Renderable;
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+pub mod button;
+pub mod checkbox;
+pub mod toggle;
+pub mod slider;
+pub mod radio;
+pub mod text_input;
+pub mod menu;
+pub mod dropdown;
+pub mod combo_box;
+pub mod tooltip;
+pub mod split_pane;
+pub mod tab_view;
+pub mod dock;
+pub mod tree_view;
+pub mod table;
+pub mod image;
+pub mod animated_image;
+pub mod icon;
+
+/// How fast state colors and knobs move, the inverse of the time in seconds
+pub const ANIMATION_SPEED: f32 = 10.0;
+
+/// The colors of a widget, taken from its style or defaulting to a dark theme.
+///
+/// | Property     | Used for                              |
+/// |--------------|---------------------------------------|
+/// | `background` | The body of buttons, boxes and tracks |
+/// | `color`      | Labels                                |
+/// | `accent`     | Checked, on and filled parts          |
+/// | `border`     | Outlines of boxes and radio circles   |
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub background: Color,
+    pub foreground: Color,
+    pub accent: Color,
+    pub border: Color,
+}
+
+impl Palette {
+    pub fn of(style: &ComputedStyle) -> Self {
+        Palette {
+            background: style.color_or("background", Color::from_u32(0xff2b2d31)),
+            foreground: style.color_or("color", Color::from_u32(0xffe6e6e6)),
+            accent: style.color_or("accent", Color::from_u32(0xff3d7eff)),
+            border: style.color_or("border", Color::from_u32(0xff5a5d66)),
+        }
+    }
+}
+
+/// Lightens a color while hovered, darkens it while pressed and fades it while disabled
+pub fn shade(color: Color, hovering: bool, pressed: bool, disabled: bool) -> Color {
+    if disabled {
+        let alpha = color.alpha() * 0.5;
+        color.set_alpha_f32(alpha)
+    } else if pressed {
+        color.mult_rgb(0.85)
+    } else if hovering {
+        color.mult_rgb(1.15)
+    } else {
+        color
+    }
+}
+
+/// The pointer and keyboard state every widget shares.
+///
+/// Clicking a widget focuses it, and clicking anywhere else takes the focus away.
+#[derive(Debug, Default)]
+pub struct Interaction {
+    pressed: bool,
+}
+
+impl Interaction {
+    pub fn new() -> Self {
+        Interaction::default()
+    }
+
+    /// If the mouse was pressed on the widget and has not been released yet
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Returns `true` when the widget is activated, either by releasing the mouse over it
+    /// after pressing it, or by pressing Space or Enter while it is focused
+    pub fn handle(&mut self, element: &mut Element, event: &Event) -> bool {
+        if !element.interactive() {
+            self.pressed = false;
+            element.set_focused(false);
+            return false;
+        }
+        match event {
+            Event::MouseClick(MouseButton::Button1, Action::Press) => {
+                self.pressed = element.hovering();
+                element.set_focused(self.pressed);
+                false
+            }
+            Event::MouseClick(MouseButton::Button1, Action::Release) => {
+                mem::take(&mut self.pressed) && element.hovering()
+            }
+            Event::Keyboard(Key::Space | Key::Enter, Action::Press, _) => element.focused(),
+            _ => false,
+        }
+    }
+}
+
+/// Shrinks `bounds` by `amount` on every side
+pub fn inset(bounds: Vec4, amount: f32) -> Vec4 {
+    bounds.shrink_by(&Vec4::xywh(amount, amount, amount, amount))
+}
+
+/// Draws `rect` with the given bounds, color and corner radius, creating it the first time
+pub unsafe fn draw_rect(rect: &mut Option<Rect>, bounds: Vec4, color: Color, radius: f32) {
+    let rect = match rect {
+        Some(rect) => rect,
+        None => rect.insert(Rect::new(bounds, solid(color))),
+    };
+    rect.set_bounds(&bounds);
+    rect.set_colors(solid(color));
+    rect.set_radius(radius);
+    rect.pre_render();
+    rect.render();
+}
+
+/// The font size of a widget's labels, `16` unless its style sets `font_size`
+pub fn font_size(style: &ComputedStyle) -> f32 {
+    style.number("font_size").unwrap_or(16.0)
+}
+
+fn font_name(style: &ComputedStyle) -> String {
+    style.get_as::<String>("font").unwrap_or("main".to_string())
+}
+
+/// The width and line height of `text` in the widget's font
+pub unsafe fn measure(style: &ComputedStyle, text: &str) -> Vec2<f32> {
+    let size = font_size(style);
+    match context().fonts().font(font_name(style)) {
+        None => Vec2::zero(),
+        Some(font) => Vec2::new(font.add_end_pos(Vec2::zero(), size, text).x, font.get_sized_height(size)),
+    }
+}
+
+/// The x position of every caret position in `text`, from before the first character to after the last
+pub unsafe fn char_offsets(style: &ComputedStyle, text: &str) -> Vec<f32> {
+    let size = font_size(style);
+    let mut offsets = vec![0.0];
+    if let Some(font) = context().fonts().font(font_name(style)) {
+        let mut end = Vec2::zero();
+        let mut buffer = [0; 4];
+        for c in text.chars() {
+            end = font.add_end_pos(end, size, c.encode_utf8(&mut buffer));
+            offsets.push(end.x);
+        }
+    }
+    offsets.resize(text.chars().count() + 1, 0.0);
+    offsets
+}
+
+/// Draws a single line of text vertically centered in `bounds`,
+/// where `align` is 0 for the left edge, 0.5 for the center and 1 for the right edge
+pub unsafe fn draw_label(style: &ComputedStyle, text: &str, bounds: Vec4, align: f32, color: Color) {
+    let size = measure(style, text);
+    let pos = Vec2::new(
+        bounds.x() + (bounds.width() - size.x) * align,
+        bounds.center_y() - size.y / 2.0,
+    );
+    if let Some(font) = context().fonts().font(font_name(style)) {
+        font.draw_string((font_size(style), text, color), pos);
+    }
+}
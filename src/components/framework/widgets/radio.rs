@@ -0,0 +1,246 @@
+use glfw::{Action, Key};
+
+use crate::components::context::context;
+use crate::components::framework::animation::{Animated, AnimationRegistry, Easing, Interpolate};
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::{draw_label, draw_rect, inset, measure, shade, Interaction, Palette, ANIMATION_SPEED};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+const ROW_HEIGHT: f32 = 24.0;
+const CIRCLE_SIZE: f32 = 18.0;
+const LABEL_GAP: f32 = 8.0;
+
+/// A single option of a [`RadioGroup`]
+struct RadioOption {
+    label: String,
+    selection: Animated<f32>,
+    rects: [Option<Rect>; 3],
+}
+
+/// A vertical list of options of which only one can be selected, styled as `RadioGroup`.
+///
+/// Clicking an option selects it. While focused, the arrow keys move the selection.
+pub struct RadioGroup {
+    element: Element,
+    interaction: Interaction,
+    options: Vec<RadioOption>,
+    selected: Option<usize>,
+    hovered: Option<usize>,
+    changed: bool,
+
+    on_change: Option<Box<dyn FnMut(usize)>>,
+}
+
+impl RadioGroup {
+    pub fn new<S: ToString>(options: impl IntoIterator<Item=S>, selected: Option<usize>) -> Self {
+        let options: Vec<RadioOption> = options.into_iter().enumerate().map(|(i, label)| RadioOption {
+            label: label.to_string(),
+            selection: Animated::new(if selected == Some(i) { 1.0 } else { 0.0 }),
+            rects: [None, None, None],
+        }).collect();
+        RadioGroup {
+            element: ElementBuilder::new()
+                .style_type("RadioGroup")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(CIRCLE_SIZE, ROW_HEIGHT * options.len() as f32),
+                    ..LayoutContext::new()
+                })
+                .register_animations(options.iter().map(|o| o.selection.animation()).collect())
+                .build(),
+            interaction: Interaction::new(),
+            selected: selected.filter(|s| *s < options.len()),
+            options,
+            hovered: None,
+            changed: true,
+            on_change: None,
+        }
+    }
+
+    /// Called with the index of the option every time the user selects a different one
+    pub fn on_change<F: FnMut(usize) + 'static>(mut self, on_change: F) -> Self {
+        self.on_change = Some(Box::new(on_change));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.set_disabled(disabled);
+        self
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn selected_label(&self) -> Option<&str> {
+        self.selected.map(|i| self.options[i].label.as_str())
+    }
+
+    /// Sets the selection without calling the change callback
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        let selected = selected.filter(|s| *s < self.options.len());
+        if selected != self.selected {
+            self.selected = selected;
+            self.changed = true;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.element.disabled()
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.element.set_disabled(disabled);
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.selected != Some(index) && index < self.options.len() {
+            self.set_selected(Some(index));
+            if let Some(on_change) = &mut self.on_change {
+                on_change(index);
+            }
+        }
+    }
+
+    fn row_bounds(&self, index: usize) -> Vec4 {
+        let bounds = self.element.bounds();
+        Vec4::xywh(bounds.x(), bounds.y() + index as f32 * ROW_HEIGHT, bounds.width(), ROW_HEIGHT)
+    }
+}
+
+impl UIHandler for RadioGroup {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+        if self.interaction.handle(&mut self.element, event) {
+            match event {
+                Event::Keyboard(..) => {
+                    // Space and Enter select the first option when nothing is selected yet
+                    if self.selected.is_none() {
+                        self.select(0);
+                    }
+                }
+                _ => {
+                    if let Some(hovered) = self.hovered {
+                        self.select(hovered);
+                    }
+                }
+            }
+        }
+
+        if self.element.focused() && self.element.interactive() && !self.options.is_empty() {
+            if let Event::Keyboard(key, Action::Press | Action::Repeat, _) = event {
+                let last = self.options.len() - 1;
+                match (key, self.selected) {
+                    (Key::Up | Key::Left, Some(s)) => self.select(s.saturating_sub(1)),
+                    (Key::Down | Key::Right, Some(s)) => self.select((s + 1).min(last)),
+                    (Key::Up | Key::Left | Key::Down | Key::Right, None) => self.select(0),
+                    (Key::Home, _) => self.select(0),
+                    (Key::End, _) => self.select(last),
+                    _ => {}
+                }
+            }
+        }
+
+        match event {
+            Event::PreRender => {
+                let hovered = if self.element.hovering() {
+                    let y = context().window().mouse().pos().y - self.element.bounds().y();
+                    Some(((y / ROW_HEIGHT).max(0.0) as usize).min(self.options.len().saturating_sub(1)))
+                } else {
+                    None
+                };
+                if hovered != self.hovered {
+                    self.hovered = hovered;
+                    self.changed = true;
+                }
+
+                if self.changed {
+                    let style = self.element.style().clone();
+                    let widest = self.options.iter().map(|o| measure(&style, &o.label).x).fold(0.0, f32::max);
+                    let layout = self.element.layout_mut();
+                    layout.min_size.x = CIRCLE_SIZE + LABEL_GAP + widest;
+                    layout.min_size.y = ROW_HEIGHT * self.options.len() as f32;
+                }
+                for (i, option) in self.options.iter_mut().enumerate() {
+                    option.selection.animate_to(if self.selected == Some(i) { 1.0 } else { 0.0 }, ANIMATION_SPEED, Easing::Sin);
+                }
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.element.style().clone();
+                let palette = Palette::of(&style);
+                let disabled = self.is_disabled();
+                let pressed = self.interaction.is_pressed();
+
+                for i in 0..self.options.len() {
+                    let row = self.row_bounds(i);
+                    let hovering = self.hovered == Some(i);
+                    let option = &mut self.options[i];
+                    let selection = option.selection.value();
+
+                    let outer = Vec4::xywh(row.x(), row.center_y() - CIRCLE_SIZE / 2.0, CIRCLE_SIZE, CIRCLE_SIZE);
+                    let border = shade(palette.border.interpolate(&palette.accent, selection), hovering, hovering && pressed, disabled);
+                    draw_rect(&mut option.rects[0], outer, border, CIRCLE_SIZE / 2.0);
+                    let inner = inset(outer, 2.0);
+                    draw_rect(&mut option.rects[1], inner, shade(palette.background, false, false, disabled), inner.width() / 2.0);
+                    if selection > 0.01 {
+                        let dot = inset(inner, 3.0 + (1.0 - selection) * (inner.width() / 2.0 - 3.0));
+                        draw_rect(&mut option.rects[2], dot, shade(palette.accent, false, false, disabled), dot.width() / 2.0);
+                    }
+
+                    let label = Vec4::ltrb(outer.right() + LABEL_GAP, row.top(), row.right(), row.bottom());
+                    draw_label(&style, &option.label, label, 0.0, shade(palette.foreground, false, false, disabled));
+                }
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
@@ -0,0 +1,264 @@
+use glfw::{Action, Key};
+
+use crate::components::context::context;
+use crate::components::framework::animation::{Animated, AnimationRegistry, Easing};
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::{LayoutContext, Sizing};
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::{draw_rect, shade, Interaction, Palette, ANIMATION_SPEED};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+const TRACK_HEIGHT: f32 = 4.0;
+const THUMB_RADIUS: f32 = 7.0;
+/// How much bigger the thumb gets while hovered or dragged
+const THUMB_GROWTH: f32 = 2.0;
+
+/// Clamps `value` into `min..=max` and rounds it to the nearest multiple of `step` from `min`.
+///
+/// A `step` of 0 allows any value. Reversed bounds are swapped.
+pub fn snap(value: f32, min: f32, max: f32, step: f32) -> f32 {
+    // Unlike `f32::clamp`, this does not panic on reversed or NaN bounds
+    let (min, max) = (min.min(max), min.max(max));
+    let value = value.max(min).min(max);
+    if step <= 0.0 {
+        return value;
+    }
+    (min + ((value - min) / step).round() * step).max(min).min(max)
+}
+
+/// A horizontal slider picking a number in a range, styled as `Slider`.
+///
+/// The value follows the mouse while the thumb or track is dragged. While focused, the arrow keys
+/// move it by one step, Page Up and Page Down by ten, and Home and End jump to the ends.
+pub struct Slider {
+    element: Element,
+    interaction: Interaction,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+    changed: bool,
+
+    thumb: Animated<f32>,
+    track: Option<Rect>,
+    fill: Option<Rect>,
+    thumb_rect: Option<Rect>,
+
+    on_change: Option<Box<dyn FnMut(f32)>>,
+}
+
+impl Slider {
+    pub fn new(min: f32, max: f32, value: f32) -> Self {
+        let thumb = Animated::new(0.0);
+        Slider {
+            element: ElementBuilder::new()
+                .style_type("Slider")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(120.0, 24.0),
+                    size_behavior: (Sizing::Grow, Sizing::Shrink),
+                    ..LayoutContext::new()
+                })
+                .register_animations(vec![thumb.animation()])
+                .build(),
+            interaction: Interaction::new(),
+            min: min.min(max),
+            max: min.max(max),
+            step: 0.0,
+            value: snap(value, min, max, 0.0),
+            changed: true,
+            thumb,
+            track: None,
+            fill: None,
+            thumb_rect: None,
+            on_change: None,
+        }
+    }
+
+    /// Only allows multiples of `step` from the minimum
+    pub fn step(mut self, step: f32) -> Self {
+        self.step = step;
+        self.value = snap(self.value, self.min, self.max, step);
+        self
+    }
+
+    /// Called with the new value every time it is changed by the user
+    pub fn on_change<F: FnMut(f32) + 'static>(mut self, on_change: F) -> Self {
+        self.on_change = Some(Box::new(on_change));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.set_disabled(disabled);
+        self
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Sets the value without calling the change callback
+    pub fn set_value(&mut self, value: f32) {
+        let value = snap(value, self.min, self.max, self.step);
+        if value != self.value {
+            self.value = value;
+            self.changed = true;
+        }
+    }
+
+    pub fn set_range(&mut self, min: f32, max: f32) {
+        self.min = min.min(max);
+        self.max = min.max(max);
+        self.set_value(self.value);
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.element.disabled()
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.element.set_disabled(disabled);
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    fn change(&mut self, value: f32) {
+        let previous = self.value;
+        self.set_value(value);
+        if self.value != previous {
+            if let Some(on_change) = &mut self.on_change {
+                on_change(self.value);
+            }
+        }
+    }
+
+    /// The amount the arrow keys move the value by
+    fn key_step(&self) -> f32 {
+        if self.step > 0.0 {
+            self.step
+        } else {
+            (self.max - self.min) / 100.0
+        }
+    }
+
+    /// The part of the bounds the thumb's center moves along
+    fn track_bounds(&self) -> Vec4 {
+        let bounds = self.element.bounds();
+        Vec4::ltrb(
+            bounds.left() + THUMB_RADIUS + THUMB_GROWTH,
+            bounds.center_y() - TRACK_HEIGHT / 2.0,
+            bounds.right() - THUMB_RADIUS - THUMB_GROWTH,
+            bounds.center_y() + TRACK_HEIGHT / 2.0,
+        )
+    }
+
+    fn progress(&self) -> f32 {
+        if self.max > self.min {
+            (self.value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl UIHandler for Slider {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+        self.interaction.handle(&mut self.element, event);
+
+        if self.element.focused() && self.element.interactive() {
+            if let Event::Keyboard(key, Action::Press | Action::Repeat, _) = event {
+                let step = self.key_step();
+                match key {
+                    Key::Left | Key::Down => self.change(self.value - step),
+                    Key::Right | Key::Up => self.change(self.value + step),
+                    Key::PageDown => self.change(self.value - step * 10.0),
+                    Key::PageUp => self.change(self.value + step * 10.0),
+                    Key::Home => self.change(self.min),
+                    Key::End => self.change(self.max),
+                    _ => {}
+                }
+            }
+        }
+
+        match event {
+            Event::PreRender => {
+                if self.interaction.is_pressed() {
+                    let track = self.track_bounds();
+                    let x = context().window().mouse().pos().x;
+                    let progress = if track.width() > 0.0 { (x - track.left()) / track.width() } else { 0.0 };
+                    self.change(self.min + progress * (self.max - self.min));
+                }
+                let grown = self.interaction.is_pressed() || self.element.hovering() || self.element.focused();
+                self.thumb.animate_to(if grown { THUMB_GROWTH } else { 0.0 }, ANIMATION_SPEED, Easing::Sin);
+            }
+            Event::Render(RenderPass::Main) => {
+                let palette = Palette::of(self.element.style());
+                let disabled = self.is_disabled();
+                let track = self.track_bounds();
+                let center = Vec2::new(track.left() + track.width() * self.progress(), track.center_y());
+
+                draw_rect(&mut self.track, track, shade(palette.border, false, false, disabled), TRACK_HEIGHT / 2.0);
+                let fill = Vec4::ltrb(track.left(), track.top(), center.x, track.bottom());
+                draw_rect(&mut self.fill, fill, shade(palette.accent, false, false, disabled), TRACK_HEIGHT / 2.0);
+
+                let radius = THUMB_RADIUS + self.thumb.value();
+                let thumb = Vec4::xywh(center.x - radius, center.y - radius, radius * 2.0, radius * 2.0);
+                draw_rect(&mut self.thumb_rect, thumb, shade(palette.foreground, false, false, disabled), radius);
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::Redraw
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+#[test]
+pub fn snapping() {
+    assert_eq!(snap(0.5, 0.0, 1.0, 0.0), 0.5);
+    assert_eq!(snap(-3.0, 0.0, 1.0, 0.0), 0.0);
+    assert_eq!(snap(7.4, 0.0, 10.0, 2.0), 8.0);
+    assert_eq!(snap(7.4, 1.0, 10.0, 2.0), 7.0);
+    // The last step would be past the maximum
+    assert_eq!(snap(9.9, 0.0, 9.0, 2.0), 9.0);
+    // Reversed and NaN bounds
+    assert_eq!(snap(12.0, 10.0, 0.0, 0.0), 10.0);
+    assert_eq!(snap(7.4, 10.0, 0.0, 2.0), 8.0);
+    assert_eq!(snap(3.0, f32::NAN, 5.0, 0.0), 5.0);
+    assert_eq!(snap(3.0, 0.0, f32::NAN, 0.0), 0.0);
+}
@@ -0,0 +1,382 @@
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::{LayoutContext, LayoutDirection, LayoutEvent, Sizing};
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::{draw_rect, Palette};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::render::stack::State;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// One of the two sides of a [`SplitPane`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pane {
+    /// The left or top pane
+    First,
+    /// The right or bottom pane
+    Second,
+}
+
+/// The sizes of both panes when `total` is split at `ratio` of the space left next to the divider.
+///
+/// The first pane is kept between its minimum and the space the second one needs, a collapsed pane is `0`.
+pub fn pane_sizes(total: f32, divider: f32, ratio: f32, min: (f32, f32), collapsed: Option<Pane>) -> (f32, f32) {
+    let available = (total - divider).max(0.0);
+    let first = match collapsed {
+        Some(Pane::First) => 0.0,
+        Some(Pane::Second) => available,
+        None => (available * ratio).min(available - min.1).max(min.0).min(available),
+    };
+    (first, available - first)
+}
+
+/// The ratio and collapsed pane after the divider was dragged so that the first pane is `first` long.
+///
+/// Dragging a collapsible pane below half of its minimum collapses it.
+pub fn drag_split(first: f32, available: f32, min: (f32, f32), collapsible: bool) -> (f32, Option<Pane>) {
+    if available <= 0.0 {
+        return (0.5, None);
+    }
+    if collapsible && first < min.0 / 2.0 {
+        return (0.0, Some(Pane::First));
+    }
+    if collapsible && available - first < min.1 / 2.0 {
+        return (1.0, Some(Pane::Second));
+    }
+    let first = first.min(available - min.1).max(min.0);
+    ((first / available).clamp(0.0, 1.0), None)
+}
+
+/// Two panes next to each other with a divider between them that can be dragged to resize them,
+/// styled as `SplitPane` with a `SplitDivider` divider.
+///
+/// Panes are laid out like the children of a [`Container`], relative to the split pane, but always
+/// get the size of their side of the split. They should grow in both directions to fill it.
+/// The split is kept as a ratio, so panes keep their proportions when the window is resized.
+///
+/// [`Container`]: crate::components::framework::element::container::Container
+pub struct SplitPane {
+    element: Element,
+    divider: Element,
+    direction: LayoutDirection,
+    panes: [Box<dyn UIHandler>; 2],
+
+    ratio: f32,
+    min_sizes: (f32, f32),
+    thickness: f32,
+    collapsible: bool,
+    collapsed: Option<Pane>,
+    changed: bool,
+
+    rect: Option<Rect>,
+    on_resize: Option<Box<dyn FnMut(f32)>>,
+}
+
+impl SplitPane {
+    /// Splits `direction` between the panes, [`LayoutDirection::Horizontal`] puts them side by side
+    pub fn new<A: UIHandler + 'static, B: UIHandler + 'static>(direction: LayoutDirection, first: A, second: B) -> Self {
+        let mut split = SplitPane {
+            element: ElementBuilder::new()
+                .style_type("SplitPane")
+                .layout_context(LayoutContext {
+                    size_behavior: (Sizing::Grow, Sizing::Grow),
+                    ..LayoutContext::new()
+                })
+                .build(),
+            divider: ElementBuilder::new()
+                .style_type("SplitDivider")
+                .draggable(true)
+                .build(),
+            direction,
+            panes: [Box::new(first), Box::new(second)],
+            ratio: 0.5,
+            min_sizes: (0.0, 0.0),
+            thickness: 6.0,
+            collapsible: false,
+            collapsed: None,
+            changed: true,
+            rect: None,
+            on_resize: None,
+        };
+        split.update_min_size();
+        split
+    }
+
+    /// Puts the panes side by side, with a vertical divider
+    pub fn horizontal<A: UIHandler + 'static, B: UIHandler + 'static>(first: A, second: B) -> Self {
+        SplitPane::new(LayoutDirection::Horizontal, first, second)
+    }
+
+    /// Puts the panes above each other, with a horizontal divider
+    pub fn vertical<A: UIHandler + 'static, B: UIHandler + 'static>(first: A, second: B) -> Self {
+        SplitPane::new(LayoutDirection::Vertical, first, second)
+    }
+
+    /// The part of the space the first pane takes, `0.5` by default
+    pub fn ratio(mut self, ratio: f32) -> Self {
+        self.set_ratio(ratio);
+        self
+    }
+
+    /// The sizes the panes are never resized below while they are not collapsed
+    pub fn min_sizes(mut self, first: f32, second: f32) -> Self {
+        self.min_sizes = (first, second);
+        self.update_min_size();
+        self
+    }
+
+    /// The size of the divider along the split, `6` by default
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self.update_min_size();
+        self
+    }
+
+    /// If dragging the divider past half of a pane's minimum size collapses the pane
+    pub fn collapsible(mut self, collapsible: bool) -> Self {
+        self.collapsible = collapsible;
+        self
+    }
+
+    /// Called with the new ratio every time the user drags the divider
+    pub fn on_resize<F: FnMut(f32) + 'static>(mut self, on_resize: F) -> Self {
+        self.on_resize = Some(Box::new(on_resize));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn get_ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio.clamp(0.0, 1.0);
+        self.changed = true;
+    }
+
+    pub fn direction(&self) -> &LayoutDirection {
+        &self.direction
+    }
+
+    pub fn collapsed(&self) -> Option<Pane> {
+        self.collapsed
+    }
+
+    /// Hides `pane`, giving all the space to the other one until [`SplitPane::expand()`] is called
+    pub fn collapse(&mut self, pane: Pane) {
+        self.collapsed = Some(pane);
+        self.changed = true;
+    }
+
+    /// Shows a collapsed pane again at the ratio it had before
+    pub fn expand(&mut self) {
+        self.collapsed = None;
+        self.changed = true;
+    }
+
+    pub fn toggle(&mut self, pane: Pane) {
+        if self.collapsed == Some(pane) {
+            self.expand();
+        } else {
+            self.collapse(pane);
+        }
+    }
+
+    pub fn pane(&mut self, pane: Pane) -> &mut Box<dyn UIHandler> {
+        match pane {
+            Pane::First => &mut self.panes[0],
+            Pane::Second => &mut self.panes[1],
+        }
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    /// Makes the split pane at least as long as both minimum sizes and the divider
+    fn update_min_size(&mut self) {
+        let min = self.min_sizes.0 + self.min_sizes.1 + self.thickness;
+        let direction = self.direction.clone();
+        let min_size = &mut self.element.layout_mut().min_size;
+        let current = *min_size.direction(&direction);
+        min_size.add_direction(&direction, min - current);
+    }
+
+    fn sizes(&self) -> (f32, f32) {
+        let total = self.element.bounds().direction_size(&self.direction);
+        pane_sizes(total, self.thickness, self.ratio, self.min_sizes, self.collapsed)
+    }
+
+    /// Resizes both panes in `direction` to fill their side of the split
+    unsafe fn size_panes(&mut self, direction: &LayoutDirection) {
+        let sizes = if *direction == self.direction {
+            self.sizes()
+        } else {
+            let size = self.element.bounds().direction_size(direction);
+            (size, size)
+        };
+        context().renderer().stack().push(State::Translate(self.element.bounds().x(), self.element.bounds().y()));
+        for (pane, size) in self.panes.iter_mut().zip([sizes.0, sizes.1]) {
+            let current = pane.bounds().direction_size(direction);
+            pane.handle(&Event::Layout(LayoutEvent::grow_direction(direction, size - current)));
+        }
+        context().renderer().stack().pop();
+    }
+
+    /// The bounds of the divider, relative to the same parent as the split pane
+    fn divider_bounds(&self) -> Vec4 {
+        let bounds = self.element.bounds();
+        let first = self.sizes().0;
+        match self.direction {
+            LayoutDirection::Horizontal => Vec4::xywh(bounds.x() + first, bounds.y(), self.thickness, bounds.height()),
+            LayoutDirection::Vertical => Vec4::xywh(bounds.x(), bounds.y() + first, bounds.width(), self.thickness),
+        }
+    }
+}
+
+impl UIHandler for SplitPane {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let mut result = self.element.handle(event);
+        if !matches!(event, Event::Layout(_)) {
+            if let EventResult::Used = self.divider.handle(event) {
+                result = EventResult::Used;
+            }
+        }
+
+        if let Event::PreRender = event {
+            if self.divider.dragging() {
+                let bounds = self.element.bounds();
+                let first = *self.divider.bounds().xy().direction(&self.direction) - *bounds.xy().direction(&self.direction);
+                let available = bounds.direction_size(&self.direction) - self.thickness;
+                let (ratio, collapsed) = drag_split(first, available, self.min_sizes, self.collapsible);
+                // A collapsed pane keeps the ratio it is expanded back to
+                if collapsed.is_none() && (ratio != self.ratio || self.collapsed.is_some()) {
+                    self.ratio = ratio;
+                    if let Some(on_resize) = &mut self.on_resize {
+                        on_resize(ratio);
+                    }
+                }
+                if collapsed != self.collapsed {
+                    self.collapsed = collapsed;
+                }
+                self.changed = true;
+            }
+        }
+
+        // Panes are handled like container children, relative to the split pane
+        context().renderer().stack().push(State::Translate(self.element.bounds().x(), self.element.bounds().y()));
+        for pane in &mut self.panes {
+            if let Event::PostRender = event {
+                if let Some(mut animations) = pane.animations() {
+                    animations.post();
+                }
+            }
+            if let Event::Layout(LayoutEvent::FitWidth | LayoutEvent::FitHeight) = event {
+                pane.handle(event);
+                continue;
+            }
+            if let Event::Layout(_) = event {
+                continue;
+            }
+            if let EventResult::Used = pane.handle(event) {
+                result = EventResult::Used;
+            }
+        }
+        context().renderer().stack().pop();
+
+        match event {
+            Event::Layout(stage) => {
+                match stage {
+                    LayoutEvent::FitWidth => self.size_panes(&LayoutDirection::Horizontal),
+                    LayoutEvent::FitHeight => self.size_panes(&LayoutDirection::Vertical),
+                    LayoutEvent::GrowWidth(_) => self.size_panes(&LayoutDirection::Horizontal),
+                    LayoutEvent::GrowHeight(_) => self.size_panes(&LayoutDirection::Vertical),
+                    LayoutEvent::Position(_) => {
+                        let first = self.sizes().0;
+                        let mut second = Vec2::new(0.0, 0.0);
+                        second.add_direction(&self.direction, first + self.thickness);
+                        context().renderer().stack().push(State::Translate(self.element.bounds().x(), self.element.bounds().y()));
+                        self.panes[0].handle(&Event::Layout(LayoutEvent::Position(Vec2::new(0.0, 0.0))));
+                        self.panes[1].handle(&Event::Layout(LayoutEvent::Position(second)));
+                        context().renderer().stack().pop();
+                        let divider = self.divider_bounds();
+                        *Element::bounds(&mut self.divider) = divider;
+                    }
+                    LayoutEvent::OptimizeSize(_) => {}
+                }
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.divider.style().clone();
+                let palette = Palette::of(&style);
+                let color = if self.divider.dragging() {
+                    palette.accent
+                } else if self.divider.hovering() {
+                    palette.border
+                } else {
+                    palette.background
+                };
+                let bounds = self.divider_bounds();
+                draw_rect(&mut self.rect, bounds, color, style.number("radius").unwrap_or(0.0));
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        for result in [self.element.tick(render_pass), self.divider.tick(render_pass)] {
+            if !result.is_valid() {
+                return result;
+            }
+        }
+        for pane in &mut self.panes {
+            let result = pane.tick(render_pass);
+            if !result.is_valid() {
+                return result;
+            }
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+#[test]
+pub fn splitting() {
+    // The divider takes 10 of the 210, the rest is split by the ratio
+    assert_eq!(pane_sizes(210.0, 10.0, 0.25, (0.0, 0.0), None), (50.0, 150.0));
+    // Minimum sizes win over the ratio
+    assert_eq!(pane_sizes(210.0, 10.0, 0.1, (80.0, 0.0), None), (80.0, 120.0));
+    assert_eq!(pane_sizes(210.0, 10.0, 0.9, (0.0, 60.0), None), (140.0, 60.0));
+    assert_eq!(pane_sizes(210.0, 10.0, 0.5, (0.0, 0.0), Some(Pane::First)), (0.0, 200.0));
+    assert_eq!(pane_sizes(210.0, 10.0, 0.5, (0.0, 0.0), Some(Pane::Second)), (200.0, 0.0));
+
+    assert_eq!(drag_split(50.0, 200.0, (0.0, 0.0), false), (0.25, None));
+    assert_eq!(drag_split(10.0, 200.0, (40.0, 0.0), false), (0.2, None));
+    assert_eq!(drag_split(10.0, 200.0, (40.0, 0.0), true).1, Some(Pane::First));
+    assert_eq!(drag_split(30.0, 200.0, (40.0, 0.0), true), (0.2, None));
+    assert_eq!(drag_split(195.0, 200.0, (0.0, 40.0), true).1, Some(Pane::Second));
+}
@@ -0,0 +1,565 @@
+use glfw::{Action, MouseButton};
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::{LayoutContext, LayoutEvent, Sizing};
+use crate::components::framework::popup::window_bounds;
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::{draw_label, draw_rect, measure, shade, Palette};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::render::stack::State;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+const TAB_PADDING: f32 = 12.0;
+const CLOSE_SIZE: f32 = 16.0;
+
+/// The index a dragged tab moves to when its center is `x` along the bar.
+///
+/// Tabs are `widths` long and laid out from `0`, the dragged tab passes another one once its center does.
+pub fn reorder_index(widths: &[f32], index: usize, x: f32) -> usize {
+    let mut offset = 0.0;
+    let mut target = 0;
+    for (i, width) in widths.iter().enumerate() {
+        if i != index && offset + width / 2.0 < x {
+            target += 1;
+        }
+        offset += width;
+    }
+    target
+}
+
+/// The tab selected after the tab at `closed` is removed, leaving `len` tabs
+pub fn selection_after_close(selected: usize, closed: usize, len: usize) -> usize {
+    let selected = if closed < selected { selected - 1 } else { selected };
+    selected.min(len.saturating_sub(1))
+}
+
+/// Decides if the tab with the given index and title closes
+type CloseCallback = Box<dyn FnMut(usize, &str) -> bool>;
+
+/// A page of a [`TabView`], with the title shown in its header, styled as `Tab`
+pub struct Tab {
+    title: String,
+    content: Box<dyn UIHandler>,
+    closable: bool,
+    header: Element,
+    width: f32,
+    rects: [Option<Rect>; 3],
+}
+
+impl Tab {
+    pub fn new<H: UIHandler + 'static>(title: impl ToString, content: H) -> Self {
+        Tab::boxed(title, Box::new(content))
+    }
+
+    pub fn boxed(title: impl ToString, content: Box<dyn UIHandler>) -> Self {
+        Tab {
+            title: title.to_string(),
+            content,
+            closable: true,
+            header: ElementBuilder::new()
+                .style_type("Tab")
+                .draggable(true)
+                .build(),
+            width: 0.0,
+            rects: [None, None, None],
+        }
+    }
+
+    /// If the header has a button closing the tab, `true` by default
+    pub fn closable(mut self, closable: bool) -> Self {
+        self.closable = closable;
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.header.add_class(class);
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set_title(&mut self, title: impl ToString) {
+        self.title = title.to_string();
+        self.width = 0.0;
+    }
+
+    pub fn is_closable(&self) -> bool {
+        self.closable
+    }
+
+    pub fn content(&mut self) -> &mut Box<dyn UIHandler> {
+        &mut self.content
+    }
+
+    pub fn into_content(self) -> Box<dyn UIHandler> {
+        self.content
+    }
+
+    /// The close button at the end of the header
+    fn close_bounds(&self) -> Vec4 {
+        let header = self.header.bounds();
+        Vec4::xywh(header.right() - TAB_PADDING / 2.0 - CLOSE_SIZE, header.center_y() - CLOSE_SIZE / 2.0, CLOSE_SIZE, CLOSE_SIZE)
+    }
+}
+
+/// Pages of content with a bar of headers to switch between them, styled as `TabView`.
+///
+/// Clicking a header selects its tab, dragging it along the bar reorders the tabs and its close
+/// button closes it. Only the selected tab's content receives events, it is laid out below the bar
+/// like the child of a [`Container`] and gets all of the remaining space.
+///
+/// [`Container`]: crate::components::framework::element::container::Container
+pub struct TabView {
+    element: Element,
+    tabs: Vec<Tab>,
+    selected: usize,
+    bar_height: f32,
+    detachable: bool,
+    changed: bool,
+
+    /// The tab whose close button was pressed, closed if the mouse is released over it
+    pressed_close: Option<usize>,
+    /// A tab that was dragged out of the bar and released at a position relative to the window
+    dropped: Option<(usize, Vec2<f32>)>,
+    window_bounds: Vec4,
+    rect: Option<Rect>,
+
+    on_select: Option<Box<dyn FnMut(usize)>>,
+    on_close: Option<CloseCallback>,
+}
+
+impl TabView {
+    pub fn new(tabs: impl IntoIterator<Item=Tab>) -> Self {
+        TabView {
+            element: ElementBuilder::new()
+                .style_type("TabView")
+                .layout_context(LayoutContext {
+                    size_behavior: (Sizing::Grow, Sizing::Grow),
+                    ..LayoutContext::new()
+                })
+                .build(),
+            tabs: tabs.into_iter().collect(),
+            selected: 0,
+            bar_height: 30.0,
+            detachable: false,
+            changed: true,
+            pressed_close: None,
+            dropped: None,
+            window_bounds: *Vec4::zero(),
+            rect: None,
+            on_select: None,
+            on_close: None,
+        }
+    }
+
+    /// The height of the bar of headers, `30` by default
+    pub fn bar_height(mut self, bar_height: f32) -> Self {
+        self.bar_height = bar_height;
+        self
+    }
+
+    /// If tabs dragged out of the bar are reported by [`TabView::take_dropped()`], used for docking
+    pub fn detachable(mut self, detachable: bool) -> Self {
+        self.detachable = detachable;
+        self
+    }
+
+    /// Called with the index of the tab every time the user selects a different one
+    pub fn on_select<F: FnMut(usize) + 'static>(mut self, on_select: F) -> Self {
+        self.on_select = Some(Box::new(on_select));
+        self
+    }
+
+    /// Called with the index and title of a tab when its close button is clicked.
+    /// Returning `false` keeps the tab open, for example to ask about unsaved changes first.
+    pub fn on_close<F: FnMut(usize, &str) -> bool + 'static>(mut self, on_close: F) -> Self {
+        self.on_close = Some(Box::new(on_close));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn tabs(&self) -> &[Tab] {
+        &self.tabs
+    }
+
+    pub fn tab(&mut self, index: usize) -> Option<&mut Tab> {
+        self.tabs.get_mut(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        if self.tabs.is_empty() { None } else { Some(self.selected) }
+    }
+
+    /// Selects a tab without calling the select callback
+    pub fn set_selected(&mut self, index: usize) {
+        if index < self.tabs.len() && index != self.selected {
+            self.selected = index;
+            self.changed = true;
+        }
+    }
+
+    /// Adds a tab at the end and selects it, returning its index
+    pub fn add_tab(&mut self, tab: Tab) -> usize {
+        self.insert_tab(self.tabs.len(), tab)
+    }
+
+    /// Inserts a tab before `index` and selects it, returning its index
+    pub fn insert_tab(&mut self, index: usize, tab: Tab) -> usize {
+        let index = index.min(self.tabs.len());
+        self.tabs.insert(index, tab);
+        self.selected = index;
+        self.changed = true;
+        index
+    }
+
+    /// Removes a tab without calling the close callback
+    pub fn remove_tab(&mut self, index: usize) -> Option<Tab> {
+        if index >= self.tabs.len() {
+            return None;
+        }
+        let tab = self.tabs.remove(index);
+        self.selected = selection_after_close(self.selected, index, self.tabs.len());
+        self.pressed_close = None;
+        self.changed = true;
+        Some(tab)
+    }
+
+    /// Closes a tab like its close button, unless the close callback keeps it open
+    pub fn close_tab(&mut self, index: usize) -> Option<Tab> {
+        let title = self.tabs.get(index)?.title.clone();
+        if let Some(on_close) = &mut self.on_close {
+            if !on_close(index, &title) {
+                return None;
+            }
+        }
+        self.remove_tab(index)
+    }
+
+    /// Moves a tab to another index, keeping it selected if it was
+    pub fn move_tab(&mut self, from: usize, to: usize) {
+        if from >= self.tabs.len() || to >= self.tabs.len() || from == to {
+            return;
+        }
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+        if self.selected == from {
+            self.selected = to;
+        } else if from < self.selected && self.selected <= to {
+            self.selected -= 1;
+        } else if to <= self.selected && self.selected < from {
+            self.selected += 1;
+        }
+        self.changed = true;
+    }
+
+    /// The tab being dragged by its header
+    pub fn dragged(&self) -> Option<usize> {
+        self.tabs.iter().position(|t| t.header.dragging())
+    }
+
+    /// The tab being dragged, if it was pulled far enough out of the bar to be dropped elsewhere
+    pub fn dragging_out(&self) -> Option<usize> {
+        let index = self.dragged().filter(|_| self.detachable)?;
+        let header = self.tabs[index].header.bounds();
+        let bar = self.bar_bounds();
+        if (header.center_y() - bar.center_y()).abs() > self.bar_height {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Takes the tab that was dragged out of the bar and released, with the mouse position relative to the window.
+    ///
+    /// Only set for the event that released the tab, when the view is [`TabView::detachable()`].
+    pub fn take_dropped(&mut self) -> Option<(usize, Vec2<f32>)> {
+        self.dropped.take()
+    }
+
+    /// The bounds of the view relative to the window, as of the last [`Event::PreRender`]
+    pub fn window_bounds(&self) -> Vec4 {
+        self.window_bounds
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    fn select(&mut self, index: usize) {
+        if index != self.selected {
+            self.set_selected(index);
+            if let Some(on_select) = &mut self.on_select {
+                on_select(index);
+            }
+        }
+    }
+
+    fn bar_bounds(&self) -> Vec4 {
+        let bounds = self.element.bounds();
+        Vec4::xywh(bounds.x(), bounds.y(), bounds.width(), self.bar_height)
+    }
+
+    /// Sends a layout event to the content of the selected tab, relative to the view
+    unsafe fn layout_content(&mut self, stage: &LayoutEvent) {
+        let bounds = self.element.bounds();
+        let height = (bounds.height() - self.bar_height).max(0.0);
+        let Some(tab) = self.tabs.get_mut(self.selected) else {
+            return;
+        };
+        let content = tab.content.bounds();
+        let event = match stage {
+            LayoutEvent::FitWidth | LayoutEvent::GrowWidth(_) => LayoutEvent::GrowWidth(bounds.width() - content.width()),
+            LayoutEvent::FitHeight | LayoutEvent::GrowHeight(_) => LayoutEvent::GrowHeight(height - content.height()),
+            LayoutEvent::Position(_) => LayoutEvent::Position(Vec2::new(0.0, self.bar_height)),
+            LayoutEvent::OptimizeSize(v) => LayoutEvent::OptimizeSize(*v),
+        };
+        context().renderer().stack().push(State::Translate(bounds.x(), bounds.y()));
+        if let LayoutEvent::FitWidth | LayoutEvent::FitHeight = stage {
+            tab.content.handle(&Event::Layout(LayoutEvent::FitWidth));
+            tab.content.handle(&Event::Layout(LayoutEvent::FitHeight));
+        }
+        tab.content.handle(&Event::Layout(event));
+        context().renderer().stack().pop();
+    }
+
+    /// Puts every header in its place along the bar, except a dragged one which only stays in the bar
+    fn position_headers(&mut self) {
+        let bar = self.bar_bounds();
+        let detachable = self.detachable;
+        let mut x = bar.x();
+        for tab in &mut self.tabs {
+            let slot = Vec4::xywh(x, bar.y(), tab.width, bar.height());
+            if !tab.header.dragging() {
+                *Element::bounds(&mut tab.header) = slot;
+            } else if !detachable {
+                Element::bounds(&mut tab.header).set_y(bar.y());
+            }
+            x += tab.width;
+        }
+    }
+
+    unsafe fn render_header(&mut self, index: usize) {
+        let selected = index == self.selected;
+        let tab = &mut self.tabs[index];
+        let style = tab.header.style().clone();
+        let palette = Palette::of(&style);
+        let bounds = tab.header.bounds();
+        let hovering = tab.header.hovering() || tab.header.dragging();
+
+        if selected || hovering {
+            draw_rect(&mut tab.rects[0], bounds, shade(palette.background, hovering && !selected, false, false), style.number("radius").unwrap_or(0.0));
+        }
+        if selected {
+            let underline = Vec4::xywh(bounds.x(), bounds.bottom() - 2.0, bounds.width(), 2.0);
+            draw_rect(&mut tab.rects[1], underline, palette.accent, 0.0);
+        }
+        let mut label = Vec4::ltrb(bounds.left() + TAB_PADDING, bounds.top(), bounds.right() - TAB_PADDING, bounds.bottom());
+        if tab.closable {
+            let close = tab.close_bounds();
+            if context().window().mouse().pos().intersects(&close) {
+                draw_rect(&mut tab.rects[2], close, shade(palette.background, true, false, false), 3.0);
+            }
+            draw_label(&style, "x", close, 0.5, shade(palette.foreground, false, false, !selected));
+            label = Vec4::ltrb(label.left(), label.top(), close.left(), label.bottom());
+        }
+        draw_label(&style, &tab.title, label, 0.0, shade(palette.foreground, false, false, !selected && !hovering));
+    }
+}
+
+impl UIHandler for TabView {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let mut result = self.element.handle(event);
+        let mouse = *context().window().mouse().pos();
+        let dragged = self.dragged();
+
+        if let Event::MouseClick(MouseButton::Button1, Action::Press) = event {
+            self.pressed_close = None;
+            for (i, tab) in self.tabs.iter_mut().enumerate() {
+                // Pressing the close button does not start dragging the tab
+                let on_close = tab.closable && mouse.intersects(&tab.close_bounds());
+                tab.header.draggable = !on_close;
+                if on_close {
+                    self.pressed_close = Some(i);
+                }
+            }
+        }
+        if !matches!(event, Event::Layout(_)) {
+            for tab in &mut self.tabs {
+                if let EventResult::Used = tab.header.handle(event) {
+                    result = EventResult::Used;
+                }
+            }
+        }
+
+        match event {
+            Event::PreRender => {
+                self.window_bounds = window_bounds(self.element.bounds());
+                for tab in &mut self.tabs {
+                    if tab.width == 0.0 {
+                        let mut width = measure(tab.header.style(), &tab.title).x + TAB_PADDING * 2.0;
+                        if tab.closable {
+                            width += CLOSE_SIZE + TAB_PADDING / 2.0;
+                        }
+                        tab.width = width;
+                        self.changed = true;
+                    }
+                }
+                if let Some(index) = dragged {
+                    let widths: Vec<f32> = self.tabs.iter().map(|t| t.width).collect();
+                    let x = self.tabs[index].header.bounds().center_x() - self.element.bounds().x();
+                    self.move_tab(index, reorder_index(&widths, index, x));
+                    self.changed = true;
+                }
+            }
+            Event::MouseClick(MouseButton::Button1, Action::Press) => {
+                if let Some(index) = self.tabs.iter().position(|t| t.header.hovering()) {
+                    self.select(index);
+                    result = EventResult::Used;
+                }
+            }
+            Event::MouseClick(MouseButton::Button1, Action::Release) => {
+                if let Some(index) = self.pressed_close.take() {
+                    if self.tabs.get_mut(index).is_some_and(|t| mouse.intersects(&t.close_bounds())) {
+                        self.close_tab(index);
+                        result = EventResult::Used;
+                    }
+                }
+                if let Some(index) = dragged {
+                    let header = self.tabs[index].header.bounds();
+                    let bar = self.bar_bounds();
+                    if self.detachable && (header.center_y() - bar.center_y()).abs() > self.bar_height {
+                        self.dropped = Some((index, window_bounds(Vec4::xywh(mouse.x, mouse.y, 0.0, 0.0)).xy()));
+                    }
+                    self.changed = true;
+                }
+            }
+            _ => {}
+        }
+
+        // The selected content is handled like a container child, relative to the view
+        match event {
+            Event::Layout(stage) => {
+                match stage {
+                    LayoutEvent::Position(_) => self.position_headers(),
+                    LayoutEvent::FitWidth => {
+                        let width: f32 = self.tabs.iter().map(|t| t.width).sum();
+                        let bounds = Element::bounds(&mut self.element);
+                        bounds.set_width(bounds.width().max(width));
+                    }
+                    LayoutEvent::FitHeight => {
+                        let bounds = Element::bounds(&mut self.element);
+                        bounds.set_height(bounds.height().max(self.bar_height));
+                    }
+                    _ => {}
+                }
+                self.layout_content(stage);
+            }
+            _ => {
+                let bounds = self.element.bounds();
+                if let Event::Render(RenderPass::Main) = event {
+                    let style = self.element.style().clone();
+                    let bar = self.bar_bounds();
+                    draw_rect(&mut self.rect, bar, Palette::of(&style).background, 0.0);
+                }
+                context().renderer().stack().push(State::Translate(bounds.x(), bounds.y()));
+                if let Some(tab) = self.tabs.get_mut(self.selected) {
+                    if let Event::PostRender = event {
+                        if let Some(mut animations) = tab.content.animations() {
+                            animations.post();
+                        }
+                    }
+                    if let EventResult::Used = tab.content.handle(event) {
+                        result = EventResult::Used;
+                    }
+                }
+                context().renderer().stack().pop();
+            }
+        }
+
+        match event {
+            Event::Render(RenderPass::Main) => {
+                // The dragged header is drawn last, over the ones it moves past
+                let order: Vec<usize> = (0..self.tabs.len()).filter(|i| Some(*i) != self.dragged()).chain(self.dragged()).collect();
+                for index in order {
+                    self.render_header(index);
+                }
+            }
+            Event::PostRender => {
+                self.changed = false;
+                self.dropped = None;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        for tab in &mut self.tabs {
+            let result = tab.header.tick(render_pass);
+            if !result.is_valid() {
+                return result;
+            }
+        }
+        if let Some(tab) = self.tabs.get_mut(self.selected) {
+            let result = tab.content.tick(render_pass);
+            if !result.is_valid() {
+                return result;
+            }
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+#[test]
+pub fn reordering() {
+    let widths = [100.0, 100.0, 100.0];
+    // The first tab has to pass the center of the second one to take its place
+    assert_eq!(reorder_index(&widths, 0, 140.0), 0);
+    assert_eq!(reorder_index(&widths, 0, 160.0), 1);
+    assert_eq!(reorder_index(&widths, 0, 290.0), 2);
+    assert_eq!(reorder_index(&widths, 2, 40.0), 0);
+    assert_eq!(reorder_index(&widths, 1, -20.0), 0);
+
+    assert_eq!(selection_after_close(2, 0, 3), 1);
+    assert_eq!(selection_after_close(2, 2, 2), 1);
+    assert_eq!(selection_after_close(0, 1, 2), 0);
+    assert_eq!(selection_after_close(0, 0, 0), 0);
+}
@@ -0,0 +1,770 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ops::Range;
+
+use glfw::{Action, Key, MouseButton};
+
+use crate::components::context::context;
+use crate::components::framework::animation::{Animated, AnimationRegistry, Easing};
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::{LayoutContext, Sizing};
+use crate::components::framework::style::ComputedStyle;
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::tab_view::reorder_index;
+use crate::components::framework::widgets::tree_view::SelectionMode;
+use crate::components::framework::widgets::{char_offsets, draw_label, draw_rect, measure, shade, Interaction, Palette};
+use crate::components::render::color::Color;
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::render::stack::State;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+const CELL_PADDING: f32 = 8.0;
+/// How close to the right edge of a header the mouse has to be to resize its column
+const RESIZE_MARGIN: f32 = 5.0;
+const ELLIPSIS: &str = "...";
+
+/// The rows of a [`Table`], only asked for the cells that are visible
+pub trait TableData {
+    fn row_count(&mut self) -> usize;
+
+    fn cell(&mut self, row: usize, column: usize) -> String;
+
+    /// The text a row is sorted by in a column, read once per row when sorting, the text of the cell by default.
+    ///
+    /// Returning `None` sorts the column with [`TableData::compare()`] instead, for orders other than by text.
+    fn sort_key(&mut self, row: usize, column: usize) -> Option<String> {
+        Some(self.cell(row, column))
+    }
+
+    /// Orders two rows by a column whose rows have no sort key
+    fn compare(&mut self, _a: usize, _b: usize, _column: usize) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+/// Orders two rows by a column, by their sort keys when both have one
+fn compare_rows<D: TableData>(data: &mut D, a: usize, b: usize, column: usize, order: SortOrder) -> Ordering {
+    let (a, b) = match order {
+        SortOrder::Ascending => (a, b),
+        SortOrder::Descending => (b, a),
+    };
+    match (data.sort_key(a, column), data.sort_key(b, column)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => data.compare(a, b, column),
+    }
+}
+
+/// The first `count` rows sorted by a column, keeping rows that are equal in their order
+fn sort_rows<D: TableData>(data: &mut D, count: usize, column: usize, order: SortOrder) -> Vec<usize> {
+    let mut rows: Vec<usize> = (0..count).collect();
+    let keys: Option<Vec<String>> = rows.iter().map(|r| data.sort_key(*r, column)).collect();
+    match (keys, order) {
+        (Some(keys), SortOrder::Ascending) => rows.sort_by(|a, b| keys[*a].cmp(&keys[*b])),
+        (Some(keys), SortOrder::Descending) => rows.sort_by(|a, b| keys[*b].cmp(&keys[*a])),
+        (None, SortOrder::Ascending) => rows.sort_by(|a, b| data.compare(*a, *b, column)),
+        (None, SortOrder::Descending) => rows.sort_by(|a, b| data.compare(*b, *a, column)),
+    }
+    rows
+}
+
+/// Inserts `rows` into the sorted `order`, each after the rows it equals like a stable sort would
+pub fn insert_sorted(order: &mut Vec<usize>, rows: Range<usize>, mut compare: impl FnMut(usize, usize) -> Ordering) {
+    for row in rows {
+        let position = order.partition_point(|r| compare(*r, row) != Ordering::Greater);
+        order.insert(position, row);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// The sort after clicking the header of `column`, going from ascending to descending to unsorted
+pub fn next_sort(sort: Option<(usize, SortOrder)>, column: usize) -> Option<(usize, SortOrder)> {
+    match sort {
+        Some((c, SortOrder::Ascending)) if c == column => Some((column, SortOrder::Descending)),
+        Some((c, SortOrder::Descending)) if c == column => None,
+        _ => Some((column, SortOrder::Ascending)),
+    }
+}
+
+/// How many characters fit in `max` with an ellipsis after them, or `None` if the whole text fits.
+///
+/// `offsets` are the caret positions of the text, from before the first character to after the last.
+pub fn fit_chars(offsets: &[f32], ellipsis: f32, max: f32) -> Option<usize> {
+    if offsets.last().is_some_and(|width| *width <= max) {
+        return None;
+    }
+    Some(offsets.partition_point(|offset| *offset + ellipsis <= max).saturating_sub(1))
+}
+
+/// Shortens `text` with an ellipsis so that it fits in `max`
+unsafe fn ellipsize(style: &ComputedStyle, text: String, max: f32) -> String {
+    match fit_chars(&char_offsets(style, &text), measure(style, ELLIPSIS).x, max) {
+        None => text,
+        Some(count) => text.chars().take(count).chain(ELLIPSIS.chars()).collect(),
+    }
+}
+
+/// Draws a label translated to `area`, so the font's cache is reused however far the row is scrolled
+unsafe fn draw_cell(style: &ComputedStyle, text: &str, area: Vec4, align: f32, color: Color) {
+    context().renderer().stack().push(State::Translate(area.x(), area.y()));
+    draw_label(style, text, Vec4::xywh(0.0, 0.0, area.width(), area.height()), align, color);
+    context().renderer().stack().pop();
+}
+
+/// A column of a [`Table`], with its header styled as `TableHeader`
+pub struct Column {
+    title: String,
+    width: f32,
+    min_width: f32,
+    align: f32,
+    sortable: bool,
+    resizable: bool,
+    header: Element,
+    rects: [Option<Rect>; 2],
+}
+
+impl Column {
+    pub fn new(title: impl ToString, width: f32) -> Self {
+        Column {
+            title: title.to_string(),
+            width,
+            min_width: 30.0,
+            align: 0.0,
+            sortable: true,
+            resizable: true,
+            header: ElementBuilder::new()
+                .style_type("TableHeader")
+                .draggable(true)
+                .build(),
+            rects: [None, None],
+        }
+    }
+
+    /// The width the column can not be resized below, `30` by default
+    pub fn min_width(mut self, min_width: f32) -> Self {
+        self.min_width = min_width;
+        self.width = self.width.max(min_width);
+        self
+    }
+
+    /// Where the text of the cells is, 0 for the left edge, 0.5 for the center and 1 for the right edge
+    pub fn align(mut self, align: f32) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn sortable(mut self, sortable: bool) -> Self {
+        self.sortable = sortable;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// If the mouse is on the edge of the header that resizes the column
+    fn on_edge(&self, mouse: Vec2<f32>) -> bool {
+        let header = self.header.bounds();
+        self.resizable && mouse.intersects(&header) && mouse.x >= header.right() - RESIZE_MARGIN
+    }
+}
+
+type SelectCallback = Box<dyn FnMut(&HashSet<usize>)>;
+type SortCallback = Box<dyn FnMut(Option<(usize, SortOrder)>)>;
+
+/// Rows of text in columns below a header that stays in place, styled as `Table`.
+///
+/// Only the visible rows are asked for and drawn, so tables with hundreds of thousands of rows stay
+/// fast. Clicking a header sorts by its column, dragging it moves the column and dragging its right
+/// edge resizes it. Text that does not fit in its cell is cut off with an ellipsis.
+///
+/// Rows are identified by their index in the [`TableData`], which stays the same when sorting.
+pub struct Table<D: TableData> {
+    element: Element,
+    interaction: Interaction,
+    data: D,
+    columns: Vec<Column>,
+    /// The columns in the order they are shown
+    display: Vec<usize>,
+    row_count: usize,
+    changed: bool,
+
+    sort: Option<(usize, SortOrder)>,
+    /// The data row shown at each position while sorted
+    order: Option<Vec<usize>>,
+    /// The position each data row is shown at while sorted
+    positions: Vec<usize>,
+
+    mode: SelectionMode,
+    selected: HashSet<usize>,
+    cursor: Option<usize>,
+    anchor: Option<usize>,
+
+    row_height: f32,
+    header_height: f32,
+    scroll: Animated<f32>,
+    target_scroll: f32,
+    scroll_x: f32,
+    follow: bool,
+
+    /// The column being resized, with the mouse position and the width when it started
+    resizing: Option<(usize, f32, f32)>,
+    /// The column whose header was pressed, with the mouse position, which sorts it when released in place
+    pressed_header: Option<(usize, Vec2<f32>)>,
+    rects: Vec<Option<Rect>>,
+    header_rect: Option<Rect>,
+
+    on_select: Option<SelectCallback>,
+    on_sort: Option<SortCallback>,
+}
+
+impl<D: TableData> Table<D> {
+    pub fn new(data: D, columns: impl IntoIterator<Item=Column>) -> Self {
+        let scroll = Animated::new(0.0);
+        let columns: Vec<Column> = columns.into_iter().collect();
+        Table {
+            element: ElementBuilder::new()
+                .style_type("Table")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(100.0, 80.0),
+                    size_behavior: (Sizing::Grow, Sizing::Grow),
+                    ..LayoutContext::new()
+                })
+                .register_animations(vec![scroll.animation()])
+                .build(),
+            interaction: Interaction::new(),
+            data,
+            display: (0..columns.len()).collect(),
+            columns,
+            row_count: 0,
+            changed: true,
+            sort: None,
+            order: None,
+            positions: Vec::new(),
+            mode: SelectionMode::Single,
+            selected: HashSet::new(),
+            cursor: None,
+            anchor: None,
+            row_height: 22.0,
+            header_height: 28.0,
+            scroll,
+            target_scroll: 0.0,
+            scroll_x: 0.0,
+            follow: false,
+            resizing: None,
+            pressed_header: None,
+            rects: Vec::new(),
+            header_rect: None,
+            on_select: None,
+            on_sort: None,
+        }
+    }
+
+    pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The height of every row, `22` by default
+    pub fn row_height(mut self, row_height: f32) -> Self {
+        self.row_height = row_height;
+        self
+    }
+
+    pub fn header_height(mut self, header_height: f32) -> Self {
+        self.header_height = header_height;
+        self
+    }
+
+    /// Keeps the last row in view as rows are added while scrolled to the bottom, like a log
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    /// Called with the selected rows every time the user changes the selection
+    pub fn on_select<F: FnMut(&HashSet<usize>) + 'static>(mut self, on_select: F) -> Self {
+        self.on_select = Some(Box::new(on_select));
+        self
+    }
+
+    /// Called with the column and order every time the user changes the sort
+    pub fn on_sort<F: FnMut(Option<(usize, SortOrder)>) + 'static>(mut self, on_sort: F) -> Self {
+        self.on_sort = Some(Box::new(on_sort));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn data(&mut self) -> &mut D {
+        &mut self.data
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// The indices of the columns in the order they are shown
+    pub fn column_order(&self) -> &[usize] {
+        &self.display
+    }
+
+    pub fn set_column_width(&mut self, column: usize, width: f32) {
+        if let Some(c) = self.columns.get_mut(column) {
+            c.width = width.max(c.min_width);
+            self.changed = true;
+        }
+    }
+
+    pub fn sort(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    /// Sorts by a column without calling the sort callback
+    pub fn set_sort(&mut self, sort: Option<(usize, SortOrder)>) {
+        self.sort = sort.filter(|(c, _)| *c < self.columns.len());
+        self.resort();
+    }
+
+    /// Sorts the rows again, for when existing rows changed. Added rows are sorted in on their own.
+    pub fn resort(&mut self) {
+        self.order = self.sort.map(|(column, order)| sort_rows(&mut self.data, self.row_count, column, order));
+        self.update_positions();
+        self.changed = true;
+    }
+
+    fn update_positions(&mut self) {
+        self.positions.clear();
+        if let Some(order) = &self.order {
+            self.positions.resize(order.len(), 0);
+            for (position, row) in order.iter().enumerate() {
+                self.positions[*row] = position;
+            }
+        }
+    }
+
+    pub fn selected(&self) -> &HashSet<usize> {
+        &self.selected
+    }
+
+    /// Selects rows without calling the select callback
+    pub fn set_selected(&mut self, selected: impl IntoIterator<Item=usize>) {
+        self.selected = selected.into_iter().filter(|r| *r < self.row_count).collect();
+        self.changed = true;
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    /// The data row shown at a position
+    fn data_row(&self, position: usize) -> usize {
+        match &self.order {
+            Some(order) => order[position],
+            None => position,
+        }
+    }
+
+    /// The position a data row is shown at
+    fn position_of(&self, row: usize) -> Option<usize> {
+        match &self.order {
+            Some(_) => self.positions.get(row).copied(),
+            None => (row < self.row_count).then_some(row),
+        }
+    }
+
+    fn body_height(&self) -> f32 {
+        (self.element.bounds().height() - self.header_height).max(0.0)
+    }
+
+    fn total_width(&self) -> f32 {
+        self.columns.iter().map(|c| c.width).sum()
+    }
+
+    fn max_scroll(&self) -> f32 {
+        (self.row_count as f32 * self.row_height - self.body_height()).max(0.0)
+    }
+
+    /// Scrolls as little as possible to show the whole row at a position
+    fn scroll_to(&mut self, position: usize) {
+        let top = position as f32 * self.row_height;
+        if top < self.target_scroll {
+            self.target_scroll = top;
+        } else if top + self.row_height > self.target_scroll + self.body_height() {
+            self.target_scroll = top + self.row_height - self.body_height();
+        }
+        self.target_scroll = self.target_scroll.clamp(0.0, self.max_scroll());
+    }
+
+    /// Checks for added or removed rows at the end, sorting in the added ones
+    fn update_rows(&mut self) {
+        let count = self.data.row_count();
+        let previous = self.row_count;
+        if count == previous {
+            return;
+        }
+        let at_bottom = self.target_scroll >= self.max_scroll() - 1.0;
+        self.row_count = count;
+        self.selected.retain(|r| *r < count);
+        self.cursor = self.cursor.filter(|c| *c < count);
+        if let (Some((column, order)), Some(rows)) = (self.sort, &mut self.order) {
+            let data = &mut self.data;
+            if count < previous {
+                rows.retain(|r| *r < count);
+            } else if count - previous <= previous {
+                insert_sorted(rows, previous..count, |a, b| compare_rows(data, a, b, column, order));
+            } else {
+                // Sorting everything is faster once most rows are new
+                *rows = sort_rows(data, count, column, order);
+            }
+            self.update_positions();
+        }
+        if self.follow && at_bottom {
+            self.target_scroll = self.max_scroll();
+        }
+        self.target_scroll = self.target_scroll.clamp(0.0, self.max_scroll());
+        self.changed = true;
+    }
+
+    fn notify(&mut self) {
+        self.changed = true;
+        if let Some(on_select) = &mut self.on_select {
+            on_select(&self.selected);
+        }
+    }
+
+    /// Selects the row at a position like clicking it, where Control toggles and Shift extends in multiple selection
+    fn select_position(&mut self, position: usize, toggle: bool, extend: bool) {
+        if position >= self.row_count {
+            return;
+        }
+        let row = self.data_row(position);
+        let anchor = self.anchor.and_then(|a| self.position_of(a));
+        match (self.mode, anchor) {
+            (SelectionMode::Multiple, Some(anchor)) if extend => {
+                let range = anchor.min(position)..=anchor.max(position);
+                self.selected = range.map(|p| self.data_row(p)).collect();
+            }
+            (SelectionMode::Multiple, _) if toggle => {
+                if !self.selected.remove(&row) {
+                    self.selected.insert(row);
+                }
+                self.anchor = Some(row);
+            }
+            _ => {
+                self.selected = HashSet::from([row]);
+                self.anchor = Some(row);
+            }
+        }
+        self.cursor = Some(row);
+        self.scroll_to(position);
+        self.notify();
+    }
+
+    unsafe fn key(&mut self, key: Key) {
+        if self.row_count == 0 {
+            return;
+        }
+        let keyboard = context().keyboard();
+        let (shift, ctrl) = (keyboard.shift(), keyboard.ctrl());
+        let last = self.row_count - 1;
+        let page = (self.body_height() / self.row_height).floor().max(1.0) as usize;
+        let cursor = self.cursor.and_then(|c| self.position_of(c));
+        let target = match (key, cursor) {
+            (Key::Up, Some(c)) => c.saturating_sub(1),
+            (Key::Down, Some(c)) => (c + 1).min(last),
+            (Key::PageUp, Some(c)) => c.saturating_sub(page),
+            (Key::PageDown, Some(c)) => (c + page).min(last),
+            (Key::Up | Key::Down | Key::Home, _) => 0,
+            (Key::End, _) => last,
+            (Key::A, _) if ctrl && self.mode == SelectionMode::Multiple => {
+                self.selected = (0..self.row_count).collect();
+                self.notify();
+                return;
+            }
+            (Key::Space, Some(c)) => {
+                self.select_position(c, true, false);
+                return;
+            }
+            _ => return,
+        };
+        self.select_position(target, false, shift);
+    }
+
+    /// Puts every header in its place, except a dragged one which only stays in the header row
+    fn position_headers(&mut self) {
+        let bounds = self.element.bounds();
+        let mut x = bounds.x() - self.scroll_x;
+        for column in &self.display {
+            let column = &mut self.columns[*column];
+            if column.header.dragging() {
+                Element::bounds(&mut column.header).set_y(bounds.y());
+            } else {
+                *Element::bounds(&mut column.header) = Vec4::xywh(x, bounds.y(), column.width, self.header_height);
+            }
+            x += column.width;
+        }
+    }
+
+    /// Moves a dragged header's column to where its center is
+    fn reorder_dragged(&mut self) {
+        let Some(position) = self.display.iter().position(|c| self.columns[*c].header.dragging()) else {
+            return;
+        };
+        let widths: Vec<f32> = self.display.iter().map(|c| self.columns[*c].width).collect();
+        let header = self.columns[self.display[position]].header.bounds();
+        let x = header.center_x() - self.element.bounds().x() + self.scroll_x;
+        let target = reorder_index(&widths, position, x);
+        if target != position {
+            let column = self.display.remove(position);
+            self.display.insert(target, column);
+        }
+        self.changed = true;
+    }
+
+    unsafe fn render_rows(&mut self, style: &ComputedStyle, palette: &Palette) {
+        let bounds = self.element.bounds();
+        let body = Vec4::xywh(bounds.x(), bounds.y() + self.header_height, bounds.width(), self.body_height());
+        let scroll = self.scroll.value();
+        let first = (scroll / self.row_height).floor().max(0.0) as usize;
+        let last = (((scroll + body.height()) / self.row_height).ceil() as usize).min(self.row_count);
+        let visible = last.saturating_sub(first);
+        if self.rects.len() < visible {
+            self.rects.resize_with(visible, || None);
+        }
+
+        context().renderer().stack().push(State::Clip(body));
+        for (slot, position) in (first..last).enumerate() {
+            let row = self.data_row(position);
+            let area = Vec4::xywh(body.x(), body.y() + position as f32 * self.row_height - scroll, body.width(), self.row_height);
+            if self.selected.contains(&row) {
+                let color = if self.element.focused() { palette.accent } else { palette.accent.set_alpha_f32(palette.accent.alpha() * 0.5) };
+                draw_rect(&mut self.rects[slot], area, color, 0.0);
+            } else if position % 2 == 1 {
+                // Every other row is a little lighter, to follow rows across wide tables
+                let stripe = shade(palette.background, true, false, false);
+                draw_rect(&mut self.rects[slot], area, stripe.set_alpha_f32(stripe.alpha() * 0.5), 0.0);
+            }
+
+            let mut x = body.x() - self.scroll_x;
+            for index in &self.display {
+                let column = &self.columns[*index];
+                let cell = Vec4::xywh(x + CELL_PADDING, area.y(), column.width - CELL_PADDING * 2.0, area.height());
+                x += column.width;
+                if cell.right() < body.left() || cell.left() > body.right() {
+                    continue;
+                }
+                let text = ellipsize(style, self.data.cell(row, *index), cell.width());
+                draw_cell(style, &text, cell, column.align, palette.foreground);
+            }
+        }
+        context().renderer().stack().pop();
+    }
+
+    unsafe fn render_header(&mut self, palette: &Palette) {
+        let bounds = self.element.bounds();
+        let header = Vec4::xywh(bounds.x(), bounds.y(), bounds.width(), self.header_height);
+        draw_rect(&mut self.header_rect, header, shade(palette.background, true, false, false), 0.0);
+
+        context().renderer().stack().push(State::Clip(header));
+        let dragged = self.display.iter().copied().find(|c| self.columns[*c].header.dragging());
+        // The dragged header is drawn last, over the ones it moves past
+        let order: Vec<usize> = self.display.iter().copied().filter(|c| Some(*c) != dragged).chain(dragged).collect();
+        for index in order {
+            let sort = self.sort.filter(|(c, _)| *c == index).map(|(_, order)| order);
+            let column = &mut self.columns[index];
+            let style = column.header.style().clone();
+            let palette = Palette::of(&style);
+            let area = column.header.bounds();
+            let hovering = column.header.hovering() || column.header.dragging();
+            if hovering {
+                draw_rect(&mut column.rects[0], area, shade(palette.background, true, false, false), 0.0);
+            }
+            let separator = Vec4::xywh(area.right() - 1.0, area.y() + 4.0, 1.0, area.height() - 8.0);
+            draw_rect(&mut column.rects[1], separator, palette.border, 0.0);
+
+            let mut text = Vec4::ltrb(area.left() + CELL_PADDING, area.top(), area.right() - CELL_PADDING, area.bottom());
+            if let Some(order) = sort {
+                let arrow = Vec4::ltrb(text.right() - 12.0, text.top(), text.right(), text.bottom());
+                draw_label(&style, if order == SortOrder::Ascending { "^" } else { "v" }, arrow, 0.5, palette.foreground);
+                text = Vec4::ltrb(text.left(), text.top(), arrow.left(), text.bottom());
+            }
+            let title = ellipsize(&style, column.title.clone(), text.width());
+            draw_cell(&style, &title, text, column.align, palette.foreground);
+        }
+        context().renderer().stack().pop();
+    }
+}
+
+impl<D: TableData> UIHandler for Table<D> {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let mut result = self.element.handle(event);
+        self.interaction.handle(&mut self.element, event);
+        let mouse = *context().window().mouse().pos();
+
+        if let Event::MouseClick(MouseButton::Button1, Action::Press) = event {
+            // Pressing the edge of a header resizes its column instead of dragging it
+            for (index, column) in self.columns.iter_mut().enumerate() {
+                column.header.draggable = !column.on_edge(mouse);
+                if column.on_edge(mouse) {
+                    self.resizing = Some((index, mouse.x, column.width));
+                }
+            }
+        }
+        if !matches!(event, Event::Layout(_)) {
+            for column in &mut self.columns {
+                if let EventResult::Used = column.header.handle(event) {
+                    result = EventResult::Used;
+                }
+            }
+        }
+
+        match event {
+            Event::PreRender => {
+                self.update_rows();
+                if let Some((index, start, width)) = self.resizing {
+                    self.set_column_width(index, width + mouse.x - start);
+                }
+                self.reorder_dragged();
+                let width = self.element.bounds().width();
+                self.scroll_x = self.scroll_x.clamp(0.0, (self.total_width() - width).max(0.0));
+                self.scroll.animate_to(self.target_scroll, 15.0, Easing::Sin);
+                self.position_headers();
+            }
+            Event::Layout(_) => self.position_headers(),
+            Event::Scroll(x, y) if self.element.hovering() => {
+                let (x, y) = if context().keyboard().shift() { (*y, 0.0) } else { (*x, *y) };
+                self.target_scroll = (self.target_scroll - y * self.row_height * 3.0).clamp(0.0, self.max_scroll());
+                self.scroll_x = (self.scroll_x - x * 40.0).clamp(0.0, (self.total_width() - self.element.bounds().width()).max(0.0));
+                self.changed = true;
+                return EventResult::Used;
+            }
+            Event::MouseClick(MouseButton::Button1, Action::Press) if self.element.hovering() && self.element.active => {
+                let bounds = self.element.bounds();
+                if mouse.y < bounds.y() + self.header_height {
+                    if self.resizing.is_none() {
+                        self.pressed_header = self.columns.iter().position(|c| c.header.hovering()).map(|c| (c, mouse));
+                    }
+                } else {
+                    let position = ((mouse.y - bounds.y() - self.header_height + self.scroll.value()) / self.row_height).floor();
+                    if position >= 0.0 {
+                        let keyboard = context().keyboard();
+                        let (ctrl, shift) = (keyboard.ctrl(), keyboard.shift());
+                        self.select_position(position as usize, ctrl, shift);
+                    }
+                }
+                result = EventResult::Used;
+            }
+            Event::MouseClick(MouseButton::Button1, Action::Release) => {
+                self.resizing = None;
+                if let Some((column, pressed)) = self.pressed_header.take() {
+                    // A click sorts, a drag only moves the column
+                    let moved = (mouse.x - pressed.x).abs().max((mouse.y - pressed.y).abs());
+                    if moved < 4.0 && self.columns[column].sortable {
+                        let sort = next_sort(self.sort, column);
+                        self.set_sort(sort);
+                        if let Some(on_sort) = &mut self.on_sort {
+                            on_sort(sort);
+                        }
+                    }
+                }
+                self.changed = true;
+            }
+            Event::Keyboard(key, Action::Press | Action::Repeat, _) if self.element.focused() && self.element.active => {
+                self.key(*key);
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.element.style().clone();
+                let palette = Palette::of(&style);
+                self.render_rows(&style, &palette);
+                self.render_header(&palette);
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        for column in &mut self.columns {
+            let result = column.header.tick(render_pass);
+            if !result.is_valid() {
+                return result;
+            }
+        }
+        if self.changed || self.resizing.is_some() || self.data.row_count() != self.row_count {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+#[test]
+pub fn sorting_and_truncation() {
+    assert_eq!(next_sort(None, 2), Some((2, SortOrder::Ascending)));
+    assert_eq!(next_sort(Some((2, SortOrder::Ascending)), 2), Some((2, SortOrder::Descending)));
+    assert_eq!(next_sort(Some((2, SortOrder::Descending)), 2), None);
+    assert_eq!(next_sort(Some((1, SortOrder::Descending)), 2), Some((2, SortOrder::Ascending)));
+
+    // Every character is 10 wide and the ellipsis 15
+    let offsets = [0.0, 10.0, 20.0, 30.0, 40.0, 50.0];
+    assert_eq!(fit_chars(&offsets, 15.0, 50.0), None);
+    assert_eq!(fit_chars(&offsets, 15.0, 49.0), Some(3));
+    assert_eq!(fit_chars(&offsets, 15.0, 35.0), Some(2));
+    assert_eq!(fit_chars(&offsets, 15.0, 10.0), Some(0));
+
+    // Rows sorted in one by one end up where sorting all of them puts them
+    struct Words(Vec<&'static str>);
+    impl TableData for Words {
+        fn row_count(&mut self) -> usize {
+            self.0.len()
+        }
+        fn cell(&mut self, row: usize, _column: usize) -> String {
+            self.0[row].to_string()
+        }
+    }
+    let mut words = Words(vec!["pear", "apple", "fig", "apple", "kiwi", "fig", "banana"]);
+    for order in [SortOrder::Ascending, SortOrder::Descending] {
+        let mut rows = sort_rows(&mut words, 3, 0, order);
+        insert_sorted(&mut rows, 3..7, |a, b| compare_rows(&mut words, a, b, 0, order));
+        assert_eq!(rows, sort_rows(&mut words, 7, 0, order));
+    }
+    assert_eq!(sort_rows(&mut words, 7, 0, SortOrder::Ascending), vec![1, 3, 6, 2, 5, 4, 0]);
+}
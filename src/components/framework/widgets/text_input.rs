@@ -0,0 +1,609 @@
+use std::rc::Rc;
+
+use glfw::{Action, Key, Modifiers};
+use regex::Regex;
+
+use crate::components::context::context;
+use crate::components::framework::animation::AnimationRegistry;
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::{LayoutContext, Sizing};
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::{char_offsets, draw_label, draw_rect, inset, shade, Interaction, Palette};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::render::stack::State;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// How long the caret stays visible, and then hidden, in seconds
+const BLINK_INTERVAL: f64 = 0.5;
+const CARET_WIDTH: f32 = 1.5;
+
+type TextCallback = Box<dyn FnMut(&str)>;
+
+/// Which text a [`TextInput`] accepts. Edits that would make the text rejected are ignored.
+#[derive(Clone)]
+pub enum InputFilter {
+    Any,
+    /// Digits with an optional leading `-` and a single `.`
+    Numeric,
+    /// Digits with an optional leading `-`
+    Integer,
+    /// The whole text has to match the expression,
+    /// so it has to accept every prefix of a valid input, such as `^[0-9a-f]{0,6}$`
+    Regex(Regex),
+    Custom(Rc<dyn Fn(&str) -> bool>),
+}
+
+impl InputFilter {
+    pub fn regex(pattern: &str) -> Result<Self, String> {
+        Regex::new(pattern).map(InputFilter::Regex).map_err(|e| e.to_string())
+    }
+
+    pub fn accepts(&self, text: &str) -> bool {
+        match self {
+            InputFilter::Any => true,
+            InputFilter::Numeric => {
+                let digits = text.strip_prefix('-').unwrap_or(text);
+                digits.chars().all(|c| c.is_ascii_digit() || c == '.') && digits.matches('.').count() <= 1
+            }
+            InputFilter::Integer => {
+                text.strip_prefix('-').unwrap_or(text).chars().all(|c| c.is_ascii_digit())
+            }
+            InputFilter::Regex(regex) => regex.is_match(text),
+            InputFilter::Custom(accepts) => accepts(text),
+        }
+    }
+}
+
+/// The text, caret and selection of a single line of input, without anything to draw.
+///
+/// Positions are in characters, and the selection goes from the anchor to the caret.
+#[derive(Clone)]
+pub struct LineEdit {
+    text: String,
+    caret: usize,
+    anchor: usize,
+    max_length: Option<usize>,
+    filter: InputFilter,
+}
+
+impl LineEdit {
+    pub fn new(text: impl ToString) -> Self {
+        let mut edit = LineEdit {
+            text: String::new(),
+            caret: 0,
+            anchor: 0,
+            max_length: None,
+            filter: InputFilter::Any,
+        };
+        edit.set_text(text);
+        edit
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text, ignoring the filter, and moves the caret to the end
+    pub fn set_text(&mut self, text: impl ToString) {
+        let text = text.to_string().replace(['\r', '\n'], "");
+        self.text = match self.max_length {
+            Some(max) => text.chars().take(max).collect(),
+            None => text,
+        };
+        self.caret = self.len();
+        self.anchor = self.caret;
+    }
+
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+        let text = self.text.clone();
+        self.set_text(text);
+    }
+
+    pub fn set_filter(&mut self, filter: InputFilter) {
+        self.filter = filter;
+    }
+
+    /// The number of characters
+    pub fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    /// The start and end of the selection, if anything is selected
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        if self.caret == self.anchor {
+            None
+        } else {
+            Some((self.caret.min(self.anchor), self.caret.max(self.anchor)))
+        }
+    }
+
+    pub fn selected_text(&self) -> &str {
+        match self.selection() {
+            None => "",
+            Some((start, end)) => &self.text[self.byte(start)..self.byte(end)],
+        }
+    }
+
+    fn byte(&self, index: usize) -> usize {
+        self.text.char_indices().nth(index).map(|(i, _)| i).unwrap_or(self.text.len())
+    }
+
+    /// Moves the caret, keeping the anchor where it was when `select` is true
+    pub fn move_to(&mut self, index: usize, select: bool) {
+        self.caret = index.min(self.len());
+        if !select {
+            self.anchor = self.caret;
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.caret = self.len();
+    }
+
+    pub fn left(&mut self, select: bool, word: bool) {
+        let to = match (self.selection(), select, word) {
+            (Some((start, _)), false, false) => start,
+            (_, _, true) => self.word_start(self.caret),
+            _ => self.caret.saturating_sub(1),
+        };
+        self.move_to(to, select);
+    }
+
+    pub fn right(&mut self, select: bool, word: bool) {
+        let to = match (self.selection(), select, word) {
+            (Some((_, end)), false, false) => end,
+            (_, _, true) => self.word_end(self.caret),
+            _ => self.caret + 1,
+        };
+        self.move_to(to, select);
+    }
+
+    fn word_start(&self, from: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut i = from.min(chars.len());
+        while i > 0 && !chars[i - 1].is_alphanumeric() {
+            i -= 1;
+        }
+        while i > 0 && chars[i - 1].is_alphanumeric() {
+            i -= 1;
+        }
+        i
+    }
+
+    fn word_end(&self, from: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut i = from.min(chars.len());
+        while i < chars.len() && !chars[i].is_alphanumeric() {
+            i += 1;
+        }
+        while i < chars.len() && chars[i].is_alphanumeric() {
+            i += 1;
+        }
+        i
+    }
+
+    /// Replaces the characters in `start..end` if the result is accepted by the filter
+    fn replace(&mut self, start: usize, end: usize, with: &str) -> bool {
+        let with: String = with.chars().filter(|c| !c.is_control()).collect();
+        let kept = self.len() - (end - start);
+        let with: String = match self.max_length {
+            Some(max) => with.chars().take(max.saturating_sub(kept)).collect(),
+            None => with,
+        };
+        if start == end && with.is_empty() {
+            return false;
+        }
+
+        let mut text = self.text.clone();
+        text.replace_range(self.byte(start)..self.byte(end), &with);
+        if !self.filter.accepts(&text) {
+            return false;
+        }
+        self.text = text;
+        self.caret = start + with.chars().count();
+        self.anchor = self.caret;
+        true
+    }
+
+    /// Types `text` over the selection, returns if the text changed
+    pub fn insert(&mut self, text: &str) -> bool {
+        let (start, end) = self.selection().unwrap_or((self.caret, self.caret));
+        self.replace(start, end, text)
+    }
+
+    /// Removes the selection, or the character or word before the caret
+    pub fn backspace(&mut self, word: bool) -> bool {
+        match self.selection() {
+            Some((start, end)) => self.replace(start, end, ""),
+            None if word => self.replace(self.word_start(self.caret), self.caret, ""),
+            None => self.replace(self.caret.saturating_sub(1), self.caret, ""),
+        }
+    }
+
+    /// Removes the selection, or the character or word after the caret
+    pub fn delete(&mut self, word: bool) -> bool {
+        match self.selection() {
+            Some((start, end)) => self.replace(start, end, ""),
+            None if word => self.replace(self.caret, self.word_end(self.caret), ""),
+            None => self.replace(self.caret, (self.caret + 1).min(self.len()), ""),
+        }
+    }
+}
+
+/// The caret position closest to `x`, given the x of every caret position
+pub fn index_at(offsets: &[f32], x: f32) -> usize {
+    let mut closest = 0;
+    for (i, offset) in offsets.iter().enumerate() {
+        if (offset - x).abs() < (offsets[closest] - x).abs() {
+            closest = i;
+        }
+    }
+    closest
+}
+
+/// Scrolls as little as possible so that `caret` is inside `0..width`,
+/// without scrolling past the end of text that is `total` wide
+pub fn scroll_to_show(scroll: f32, caret: f32, width: f32, total: f32) -> f32 {
+    let mut scroll = scroll;
+    if caret - scroll > width {
+        scroll = caret - width;
+    }
+    if caret < scroll {
+        scroll = caret;
+    }
+    scroll.min((total - width).max(0.0)).max(0.0)
+}
+
+/// A single line of editable text, styled as `TextInput`.
+///
+/// Selects with the mouse, Shift and the arrow keys, or Ctrl+A, and uses Ctrl to move and delete
+/// by words. Ctrl+C, Ctrl+X and Ctrl+V use the clipboard, and Enter submits.
+pub struct TextInput {
+    element: Element,
+    interaction: Interaction,
+    edit: LineEdit,
+    placeholder: String,
+    mask: Option<char>,
+    changed: bool,
+
+    offsets: Vec<f32>,
+    scroll: f32,
+    blink_start: f64,
+    caret_visible: bool,
+    rects: [Option<Rect>; 4],
+
+    on_change: Option<TextCallback>,
+    on_submit: Option<TextCallback>,
+}
+
+impl TextInput {
+    pub fn new(text: impl ToString) -> Self {
+        TextInput {
+            element: ElementBuilder::new()
+                .style_type("TextInput")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(160.0, 28.0),
+                    padding: Vec4::ltrb(8.0, 4.0, 8.0, 4.0),
+                    size_behavior: (Sizing::Grow, Sizing::Shrink),
+                    ..LayoutContext::new()
+                })
+                .build(),
+            interaction: Interaction::new(),
+            edit: LineEdit::new(text),
+            placeholder: String::new(),
+            mask: None,
+            changed: true,
+            offsets: vec![0.0],
+            scroll: 0.0,
+            blink_start: 0.0,
+            caret_visible: true,
+            rects: [None, None, None, None],
+            on_change: None,
+            on_submit: None,
+        }
+    }
+
+    /// Shown in a faded color while the text is empty
+    pub fn placeholder(mut self, placeholder: impl ToString) -> Self {
+        self.placeholder = placeholder.to_string();
+        self
+    }
+
+    /// Shows every character as `mask`, and disables copying
+    pub fn password(mut self, mask: char) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.edit.set_max_length(Some(max_length));
+        self
+    }
+
+    pub fn filter(mut self, filter: InputFilter) -> Self {
+        self.edit.set_filter(filter);
+        self
+    }
+
+    /// Called with the text after every edit
+    pub fn on_change<F: FnMut(&str) + 'static>(mut self, on_change: F) -> Self {
+        self.on_change = Some(Box::new(on_change));
+        self
+    }
+
+    /// Called with the text when Enter is pressed
+    pub fn on_submit<F: FnMut(&str) + 'static>(mut self, on_submit: F) -> Self {
+        self.on_submit = Some(Box::new(on_submit));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.set_disabled(disabled);
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        self.edit.text()
+    }
+
+    /// Sets the text without calling the change callback
+    pub fn set_text(&mut self, text: impl ToString) {
+        self.edit.set_text(text);
+        self.changed = true;
+    }
+
+    pub fn edit(&mut self) -> &mut LineEdit {
+        self.changed = true;
+        &mut self.edit
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.element.disabled()
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.element.set_disabled(disabled);
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    fn displayed(&self) -> String {
+        match self.mask {
+            Some(mask) => mask.to_string().repeat(self.edit.len()),
+            None => self.edit.text().to_string(),
+        }
+    }
+
+    fn text_bounds(&self) -> Vec4 {
+        let bounds = self.element.bounds();
+        let padding = self.element.layout_context().padding;
+        Vec4::ltrb(
+            bounds.left() + padding.left(),
+            bounds.top() + padding.top(),
+            bounds.right() - padding.right(),
+            bounds.bottom() - padding.bottom(),
+        )
+    }
+
+    /// The caret position under the mouse
+    unsafe fn mouse_index(&self) -> usize {
+        let x = context().window().mouse().pos().x - self.text_bounds().left() + self.scroll;
+        index_at(&self.offsets, x)
+    }
+
+    unsafe fn edited(&mut self, changed_text: bool) {
+        self.changed = true;
+        self.blink_start = context().framework().clock().elapsed();
+        if changed_text {
+            if let Some(on_change) = &mut self.on_change {
+                on_change(self.edit.text());
+            }
+        }
+    }
+
+    unsafe fn key(&mut self, key: Key, mods: Modifiers) {
+        let shift = mods.contains(Modifiers::Shift);
+        let ctrl = mods.contains(Modifiers::Control);
+        let clipboard = context().p_window();
+        let changed = match key {
+            Key::Left => { self.edit.left(shift, ctrl); false }
+            Key::Right => { self.edit.right(shift, ctrl); false }
+            Key::Home => { self.edit.move_to(0, shift); false }
+            Key::End => { self.edit.move_to(self.edit.len(), shift); false }
+            Key::Backspace => self.edit.backspace(ctrl),
+            Key::Delete => self.edit.delete(ctrl),
+            Key::A if ctrl => { self.edit.select_all(); false }
+            Key::C if ctrl && self.mask.is_none() => {
+                clipboard.set_clipboard_string(self.edit.selected_text());
+                false
+            }
+            Key::X if ctrl && self.mask.is_none() => {
+                clipboard.set_clipboard_string(self.edit.selected_text());
+                self.edit.backspace(false)
+            }
+            Key::V if ctrl => match clipboard.get_clipboard_string() {
+                Some(pasted) => self.edit.insert(&pasted.replace(['\r', '\n'], " ")),
+                None => false,
+            },
+            Key::Enter | Key::KpEnter => {
+                if let Some(on_submit) = &mut self.on_submit {
+                    on_submit(self.edit.text());
+                }
+                false
+            }
+            _ => return,
+        };
+        self.edited(changed);
+    }
+}
+
+impl UIHandler for TextInput {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+        self.interaction.handle(&mut self.element, event);
+
+        if self.element.interactive() {
+            match event {
+                Event::MouseClick(_, Action::Press) if self.interaction.is_pressed() => {
+                    let shift = context().keyboard().shift();
+                    self.edit.move_to(self.mouse_index(), shift);
+                    self.edited(false);
+                }
+                Event::Keyboard(key, Action::Press | Action::Repeat, mods) if self.element.focused() => {
+                    self.key(*key, *mods);
+                }
+                Event::Char(c) if self.element.focused() => {
+                    let changed = self.edit.insert(c.encode_utf8(&mut [0; 4]));
+                    self.edited(changed);
+                }
+                _ => {}
+            }
+        }
+
+        match event {
+            Event::PreRender => {
+                if self.changed {
+                    self.offsets = char_offsets(self.element.style(), &self.displayed());
+                }
+                if self.interaction.is_pressed() {
+                    let index = self.mouse_index();
+                    if index != self.edit.caret() {
+                        self.edit.move_to(index, true);
+                        self.changed = true;
+                    }
+                }
+
+                let width = self.text_bounds().width();
+                let total = *self.offsets.last().unwrap();
+                let scroll = scroll_to_show(self.scroll, self.offsets[self.edit.caret()], width - CARET_WIDTH, total);
+                if scroll != self.scroll {
+                    self.scroll = scroll;
+                    self.changed = true;
+                }
+
+                let since = context().framework().clock().elapsed() - self.blink_start;
+                let visible = self.element.focused() && ((since / BLINK_INTERVAL) as u64).is_multiple_of(2);
+                if visible != self.caret_visible {
+                    self.caret_visible = visible;
+                    self.changed = true;
+                }
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.element.style().clone();
+                let palette = Palette::of(&style);
+                let bounds = self.element.bounds();
+                let (hovering, focused, disabled) = (self.element.hovering(), self.element.focused(), self.is_disabled());
+                let text_bounds = self.text_bounds();
+
+                let border = if focused { palette.accent } else { palette.border };
+                draw_rect(&mut self.rects[0], bounds, shade(border, hovering && !focused, false, disabled), 4.0);
+                draw_rect(&mut self.rects[1], inset(bounds, 1.0), shade(palette.background, false, false, disabled), 3.0);
+
+                context().renderer().stack().push(State::Clip(text_bounds));
+                let x = text_bounds.left() - self.scroll;
+                if let Some((start, end)) = self.edit.selection() {
+                    let selection = Vec4::ltrb(x + self.offsets[start], text_bounds.top(), x + self.offsets[end], text_bounds.bottom());
+                    let color = palette.accent.set_alpha_f32(if focused { 0.4 } else { 0.2 });
+                    draw_rect(&mut self.rects[2], selection, color, 0.0);
+                }
+                if self.edit.is_empty() {
+                    let faded = palette.foreground.set_alpha_f32(palette.foreground.alpha() * 0.5);
+                    draw_label(&style, &self.placeholder, text_bounds, 0.0, shade(faded, false, false, disabled));
+                } else {
+                    let line = Vec4::xywh(x, text_bounds.top(), *self.offsets.last().unwrap(), text_bounds.height());
+                    draw_label(&style, &self.displayed(), line, 0.0, shade(palette.foreground, false, false, disabled));
+                }
+                if self.caret_visible {
+                    let caret = Vec4::xywh(x + self.offsets[self.edit.caret()], text_bounds.top(), CARET_WIDTH, text_bounds.height());
+                    draw_rect(&mut self.rects[3], caret, palette.foreground, 0.0);
+                }
+                context().renderer().stack().pop();
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::Redraw
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+#[test]
+pub fn line_edit() {
+    let mut edit = LineEdit::new("hello world");
+    assert_eq!(edit.caret(), 11);
+    edit.left(true, true);
+    assert_eq!(edit.selected_text(), "world");
+    assert!(edit.insert("there"));
+    assert_eq!(edit.text(), "hello there");
+    edit.backspace(true);
+    assert_eq!(edit.text(), "hello ");
+
+    edit.set_max_length(Some(8));
+    edit.insert("friends");
+    assert_eq!(edit.text(), "hello fr");
+
+    let mut number = LineEdit::new("");
+    number.set_filter(InputFilter::Numeric);
+    assert!(number.insert("-1.5"));
+    assert!(!number.insert("."));
+    assert!(!number.insert("a"));
+    assert_eq!(number.text(), "-1.5");
+
+    let mut hex = LineEdit::new("");
+    hex.set_filter(InputFilter::regex("^[0-9a-f]{0,2}$").unwrap());
+    hex.insert("f");
+    hex.insert("g");
+    hex.insert("0");
+    hex.insert("1");
+    assert_eq!(hex.text(), "f0");
+
+    assert_eq!(index_at(&[0.0, 10.0, 20.0], 14.0), 1);
+    assert_eq!(scroll_to_show(0.0, 150.0, 100.0, 200.0), 50.0);
+    assert_eq!(scroll_to_show(50.0, 20.0, 100.0, 200.0), 20.0);
+    // Deleting text scrolls back so that no space is left after the end
+    assert_eq!(scroll_to_show(50.0, 110.0, 100.0, 110.0), 10.0);
+}
@@ -0,0 +1,170 @@
+use crate::components::framework::animation::{Animated, AnimationRegistry, Easing, Interpolate};
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::{draw_label, draw_rect, inset, measure, shade, Interaction, Palette, ANIMATION_SPEED};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+const TRACK_SIZE: Vec2<f32> = Vec2 { x: 36.0, y: 20.0 };
+const KNOB_INSET: f32 = 2.0;
+const LABEL_GAP: f32 = 8.0;
+
+/// An on/off switch whose knob slides between the two sides, styled as `Toggle`
+pub struct Toggle {
+    element: Element,
+    interaction: Interaction,
+    label: String,
+    on: bool,
+    changed: bool,
+
+    knob: Animated<f32>,
+    track: Option<Rect>,
+    knob_rect: Option<Rect>,
+
+    on_change: Option<Box<dyn FnMut(bool)>>,
+}
+
+impl Toggle {
+    pub fn new(label: impl ToString, on: bool) -> Self {
+        let knob = Animated::new(if on { 1.0 } else { 0.0 });
+        Toggle {
+            element: ElementBuilder::new()
+                .style_type("Toggle")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(TRACK_SIZE.x, 24.0),
+                    ..LayoutContext::new()
+                })
+                .register_animations(vec![knob.animation()])
+                .build(),
+            interaction: Interaction::new(),
+            label: label.to_string(),
+            on,
+            changed: true,
+            knob,
+            track: None,
+            knob_rect: None,
+            on_change: None,
+        }
+    }
+
+    /// Called with the new value every time the toggle is switched
+    pub fn on_change<F: FnMut(bool) + 'static>(mut self, on_change: F) -> Self {
+        self.on_change = Some(Box::new(on_change));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.set_disabled(disabled);
+        self
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    /// Sets the value without calling the change callback
+    pub fn set_on(&mut self, on: bool) {
+        self.on = on;
+        self.changed = true;
+    }
+
+    pub fn toggle(&mut self) {
+        self.set_on(!self.on);
+        if let Some(on_change) = &mut self.on_change {
+            on_change(self.on);
+        }
+    }
+
+    pub fn set_label(&mut self, label: impl ToString) {
+        self.label = label.to_string();
+        self.changed = true;
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.element.disabled()
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.element.set_disabled(disabled);
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+}
+
+impl UIHandler for Toggle {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+        if self.interaction.handle(&mut self.element, event) {
+            self.toggle();
+        }
+
+        match event {
+            Event::PreRender => {
+                if self.changed {
+                    let label = measure(self.element.style(), &self.label).x;
+                    self.element.layout_mut().min_size.x = TRACK_SIZE.x + if label > 0.0 { LABEL_GAP + label } else { 0.0 };
+                }
+                self.knob.animate_to(if self.on { 1.0 } else { 0.0 }, ANIMATION_SPEED, Easing::Sin);
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.element.style().clone();
+                let palette = Palette::of(&style);
+                let bounds = self.element.bounds();
+                let (hovering, disabled) = (self.element.hovering(), self.is_disabled());
+                let pressed = self.interaction.is_pressed() && hovering;
+                let knob = self.knob.value();
+
+                let track = Vec4::xywh(bounds.x(), bounds.center_y() - TRACK_SIZE.y / 2.0, TRACK_SIZE.x, TRACK_SIZE.y);
+                let color = shade(palette.border.interpolate(&palette.accent, knob), hovering, pressed, disabled);
+                draw_rect(&mut self.track, track, color, TRACK_SIZE.y / 2.0);
+
+                let size = TRACK_SIZE.y - KNOB_INSET * 2.0;
+                let travel = TRACK_SIZE.x - TRACK_SIZE.y;
+                let knob_bounds = inset(Vec4::xywh(track.x() + travel * knob, track.y(), TRACK_SIZE.y, TRACK_SIZE.y), KNOB_INSET);
+                draw_rect(&mut self.knob_rect, knob_bounds, shade(palette.foreground, false, false, disabled), size / 2.0);
+
+                let label = Vec4::ltrb(track.right() + LABEL_GAP, bounds.top(), bounds.right(), bounds.bottom());
+                draw_label(&style, &self.label, label, 0.0, shade(palette.foreground, false, false, disabled));
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
@@ -0,0 +1,333 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use glfw::MouseButton;
+
+use crate::components::context::context;
+use crate::components::framework::animation::{Animated, AnimationRegistry, Easing};
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::LayoutContext;
+use crate::components::framework::popup::{window_bounds, Placement, Popup, PopupId};
+use crate::components::framework::ui_traits::{random_id, TickResult, UIHandler, UIHandlerRef};
+use crate::components::framework::widgets::{draw_rect, font_name, font_size, Palette, ANIMATION_SPEED};
+use crate::components::render::font::format::{FormatItem, Text};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+const PADDING: Vec2<f32> = Vec2 { x: 8.0, y: 5.0 };
+
+/// Multiplies the alpha of every color in `text` by `opacity`, starting with `color` for the parts before the first one
+fn faded(text: &Text, color: FormatItem, opacity: f32) -> Text {
+    let mut result = Text::new();
+    for item in [color].iter().chain(text.items()) {
+        result.push(match item {
+            FormatItem::Color(color) => FormatItem::Color(color.set_alpha_f32(color.alpha() * opacity)),
+            other => other.clone(),
+        });
+    }
+    result
+}
+
+/// The box a [`Tooltip`] shows its text in, styled as `Tooltip`
+pub struct TooltipBubble {
+    element: Element,
+    text: Text,
+    popup: PopupId,
+    visible: bool,
+    changed: bool,
+
+    opacity: Animated<f32>,
+    rect: Option<Rect>,
+}
+
+impl TooltipBubble {
+    fn new(text: Text) -> Self {
+        let opacity = Animated::new(0.0);
+        TooltipBubble {
+            element: ElementBuilder::new()
+                .style_type("Tooltip")
+                .layout_context(LayoutContext {
+                    min_size: PADDING + PADDING,
+                    ..LayoutContext::new()
+                })
+                .register_animations(vec![opacity.animation()])
+                .build(),
+            text,
+            popup: 0,
+            visible: true,
+            changed: true,
+            opacity,
+            rect: None,
+        }
+    }
+
+    pub fn set_text(&mut self, text: impl Into<Text>) {
+        self.text = text.into();
+        self.changed = true;
+    }
+
+    /// Fades the bubble out, closing its popup once it is invisible
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    /// Fades the bubble back in if it was hiding
+    pub fn show(&mut self) {
+        self.visible = true;
+    }
+
+    /// The prefix every rendered text starts with, before the items of the text itself
+    fn style_items(&self) -> [FormatItem; 2] {
+        let style = self.element.style();
+        [FormatItem::Size(font_size(style)), FormatItem::Color(Palette::of(style).foreground)]
+    }
+}
+
+impl UIHandler for TooltipBubble {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+        match event {
+            Event::PreRender => {
+                self.opacity.animate_to(if self.visible { 1.0 } else { 0.0 }, ANIMATION_SPEED, Easing::Sin);
+                if !self.visible && self.opacity.value() <= 0.0 {
+                    context().framework().close_popup(self.popup);
+                }
+
+                if self.changed {
+                    let [size, _] = self.style_items();
+                    let mut text = Text::new();
+                    text.push(size);
+                    text.append(&self.text);
+                    let size = match context().fonts().font(font_name(self.element.style())) {
+                        None => Vec2::zero(),
+                        Some(font) => font.get_inst(text, (0.0, 0.0), (0.0, 0.0)).1.bounds().wh(),
+                    };
+                    self.element.layout_mut().min_size = size + PADDING + PADDING;
+                }
+            }
+            Event::Render(RenderPass::Main) => {
+                let style = self.element.style().clone();
+                let palette = Palette::of(&style);
+                let bounds = self.element.bounds();
+                let opacity = self.opacity.value();
+
+                let background = palette.background.set_alpha_f32(palette.background.alpha() * opacity);
+                draw_rect(&mut self.rect, bounds, background, style.number("radius").unwrap_or(4.0));
+
+                let [size, color] = self.style_items();
+                let mut text = Text::new();
+                text.push(size);
+                text.append(&faded(&self.text, color, opacity));
+                if let Some(font) = context().fonts().font(font_name(&style)) {
+                    font.draw_string(text, bounds.xy() + PADDING);
+                }
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+/// Shows rich [`Text`] next to an anchor once it has been hovered for a while, fading in and out.
+///
+/// Widgets own one and call [`Tooltip::update()`] on every [`Event::PreRender`] with their element's
+/// [`Element::hovering()`] and bounds, or are wrapped in a [`WithTooltip`]. The bubble is placed
+/// above the anchor by default and flips to the other side when it would leave the window.
+pub struct Tooltip {
+    text: Text,
+    delay: f32,
+    placement: Placement,
+    hovered_for: f32,
+    bubble: Option<(PopupId, Rc<RefCell<TooltipBubble>>)>,
+}
+
+impl Tooltip {
+    pub fn new(text: impl Into<Text>) -> Self {
+        Tooltip {
+            text: text.into(),
+            delay: 0.5,
+            placement: Placement::Above,
+            hovered_for: 0.0,
+            bubble: None,
+        }
+    }
+
+    /// How long the anchor has to be hovered before the tooltip shows, in seconds
+    pub fn delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// The side of the anchor the tooltip prefers, [`Placement::Above`] by default
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    pub fn text(&self) -> &Text {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: impl Into<Text>) {
+        self.text = text.into();
+        if let Some((_, bubble)) = &self.bubble {
+            bubble.borrow_mut().set_text(self.text.clone());
+        }
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.bubble.is_some()
+    }
+
+    /// Counts how long `anchor` has been hovered and shows or hides the tooltip.
+    ///
+    /// `anchor` is relative to the containers currently handling the event, like element bounds.
+    /// Pressing a mouse button hides the tooltip until the anchor is hovered again.
+    pub unsafe fn update(&mut self, hovering: bool, anchor: Vec4) {
+        if self.bubble.as_ref().is_some_and(|(id, _)| !context().framework().is_popup_open(*id)) {
+            self.bubble = None;
+        }
+        let pressed = context().window().mouse().is_pressed(MouseButton::Button1)
+            || context().window().mouse().is_pressed(MouseButton::Button2);
+        if !hovering {
+            self.hovered_for = 0.0;
+        } else if pressed {
+            self.hovered_for = f32::MIN;
+        } else if self.hovered_for >= 0.0 {
+            self.hovered_for += context().framework().pre_delta();
+        }
+
+        let show = self.hovered_for >= self.delay;
+        match (&self.bubble, show) {
+            (Some((_, bubble)), show) => {
+                let mut bubble = bubble.borrow_mut();
+                if show { bubble.show() } else { bubble.hide() }
+            }
+            (None, true) => {
+                let mut bubble = TooltipBubble::new(self.text.clone());
+                bubble.popup = random_id();
+                let id = bubble.popup;
+                let bubble = Rc::new(RefCell::new(bubble));
+                let popup = Popup::new(UIHandlerRef::from(bubble.clone()), window_bounds(anchor))
+                    .with_id(id)
+                    .placement(self.placement)
+                    .align(0.5)
+                    .gap(6.0)
+                    // Tooltips never take input away from what is under them
+                    .close_on_outside(false)
+                    .close_on_escape(false)
+                    .grab_keyboard(false);
+                context().framework().open_popup(popup);
+                self.bubble = Some((id, bubble));
+            }
+            (None, false) => {}
+        }
+    }
+
+    /// Hides the tooltip right away, without fading
+    pub unsafe fn close(&mut self) {
+        if let Some((id, _)) = self.bubble.take() {
+            context().framework().close_popup(id);
+        }
+        self.hovered_for = 0.0;
+    }
+}
+
+/// Shows a [`Tooltip`] while its child is hovered
+pub struct WithTooltip<H: UIHandler> {
+    child: H,
+    tooltip: Tooltip,
+}
+
+impl<H: UIHandler> WithTooltip<H> {
+    pub fn new(child: H, tooltip: Tooltip) -> Self {
+        WithTooltip {
+            child,
+            tooltip,
+        }
+    }
+
+    pub fn child(&mut self) -> &mut H {
+        &mut self.child
+    }
+
+    pub fn tooltip(&mut self) -> &mut Tooltip {
+        &mut self.tooltip
+    }
+}
+
+impl<H: UIHandler> UIHandler for WithTooltip<H> {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.child.handle(event);
+        if let Event::PreRender = event {
+            // The same check as `Element::hovering`, for children that are not elements
+            let bounds = self.child.bounds();
+            let hovering = context().window().mouse().hovers(&bounds);
+            self.tooltip.update(hovering, bounds);
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        self.child.tick(render_pass)
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.child.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.child.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.child.layout_context()
+    }
+}
+
+#[test]
+pub fn fading() {
+    use crate::components::render::color::Color;
+
+    let mut text = Text::new();
+    text.push("plain ".into());
+    text.push(Color::from_f32(1.0, 0.0, 0.0, 0.5).into());
+    text.push("red".into());
+    let faded = faded(&text, FormatItem::Color(Color::from_f32(1.0, 1.0, 1.0, 1.0)), 0.5);
+
+    let alphas: Vec<f32> = faded.items().iter().filter_map(|item| match item {
+        FormatItem::Color(color) => Some(color.alpha()),
+        _ => None,
+    }).collect();
+    assert_eq!(alphas, vec![0.5, 0.25]);
+    assert_eq!(faded.visible_length(), text.visible_length());
+}
@@ -0,0 +1,754 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use glfw::{Action, Key, MouseButton};
+
+use crate::components::context::context;
+use crate::components::framework::animation::{Animated, Animation, AnimationRegistry, Easing};
+use crate::components::framework::element::{Element, ElementBuilder};
+use crate::components::framework::event::{Event, EventResult, RenderPass};
+use crate::components::framework::layout::{LayoutContext, Sizing};
+use crate::components::framework::ui_traits::{TickResult, UIHandler};
+use crate::components::framework::widgets::{draw_label, draw_rect, shade, Interaction, Palette};
+use crate::components::render::renderer::shapes::Rect;
+use crate::components::render::stack::State;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// How long expanding or collapsing a node takes, in seconds
+const EXPAND_DURATION: f32 = 0.15;
+/// How long after the last typed character a new search starts, in seconds
+const SEARCH_TIMEOUT: f32 = 1.0;
+const ARROW_WIDTH: f32 = 16.0;
+
+/// The hierarchy a [`TreeView`] shows, queried only for the nodes that are visible.
+///
+/// Whether a node has children is asked the first time it is shown, and its children the first time
+/// it is expanded. Both are cached until [`TreeView::refresh()`] is called, so they can be loaded lazily.
+pub trait TreeData {
+    /// Identifies a node, kept by the tree to remember expanded and selected nodes
+    type Id: Clone + Eq + Hash;
+
+    fn roots(&mut self) -> Vec<Self::Id>;
+
+    /// If the node can be expanded, called before its children are loaded
+    fn has_children(&mut self, id: &Self::Id) -> bool;
+
+    /// The children of an expanded node, or `None` while they are still loading.
+    /// The tree shows a loading row and asks again every frame until they are there.
+    fn children(&mut self, id: &Self::Id) -> Option<Vec<Self::Id>>;
+
+    fn label(&mut self, id: &Self::Id) -> String;
+}
+
+/// How many nodes of a [`TreeView`] can be selected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionMode {
+    Single,
+    /// Control toggles nodes and Shift selects ranges, with clicks and the arrow keys
+    Multiple,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RowKind {
+    Node,
+    /// Shown below a node while its children are loading
+    Loading,
+}
+
+/// A node currently shown in the tree, in the order they are drawn
+#[derive(Debug, Clone)]
+struct Row<Id> {
+    id: Id,
+    kind: RowKind,
+    depth: usize,
+    expandable: bool,
+    parent: Option<usize>,
+    /// The part of the row's height shown while an ancestor expands or collapses
+    scale: f32,
+}
+
+/// Which nodes are expanded and their loaded children
+struct ExpandState<Id> {
+    expanded: HashSet<Id>,
+    /// Nodes expanding or collapsing, with their progress from collapsed at 0 to expanded at 1
+    transitions: HashMap<Id, Animation>,
+    children: HashMap<Id, Vec<Id>>,
+    /// If the shown nodes have children, as answered by the data
+    expandable: HashMap<Id, bool>,
+}
+
+impl<Id: Clone + Eq + Hash> ExpandState<Id> {
+    fn new() -> Self {
+        ExpandState {
+            expanded: HashSet::new(),
+            transitions: HashMap::new(),
+            children: HashMap::new(),
+            expandable: HashMap::new(),
+        }
+    }
+
+    fn progress(&self, id: &Id) -> f32 {
+        self.transitions.get(id).map_or(1.0, |a| a.value())
+    }
+
+    /// Children are shown while expanded and while collapsing
+    fn shows_children(&self, id: &Id) -> bool {
+        self.expanded.contains(id) || self.transitions.contains_key(id)
+    }
+}
+
+/// A node to list, with its depth, parent row and scale
+type Pending<Id> = (Id, usize, Option<usize>, f32);
+
+/// Lists the rows of every root and of the children of shown nodes, depth first
+fn flatten<D: TreeData>(data: &mut D, state: &mut ExpandState<D::Id>) -> Vec<Row<D::Id>> {
+    let roots = data.roots().into_iter().rev().map(|id| (id, 0, None, 1.0)).collect();
+    flatten_from(data, state, roots, 0)
+}
+
+/// Lists the rows of the nodes on `stack` and the children of shown nodes, depth first,
+/// numbered from `first` so parent rows point at where the rows end up
+fn flatten_from<D: TreeData>(data: &mut D, state: &mut ExpandState<D::Id>, mut stack: Vec<Pending<D::Id>>, first: usize) -> Vec<Row<D::Id>> {
+    let mut rows = Vec::new();
+    while let Some((id, depth, parent, scale)) = stack.pop() {
+        let expandable = match state.expandable.get(&id) {
+            Some(expandable) => *expandable,
+            None => {
+                let expandable = data.has_children(&id);
+                state.expandable.insert(id.clone(), expandable);
+                expandable
+            }
+        };
+        let index = first + rows.len();
+        rows.push(Row { id: id.clone(), kind: RowKind::Node, depth, expandable, parent, scale });
+        if !expandable || !state.shows_children(&id) {
+            continue;
+        }
+        let scale = scale * state.progress(&id);
+        let children = match state.children.get(&id) {
+            Some(children) => Some(children.clone()),
+            None => data.children(&id).inspect(|children| {
+                state.children.insert(id.clone(), children.clone());
+            }),
+        };
+        match children {
+            None => rows.push(Row { id, kind: RowKind::Loading, depth: depth + 1, expandable: false, parent: Some(index), scale }),
+            Some(children) => stack.extend(children.into_iter().rev().map(|child| (child, depth + 1, Some(index), scale))),
+        }
+    }
+    rows
+}
+
+/// Lists the node at row `index` and its descendants again, replacing their old rows
+fn update_subtree<D: TreeData>(data: &mut D, state: &mut ExpandState<D::Id>, rows: &mut Vec<Row<D::Id>>, index: usize) {
+    let row = &rows[index];
+    let end = rows[index + 1..].iter().position(|r| r.depth <= row.depth).map_or(rows.len(), |i| index + 1 + i);
+    let subtree = flatten_from(data, state, vec![(row.id.clone(), row.depth, row.parent, row.scale)], index);
+    let new_end = index + subtree.len();
+    rows.splice(index..end, subtree);
+    // Rows after the subtree moved, and so did their parents that come after it
+    for row in &mut rows[new_end..] {
+        if let Some(parent) = &mut row.parent {
+            if *parent >= end {
+                *parent = *parent + new_end - end;
+            }
+        }
+    }
+}
+
+/// The first index from `start`, wrapping around after `count`, whose label starts with `query`, ignoring case.
+///
+/// Labels are only asked for until one matches, rows without one are skipped.
+pub fn find_prefix(count: usize, start: usize, query: &str, mut label: impl FnMut(usize) -> Option<String>) -> Option<usize> {
+    let query = query.to_lowercase();
+    (0..count)
+        .map(|i| (start + i) % count)
+        .find(|i| label(*i).is_some_and(|label| label.to_lowercase().starts_with(&query)))
+}
+
+type SelectCallback<Id> = Box<dyn FnMut(&[Id])>;
+type ActivateCallback<Id> = Box<dyn FnMut(&Id)>;
+
+/// Hierarchical data, such as files or documents, shown as rows that can be expanded, styled as `TreeView`.
+///
+/// Only the visible rows are drawn and only the children of expanded nodes are loaded, so trees
+/// with many nodes stay fast. Clicking the arrow of a node or pressing Left and Right expands and
+/// collapses it, Up and Down move the selection and typing jumps to the next node whose label
+/// starts with what was typed.
+pub struct TreeView<D: TreeData> {
+    element: Element,
+    interaction: Interaction,
+    data: D,
+    state: ExpandState<D::Id>,
+    rows: Vec<Row<D::Id>>,
+    /// The top of every row, plus the total height as the last value
+    offsets: Vec<f32>,
+    /// If every row has to be listed again, otherwise only the subtrees of `outdated` nodes are
+    dirty: bool,
+    outdated: HashSet<D::Id>,
+    changed: bool,
+
+    mode: SelectionMode,
+    selected: Vec<D::Id>,
+    cursor: Option<D::Id>,
+    anchor: Option<D::Id>,
+
+    row_height: f32,
+    indent: f32,
+    scroll: Animated<f32>,
+    target_scroll: f32,
+    search: String,
+    since_typed: f32,
+
+    rects: Vec<Option<Rect>>,
+    on_select: Option<SelectCallback<D::Id>>,
+    on_activate: Option<ActivateCallback<D::Id>>,
+}
+
+impl<D: TreeData> TreeView<D> {
+    pub fn new(data: D) -> Self {
+        let scroll = Animated::new(0.0);
+        TreeView {
+            element: ElementBuilder::new()
+                .style_type("TreeView")
+                .layout_context(LayoutContext {
+                    min_size: Vec2::new(100.0, 72.0),
+                    size_behavior: (Sizing::Grow, Sizing::Grow),
+                    ..LayoutContext::new()
+                })
+                .register_animations(vec![scroll.animation()])
+                .build(),
+            interaction: Interaction::new(),
+            data,
+            state: ExpandState::new(),
+            rows: Vec::new(),
+            offsets: vec![0.0],
+            dirty: true,
+            outdated: HashSet::new(),
+            changed: true,
+            mode: SelectionMode::Single,
+            selected: Vec::new(),
+            cursor: None,
+            anchor: None,
+            row_height: 24.0,
+            indent: 16.0,
+            scroll,
+            target_scroll: 0.0,
+            search: String::new(),
+            since_typed: 0.0,
+            rects: Vec::new(),
+            on_select: None,
+            on_activate: None,
+        }
+    }
+
+    pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The height of every row, `24` by default
+    pub fn row_height(mut self, row_height: f32) -> Self {
+        self.row_height = row_height;
+        self
+    }
+
+    /// How far each level is indented, `16` by default
+    pub fn indent(mut self, indent: f32) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Called with the selected nodes every time the user changes the selection
+    pub fn on_select<F: FnMut(&[D::Id]) + 'static>(mut self, on_select: F) -> Self {
+        self.on_select = Some(Box::new(on_select));
+        self
+    }
+
+    /// Called with the node under the cursor when Enter is pressed
+    pub fn on_activate<F: FnMut(&D::Id) + 'static>(mut self, on_activate: F) -> Self {
+        self.on_activate = Some(Box::new(on_activate));
+        self
+    }
+
+    pub fn class(mut self, class: impl ToString) -> Self {
+        self.element.add_class(class);
+        self
+    }
+
+    pub fn data(&mut self) -> &mut D {
+        &mut self.data
+    }
+
+    /// Forgets the loaded children of `id`, or of every node when `None`, asking the data again
+    pub fn refresh(&mut self, id: Option<&D::Id>) {
+        match id {
+            Some(id) => {
+                self.state.children.remove(id);
+                self.state.expandable.remove(id);
+                self.outdated.insert(id.clone());
+            }
+            None => {
+                self.state.children.clear();
+                self.state.expandable.clear();
+                self.dirty = true;
+            }
+        }
+    }
+
+    pub fn is_expanded(&self, id: &D::Id) -> bool {
+        self.state.expanded.contains(id)
+    }
+
+    pub fn expand(&mut self, id: &D::Id) {
+        if self.state.expanded.insert(id.clone()) {
+            self.animate(id, 1.0);
+        }
+    }
+
+    pub fn collapse(&mut self, id: &D::Id) {
+        if self.state.expanded.remove(id) {
+            self.animate(id, 0.0);
+        }
+    }
+
+    pub fn toggle(&mut self, id: &D::Id) {
+        if self.is_expanded(id) {
+            self.collapse(id);
+        } else {
+            self.expand(id);
+        }
+    }
+
+    pub fn selected(&self) -> &[D::Id] {
+        &self.selected
+    }
+
+    /// Selects nodes without calling the select callback, moving the cursor to the last one
+    pub fn set_selected(&mut self, selected: Vec<D::Id>) {
+        self.cursor = selected.last().cloned();
+        self.anchor = self.cursor.clone();
+        self.selected = selected;
+        self.changed = true;
+    }
+
+    /// The node the keyboard moves from
+    pub fn cursor(&self) -> Option<&D::Id> {
+        self.cursor.as_ref()
+    }
+
+    pub fn element(&mut self) -> &mut Element {
+        &mut self.element
+    }
+
+    fn animate(&mut self, id: &D::Id, to: f32) {
+        let from = self.state.progress(id);
+        let from = if self.state.transitions.contains_key(id) { from } else { 1.0 - to };
+        self.state.transitions.insert(id.clone(), Animation::timed(from, to, EXPAND_DURATION * (to - from).abs(), Easing::EASE_IN_OUT));
+        self.outdated.insert(id.clone());
+    }
+
+    fn update_rows(&mut self) {
+        self.rows = flatten(&mut self.data, &mut self.state);
+        self.dirty = false;
+        self.outdated.clear();
+        self.update_offsets();
+    }
+
+    /// Lists the rows below the outdated nodes again, parents first as they include their children
+    fn update_outdated(&mut self) {
+        let mut outdated: Vec<(usize, D::Id)> = std::mem::take(&mut self.outdated).into_iter()
+            .filter_map(|id| self.row_of(&id).map(|index| (self.rows[index].depth, id)))
+            .collect();
+        outdated.sort_by_key(|(depth, _)| *depth);
+        for (_, id) in outdated {
+            if let Some(index) = self.row_of(&id) {
+                update_subtree(&mut self.data, &mut self.state, &mut self.rows, index);
+            }
+        }
+        self.update_offsets();
+    }
+
+    fn update_offsets(&mut self) {
+        self.offsets.clear();
+        let mut total = 0.0;
+        for row in &self.rows {
+            self.offsets.push(total);
+            total += self.row_height * row.scale;
+        }
+        self.offsets.push(total);
+        self.target_scroll = self.clamp_scroll(self.target_scroll);
+        self.changed = true;
+    }
+
+    fn content_height(&self) -> f32 {
+        *self.offsets.last().unwrap()
+    }
+
+    fn clamp_scroll(&self, scroll: f32) -> f32 {
+        scroll.min(self.content_height() - self.element.bounds().height()).max(0.0)
+    }
+
+    fn row_of(&self, id: &D::Id) -> Option<usize> {
+        self.rows.iter().position(|r| r.kind == RowKind::Node && r.id == *id)
+    }
+
+    /// The row under `y`, relative to the top of the content
+    fn row_at(&self, y: f32) -> Option<usize> {
+        let index = self.offsets[1..].partition_point(|bottom| *bottom <= y);
+        (index < self.rows.len()).then_some(index)
+    }
+
+    /// Scrolls as little as possible to show the whole row
+    fn scroll_to(&mut self, index: usize) {
+        let (top, bottom) = (self.offsets[index], self.offsets[index] + self.row_height);
+        let height = self.element.bounds().height();
+        if top < self.target_scroll {
+            self.target_scroll = top;
+        } else if bottom > self.target_scroll + height {
+            self.target_scroll = bottom - height;
+        }
+        self.target_scroll = self.clamp_scroll(self.target_scroll);
+    }
+
+    fn notify(&mut self) {
+        self.changed = true;
+        if let Some(on_select) = &mut self.on_select {
+            on_select(&self.selected);
+        }
+    }
+
+    /// Selects the node of a row like clicking it, where Control toggles and Shift extends in multiple selection
+    fn select_row(&mut self, index: usize, toggle: bool, extend: bool) {
+        let Some(row) = self.rows.get(index).filter(|r| r.kind == RowKind::Node) else {
+            return;
+        };
+        let id = row.id.clone();
+        let anchor = self.anchor.as_ref().and_then(|a| self.row_of(a));
+        match (self.mode, anchor) {
+            (SelectionMode::Multiple, Some(anchor)) if extend => {
+                let (from, to) = (anchor.min(index), anchor.max(index));
+                self.selected = self.rows[from..=to].iter().filter(|r| r.kind == RowKind::Node).map(|r| r.id.clone()).collect();
+            }
+            (SelectionMode::Multiple, _) if toggle => {
+                match self.selected.iter().position(|s| *s == id) {
+                    Some(i) => { self.selected.remove(i); }
+                    None => self.selected.push(id.clone()),
+                }
+                self.anchor = Some(id.clone());
+            }
+            _ => {
+                self.selected = vec![id.clone()];
+                self.anchor = Some(id.clone());
+            }
+        }
+        self.cursor = Some(id);
+        self.scroll_to(index);
+        self.notify();
+    }
+
+    /// Moves the cursor to another row, selecting it unless only the cursor moves
+    fn move_cursor(&mut self, index: usize, shift: bool, ctrl: bool) {
+        let index = index.min(self.rows.len().saturating_sub(1));
+        if ctrl && self.mode == SelectionMode::Multiple {
+            if let Some(row) = self.rows.get(index) {
+                self.cursor = Some(row.id.clone());
+                self.scroll_to(index);
+                self.changed = true;
+            }
+        } else {
+            self.select_row(index, false, shift);
+        }
+    }
+
+    unsafe fn key(&mut self, key: Key) {
+        let keyboard = context().keyboard();
+        let (shift, ctrl) = (keyboard.shift(), keyboard.ctrl());
+        let cursor = self.cursor.as_ref().and_then(|c| self.row_of(c));
+        let page = (self.element.bounds().height() / self.row_height).floor().max(1.0) as usize;
+        let last = self.rows.len().saturating_sub(1);
+        match (key, cursor) {
+            (Key::Up, Some(c)) => self.move_cursor(c.saturating_sub(1), shift, ctrl),
+            (Key::Down, Some(c)) => self.move_cursor(c + 1, shift, ctrl),
+            (Key::PageUp, Some(c)) => self.move_cursor(c.saturating_sub(page), shift, ctrl),
+            (Key::PageDown, Some(c)) => self.move_cursor(c + page, shift, ctrl),
+            (Key::Up | Key::Down | Key::Home, _) => self.move_cursor(0, shift, ctrl),
+            (Key::End, _) => self.move_cursor(last, shift, ctrl),
+            (Key::Right, Some(c)) => {
+                let row = &self.rows[c];
+                if row.expandable && !self.is_expanded(&row.id) {
+                    let id = row.id.clone();
+                    self.expand(&id);
+                } else if self.rows.get(c + 1).is_some_and(|r| r.parent == Some(c) && r.kind == RowKind::Node) {
+                    self.move_cursor(c + 1, false, false);
+                }
+            }
+            (Key::Left, Some(c)) => {
+                let row = &self.rows[c];
+                if row.expandable && self.is_expanded(&row.id) {
+                    let id = row.id.clone();
+                    self.collapse(&id);
+                } else if let Some(parent) = row.parent {
+                    self.move_cursor(parent, false, false);
+                }
+            }
+            (Key::Space, Some(c)) => self.select_row(c, true, false),
+            (Key::Enter, Some(c)) => {
+                let id = self.rows[c].id.clone();
+                if let Some(on_activate) = &mut self.on_activate {
+                    on_activate(&id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds to the search and jumps to the next node starting with it
+    fn type_char(&mut self, c: char) {
+        if self.since_typed > SEARCH_TIMEOUT {
+            self.search.clear();
+        }
+        self.since_typed = 0.0;
+        self.search.push(c);
+
+        let cursor = self.cursor.as_ref().and_then(|c| self.row_of(c));
+        // A new search moves past the current node, a longer one can stay on it
+        let start = match cursor {
+            Some(c) if self.search.chars().count() == 1 => c + 1,
+            Some(c) => c,
+            None => 0,
+        };
+        let (rows, data) = (&self.rows, &mut self.data);
+        let label = |i: usize| (rows[i].kind == RowKind::Node).then(|| data.label(&rows[i].id));
+        if let Some(index) = find_prefix(rows.len(), start, &self.search, label) {
+            self.select_row(index, false, false);
+        }
+    }
+
+    unsafe fn render_rows(&mut self) {
+        let style = self.element.style().clone();
+        let palette = Palette::of(&style);
+        let bounds = self.element.bounds();
+        let scroll = self.scroll.value();
+        let mouse = *context().window().mouse().pos();
+        let focused = self.element.focused();
+
+        let first = self.offsets[1..].partition_point(|bottom| *bottom <= scroll);
+        let last = self.offsets[..self.rows.len()].partition_point(|top| *top < scroll + bounds.height());
+        let count = last.saturating_sub(first);
+        if self.rects.len() < count * 2 {
+            self.rects.resize_with(count * 2, || None);
+        }
+
+        context().renderer().stack().push(State::Clip(bounds));
+        for (slot, index) in (first..last).enumerate() {
+            let row = &self.rows[index];
+            let height = self.offsets[index + 1] - self.offsets[index];
+            if height < 0.5 {
+                continue;
+            }
+            let area = Vec4::xywh(bounds.x(), bounds.y() + self.offsets[index] - scroll, bounds.width(), height);
+            let selected = row.kind == RowKind::Node && self.selected.contains(&row.id);
+            let under_cursor = self.cursor.as_ref() == Some(&row.id) && row.kind == RowKind::Node;
+
+            if selected {
+                let color = if focused { palette.accent } else { palette.accent.set_alpha_f32(palette.accent.alpha() * 0.5) };
+                draw_rect(&mut self.rects[slot * 2], area, color, 0.0);
+            } else if mouse.intersects(&area) && self.element.hovering() {
+                draw_rect(&mut self.rects[slot * 2], area, shade(palette.background, true, false, false), 0.0);
+            }
+            if under_cursor && focused && self.mode == SelectionMode::Multiple {
+                let marker = Vec4::xywh(area.x(), area.y(), 2.0, area.height());
+                draw_rect(&mut self.rects[slot * 2 + 1], marker, palette.foreground, 0.0);
+            }
+
+            let x = area.x() + 4.0 + row.depth as f32 * self.indent;
+            // Rows fade in and out with the expanding node they belong to
+            let color = palette.foreground.set_alpha_f32(palette.foreground.alpha() * row.scale);
+            match row.kind {
+                RowKind::Node => {
+                    if row.expandable {
+                        let arrow = if self.state.expanded.contains(&row.id) { "v" } else { ">" };
+                        draw_label(&style, arrow, Vec4::xywh(x, area.y(), ARROW_WIDTH, area.height()), 0.5, color);
+                    }
+                    let label = self.data.label(&self.rows[index].id);
+                    let text = Vec4::ltrb(x + ARROW_WIDTH + 4.0, area.top(), area.right(), area.bottom());
+                    draw_label(&style, &label, text, 0.0, color);
+                }
+                RowKind::Loading => {
+                    let text = Vec4::ltrb(x + ARROW_WIDTH + 4.0, area.top(), area.right(), area.bottom());
+                    draw_label(&style, "Loading...", text, 0.0, shade(color, false, false, true));
+                }
+            }
+        }
+        context().renderer().stack().pop();
+    }
+}
+
+impl<D: TreeData> UIHandler for TreeView<D> {
+    unsafe fn handle(&mut self, event: &Event) -> EventResult {
+        let result = self.element.handle(event);
+        self.interaction.handle(&mut self.element, event);
+
+        match event {
+            Event::PreRender => {
+                let delta = context().framework().pre_delta();
+                self.since_typed += delta;
+                // Expanding and collapsing nodes scale their rows, until and including the last frame
+                for (id, animation) in &mut self.state.transitions {
+                    animation.advance(delta);
+                    self.outdated.insert(id.clone());
+                }
+                self.state.transitions.retain(|_, a| !a.is_finished());
+                // Loading rows ask for their children again until they arrive
+                let loading = self.rows.iter().filter(|r| r.kind == RowKind::Loading).map(|r| r.id.clone());
+                self.outdated.extend(loading);
+                if self.dirty {
+                    self.update_rows();
+                } else if !self.outdated.is_empty() {
+                    self.update_outdated();
+                }
+                self.scroll.animate_to(self.target_scroll, 15.0, Easing::Sin);
+            }
+            Event::Scroll(_, y) if self.element.hovering() => {
+                self.target_scroll = self.clamp_scroll(self.target_scroll - *y * self.row_height * 2.0);
+                self.changed = true;
+                return EventResult::Used;
+            }
+            Event::MouseClick(MouseButton::Button1, Action::Press) if self.element.hovering() && self.element.active => {
+                let bounds = self.element.bounds();
+                let mouse = *context().window().mouse().pos();
+                if let Some(index) = self.row_at(mouse.y - bounds.y() + self.scroll.value()) {
+                    let row = &self.rows[index];
+                    let arrow = bounds.x() + 4.0 + row.depth as f32 * self.indent;
+                    if row.expandable && mouse.x >= arrow && mouse.x < arrow + ARROW_WIDTH {
+                        let id = row.id.clone();
+                        self.toggle(&id);
+                    } else {
+                        let keyboard = context().keyboard();
+                        let (ctrl, shift) = (keyboard.ctrl(), keyboard.shift());
+                        self.select_row(index, ctrl, shift);
+                    }
+                }
+                return EventResult::Used;
+            }
+            Event::Keyboard(key, Action::Press | Action::Repeat, _) if self.element.focused() && self.element.active => {
+                self.key(*key);
+            }
+            Event::Char(c) if self.element.focused() && self.element.active => {
+                self.type_char(*c);
+            }
+            Event::Render(RenderPass::Main) => {
+                self.render_rows();
+            }
+            Event::PostRender => {
+                self.changed = false;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    unsafe fn tick(&mut self, render_pass: &RenderPass) -> TickResult {
+        let result = self.element.tick(render_pass);
+        if !result.is_valid() {
+            return result;
+        }
+        if self.changed || self.dirty || !self.outdated.is_empty() || !self.state.transitions.is_empty() {
+            TickResult::RedrawLayout
+        } else {
+            TickResult::Valid
+        }
+    }
+
+    fn animations(&mut self) -> Option<AnimationRegistry> {
+        self.element.animations()
+    }
+
+    fn bounds(&self) -> Vec4 {
+        self.element.bounds()
+    }
+
+    fn layout_context(&self) -> LayoutContext {
+        self.element.layout_context()
+    }
+}
+
+#[test]
+pub fn flattening() {
+    /// Node `n` has the children `n * 10 + 1` and `n * 10 + 2` while below 100, 2 is still loading
+    struct Numbers;
+    impl TreeData for Numbers {
+        type Id = u32;
+        fn roots(&mut self) -> Vec<u32> {
+            vec![1, 2]
+        }
+        fn has_children(&mut self, id: &u32) -> bool {
+            *id < 100
+        }
+        fn children(&mut self, id: &u32) -> Option<Vec<u32>> {
+            if *id == 2 { None } else { Some(vec![id * 10 + 1, id * 10 + 2]) }
+        }
+        fn label(&mut self, id: &u32) -> String {
+            id.to_string()
+        }
+    }
+
+    let mut state = ExpandState::new();
+    let ids = |rows: &[Row<u32>]| rows.iter().map(|r| (r.id, r.depth, r.kind)).collect::<Vec<_>>();
+    assert_eq!(ids(&flatten(&mut Numbers, &mut state)), vec![(1, 0, RowKind::Node), (2, 0, RowKind::Node)]);
+
+    state.expanded.extend([1, 12, 2]);
+    let rows = flatten(&mut Numbers, &mut state);
+    assert_eq!(ids(&rows), vec![
+        (1, 0, RowKind::Node),
+        (11, 1, RowKind::Node),
+        (12, 1, RowKind::Node),
+        (121, 2, RowKind::Node),
+        (122, 2, RowKind::Node),
+        (2, 0, RowKind::Node),
+        (2, 1, RowKind::Loading),
+    ]);
+    assert_eq!(rows[3].parent, Some(2));
+    // Children are only loaded for expanded nodes
+    assert!(state.children.contains_key(&12) && !state.children.contains_key(&11));
+
+    // Collapsing nodes keep their rows, scaled by the progress
+    state.expanded.remove(&1);
+    state.transitions.insert(1, Animation::new(0.0, 0.5));
+    let mut rows = flatten(&mut Numbers, &mut state);
+    assert_eq!(rows[1].scale, 0.5);
+    assert_eq!(rows[5].scale, 1.0);
+
+    // Listing the subtree of a finished transition again gives the same rows as listing everything
+    state.transitions.clear();
+    state.expanded.extend([1, 11]);
+    update_subtree(&mut Numbers, &mut state, &mut rows, 0);
+    let all = flatten(&mut Numbers, &mut state);
+    assert_eq!(ids(&rows), ids(&all));
+    assert_eq!(rows.iter().map(|r| (r.parent, r.scale)).collect::<Vec<_>>(), all.iter().map(|r| (r.parent, r.scale)).collect::<Vec<_>>());
+    state.expanded.remove(&1);
+    update_subtree(&mut Numbers, &mut state, &mut rows, 0);
+    assert_eq!(ids(&rows), vec![(1, 0, RowKind::Node), (2, 0, RowKind::Node), (2, 1, RowKind::Loading)]);
+    assert_eq!(rows[2].parent, Some(1));
+}
+
+#[test]
+pub fn searching() {
+    let labels: Vec<String> = ["src", "Cargo.toml", "cargo.lock", "README.md"].iter().map(|l| l.to_string()).collect();
+    let label = |i: usize| Some(labels[i].clone());
+    assert_eq!(find_prefix(labels.len(), 0, "c", label), Some(1));
+    assert_eq!(find_prefix(labels.len(), 2, "C", label), Some(2));
+    assert_eq!(find_prefix(labels.len(), 3, "s", label), Some(0));
+    assert_eq!(find_prefix(labels.len(), 0, "cargo.l", label), Some(2));
+    assert_eq!(find_prefix(labels.len(), 0, "x", label), None);
+
+    // Labels after the match are never asked for, and rows without one are skipped
+    let mut asked = vec![];
+    let found = find_prefix(labels.len(), 1, "r", |i| {
+        asked.push(i);
+        (i != 1).then(|| labels[i].clone())
+    });
+    assert_eq!((found, asked), (Some(3), vec![1, 2, 3]));
+}
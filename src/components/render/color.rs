@@ -120,8 +120,24 @@ impl Color {
         self.mult_rgb(1.1)
     }
 
+    /// Returns the hue, saturation and value, all from 0 to 1, as used by [`Color::from_hsv()`]
     pub fn hsv_values(&self) -> (f32, f32, f32) {
-        todo!()
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == self.r {
+            ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            (self.b - self.r) / delta + 2.0
+        } else {
+            (self.r - self.g) / delta + 4.0
+        } / 6.0;
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+
+        (hue, saturation, max)
     }
 
     pub unsafe fn apply(&self) {
@@ -0,0 +1,303 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::ops::Deref;
+use std::path::Path;
+use std::rc::Rc;
+
+use gl::{LINEAR, RGBA};
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::{AnimationDecoder, ImageFormat, RgbaImage};
+
+use crate::components::render::color::Color;
+use crate::components::render::svg::Svg;
+use crate::components::wrapper::texture::Texture;
+
+/// Where a cached image came from, hashed into the cache key
+#[derive(Hash)]
+enum Source<'a> {
+    Path(&'a Path),
+    Bytes(&'a [u8]),
+}
+
+impl Source<'_> {
+    fn key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Decodes an encoded PNG, JPEG or GIF into RGBA pixels, taking the first frame of animations
+pub fn decode(bytes: &[u8]) -> Result<RgbaImage, String> {
+    image::load_from_memory(bytes)
+        .map(|image| image.into_rgba8())
+        .map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+/// Frames shown for less than this, in seconds, are slowed down to [`DEFAULT_FRAME_DELAY`] like browsers do
+const MIN_FRAME_DELAY: f32 = 0.02;
+/// How long a frame without a usable delay is shown, in seconds
+const DEFAULT_FRAME_DELAY: f32 = 0.1;
+/// How many rasterized SVGs are kept, the least recently used ones are dropped first
+const MAX_RASTERIZED: usize = 128;
+
+/// A texture owned by everything holding it, deleted once the last [`Rc`] to it is dropped.
+///
+/// Evicting an image from the [`ImageManager`] only drops its handle, so elements still showing it keep a valid texture.
+#[derive(Debug)]
+pub struct SharedTexture(Texture);
+
+impl SharedTexture {
+    /// Takes ownership of `texture`, which must not be deleted elsewhere
+    pub fn new(texture: Texture) -> Rc<Self> {
+        Rc::new(SharedTexture(texture))
+    }
+}
+
+impl Deref for SharedTexture {
+    type Target = Texture;
+
+    fn deref(&self) -> &Texture {
+        &self.0
+    }
+}
+
+impl Drop for SharedTexture {
+    fn drop(&mut self) {
+        if self.0.texture_id != 0 {
+            unsafe { self.0.delete() }
+        }
+    }
+}
+
+/// One frame of an animated image
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub texture: Rc<SharedTexture>,
+    /// How long the frame is shown, in seconds
+    pub delay: f32,
+}
+
+/// Decodes every frame of an animated GIF or APNG with how long it is shown, in seconds.
+///
+/// Frames are composited onto the full canvas. Any other image decodes into a single frame.
+pub fn decode_frames(bytes: &[u8]) -> Result<Vec<(RgbaImage, f32)>, String> {
+    let error = |e: image::ImageError| format!("Failed to decode image: {}", e);
+    let frames = match image::guess_format(bytes).map_err(error)? {
+        ImageFormat::Gif => GifDecoder::new(Cursor::new(bytes)).map_err(error)?.into_frames(),
+        ImageFormat::Png => {
+            let decoder = PngDecoder::new(Cursor::new(bytes)).map_err(error)?;
+            if !decoder.is_apng().map_err(error)? {
+                return Ok(vec![(decode(bytes)?, 0.0)]);
+            }
+            decoder.apng().map_err(error)?.into_frames()
+        }
+        _ => return Ok(vec![(decode(bytes)?, 0.0)]),
+    };
+
+    let frames = frames.collect_frames().map_err(error)?;
+    Ok(frames.into_iter().map(|frame| {
+        let (numerator, denominator) = frame.delay().numer_denom_ms();
+        let delay = numerator as f32 / denominator.max(1) as f32 / 1000.0;
+        let delay = if delay < MIN_FRAME_DELAY { DEFAULT_FRAME_DELAY } else { delay };
+        (frame.into_buffer(), delay)
+    }).collect())
+}
+
+/// Uploads decoded pixels into a linearly filtered RGBA texture
+pub unsafe fn upload(image: &RgbaImage) -> Texture {
+    Texture::create(image.width() as i32, image.height() as i32, image.as_raw(), RGBA, LINEAR)
+}
+
+/// Identifies a rasterized SVG by the key of the SVG, its size and if it is a mask
+type RasterKey = (u64, u32, u32, bool);
+
+/// Loads images into textures, decoding every file or byte buffer only once.
+///
+/// Textures are shared by everything that loads the same source. They stay cached until
+/// [`ImageManager::evict`] or [`ImageManager::clear`] is called, and are deleted once nothing holds them anymore.
+/// SVGs are parsed once and rasterized once for every size they are drawn at.
+pub struct ImageManager {
+    textures: HashMap<u64, Rc<SharedTexture>>,
+    animations: HashMap<u64, Vec<Frame>>,
+    svgs: HashMap<u64, Rc<Svg>>,
+    /// Rasterized SVGs with when they were last used
+    rasterized: HashMap<RasterKey, (Rc<SharedTexture>, u64)>,
+    /// Counts the requests for rasterized SVGs, ordering their last uses
+    rasterize_count: u64,
+}
+
+impl ImageManager {
+    pub fn new() -> Self {
+        ImageManager {
+            textures: HashMap::new(),
+            animations: HashMap::new(),
+            svgs: HashMap::new(),
+            rasterized: HashMap::new(),
+            rasterize_count: 0,
+        }
+    }
+
+    /// The texture of the image file at `path`, read and decoded the first time it is requested
+    pub unsafe fn load_path(&mut self, path: impl AsRef<Path>) -> Result<Rc<SharedTexture>, String> {
+        let path = path.as_ref();
+        let key = Source::Path(path).key();
+        if let Some(texture) = self.textures.get(&key) {
+            return Ok(texture.clone());
+        }
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read image ({}): {}", path.display(), e))?;
+        let texture = SharedTexture::new(upload(&decode(&bytes)?));
+        self.textures.insert(key, texture.clone());
+        Ok(texture)
+    }
+
+    /// The texture of an encoded image, keyed by a hash of its bytes
+    pub unsafe fn load_bytes(&mut self, bytes: &[u8]) -> Result<Rc<SharedTexture>, String> {
+        let key = Source::Bytes(bytes).key();
+        if let Some(texture) = self.textures.get(&key) {
+            return Ok(texture.clone());
+        }
+        let texture = SharedTexture::new(upload(&decode(bytes)?));
+        self.textures.insert(key, texture.clone());
+        Ok(texture)
+    }
+
+    /// Every frame of the animated image file at `path`, read and decoded the first time it is requested
+    pub unsafe fn load_frames_path(&mut self, path: impl AsRef<Path>) -> Result<Vec<Frame>, String> {
+        let path = path.as_ref();
+        let key = Source::Path(path).key();
+        if let Some(frames) = self.animations.get(&key) {
+            return Ok(frames.clone());
+        }
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read image ({}): {}", path.display(), e))?;
+        let frames = upload_frames(&bytes)?;
+        self.animations.insert(key, frames.clone());
+        Ok(frames)
+    }
+
+    /// Every frame of an encoded animated image, keyed by a hash of its bytes
+    pub unsafe fn load_frames_bytes(&mut self, bytes: &[u8]) -> Result<Vec<Frame>, String> {
+        let key = Source::Bytes(bytes).key();
+        if let Some(frames) = self.animations.get(&key) {
+            return Ok(frames.clone());
+        }
+        let frames = upload_frames(bytes)?;
+        self.animations.insert(key, frames.clone());
+        Ok(frames)
+    }
+
+    /// The SVG file at `path`, read and parsed the first time it is requested
+    pub fn load_svg_path(&mut self, path: impl AsRef<Path>) -> Result<Rc<Svg>, String> {
+        let path = path.as_ref();
+        let key = Source::Path(path).key();
+        if let Some(svg) = self.svgs.get(&key) {
+            return Ok(svg.clone());
+        }
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read SVG ({}): {}", path.display(), e))?;
+        let svg = Rc::new(Svg::parse(&source)?);
+        self.svgs.insert(key, svg.clone());
+        Ok(svg)
+    }
+
+    /// An SVG document, keyed by a hash of its bytes
+    pub fn load_svg_bytes(&mut self, bytes: &[u8]) -> Result<Rc<Svg>, String> {
+        let key = Source::Bytes(bytes).key();
+        if let Some(svg) = self.svgs.get(&key) {
+            return Ok(svg.clone());
+        }
+        let source = std::str::from_utf8(bytes).map_err(|e| format!("Failed to read SVG: {}", e))?;
+        let svg = Rc::new(Svg::parse(source)?);
+        self.svgs.insert(key, svg.clone());
+        Ok(svg)
+    }
+
+    /// The texture of `svg` rasterized at `width` by `height` pixels, rasterized the first time it is requested.
+    ///
+    /// A `mask` is drawn entirely in white with the opacity of every shape, to be tinted when drawn.
+    /// Only the [`MAX_RASTERIZED`] most recently used sizes stay cached.
+    pub unsafe fn svg_texture(&mut self, svg: &Svg, width: u32, height: u32, mask: bool) -> Rc<SharedTexture> {
+        let key = (svg.key(), width.max(1), height.max(1), mask);
+        self.rasterize_count += 1;
+        if let Some((texture, used)) = self.rasterized.get_mut(&key) {
+            *used = self.rasterize_count;
+            return texture.clone();
+        }
+        if self.rasterized.len() >= MAX_RASTERIZED {
+            let oldest = self.rasterized.iter().min_by_key(|(_, (_, used))| *used).map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                self.rasterized.remove(&oldest);
+            }
+        }
+        let white = Color::from_u32(0xffffffff);
+        let image = if mask {
+            svg.mask().rasterize(key.1, key.2, white)
+        } else {
+            svg.rasterize(key.1, key.2, Color::from_u32(0xff000000))
+        };
+        let texture = SharedTexture::new(upload(&image));
+        self.rasterized.insert(key, (texture.clone(), self.rasterize_count));
+        texture
+    }
+
+    /// Drops the cached textures of the image file at `path`, the next load decodes it again.
+    /// The textures are deleted once no element shows them anymore.
+    pub fn evict(&mut self, path: impl AsRef<Path>) {
+        self.remove(Source::Path(path.as_ref()).key());
+    }
+
+    /// Drops the cached textures of an encoded image loaded from bytes
+    pub fn evict_bytes(&mut self, bytes: &[u8]) {
+        self.remove(Source::Bytes(bytes).key());
+    }
+
+    fn remove(&mut self, key: u64) {
+        self.textures.remove(&key);
+        self.animations.remove(&key);
+        // Rasterized textures are keyed by the content of the SVG, which other sources may share
+        if let Some(svg) = self.svgs.remove(&key) {
+            if !self.svgs.values().any(|other| other.key() == svg.key()) {
+                self.rasterized.retain(|k, _| k.0 != svg.key());
+            }
+        }
+    }
+
+    /// Drops every cached texture
+    pub fn clear(&mut self) {
+        self.textures.clear();
+        self.animations.clear();
+        self.rasterized.clear();
+        self.svgs.clear();
+    }
+}
+
+unsafe fn upload_frames(bytes: &[u8]) -> Result<Vec<Frame>, String> {
+    Ok(decode_frames(bytes)?.iter().map(|(image, delay)| Frame {
+        texture: SharedTexture::new(upload(image)),
+        delay: *delay,
+    }).collect())
+}
+
+impl Default for ImageManager {
+    fn default() -> Self {
+        ImageManager::new()
+    }
+}
+
+#[test]
+pub fn decoding() {
+    // A 1x1 red PNG
+    let png = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+        0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00, 0x00, 0x90, 0x77, 0x53,
+        0xde, 0x00, 0x00, 0x00, 0x0c, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xf8, 0xcf, 0xc0, 0x00,
+        0x00, 0x03, 0x01, 0x01, 0x00, 0xf7, 0x03, 0x41, 0x43, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e,
+        0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+    let image = decode(&png).unwrap();
+    assert_eq!(image.dimensions(), (1, 1));
+    assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+    assert!(decode(&[1, 2, 3]).is_err());
+}
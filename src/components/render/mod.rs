@@ -3,3 +3,6 @@ pub mod font;
 pub mod mask;
 pub mod color;
 pub mod stack;
+pub mod image;
+pub mod path;
+pub mod svg;
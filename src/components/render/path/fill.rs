@@ -0,0 +1,212 @@
+use crate::components::render::path::{cross, length, normalize, perp, scale, Mesh, Polyline};
+use crate::components::spatial::vec2::Vec2;
+
+/// Which parts of overlapping sub-paths are inside a fill
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FillRule {
+    /// Inside wherever the sub-paths wind around a point at all, so holes need the opposite direction
+    #[default]
+    NonZero,
+    /// Inside wherever a point is enclosed an odd number of times, so any overlap is a hole
+    EvenOdd,
+}
+
+impl FillRule {
+    fn inside(&self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// A non-horizontal edge going down from `top` to `bottom`, `winding` is 1 if the outline goes down along it
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    top: Vec2<f32>,
+    bottom: Vec2<f32>,
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        self.top.x + (self.bottom.x - self.top.x) * t
+    }
+}
+
+/// The y of the point where two edges cross, if they do
+fn crossing(a: &Edge, b: &Edge) -> Option<f32> {
+    let (p, r) = (a.top, a.bottom - a.top);
+    let (q, s) = (b.top, b.bottom - b.top);
+    let denominator = cross(r, s);
+    if denominator.abs() < 1e-9 {
+        return None;
+    }
+    let t = cross(q - p, s) / denominator;
+    let u = cross(q - p, r) / denominator;
+    if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+        Some(p.y + r.y * t)
+    } else {
+        None
+    }
+}
+
+/// The geometry of the area enclosed by `lines`, each closed implicitly.
+///
+/// The area is swept from top to bottom in bands between the vertices, keeping the edges in the current band
+/// sorted by x. Edges that swap places within a band cross there, so the band is cut at their crossings into
+/// slabs where no edges cross. In each slab the sorted edges give the spans that are inside by `rule`,
+/// each drawn as a trapezoid.
+pub fn fill(lines: &[Polyline], rule: FillRule) -> Mesh {
+    let mut mesh = Mesh::new();
+    let mut edges = vec![];
+    for line in lines.iter().filter(|line| line.points.len() > 2) {
+        let points = &line.points;
+        for i in 0..points.len() {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            if a.y < b.y {
+                edges.push(Edge { top: a, bottom: b, winding: 1 });
+            } else if a.y > b.y {
+                edges.push(Edge { top: b, bottom: a, winding: -1 });
+            }
+        }
+    }
+    edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+
+    let mut ys: Vec<f32> = edges.iter().flat_map(|e| [e.top.y, e.bottom.y]).collect();
+    ys.sort_by(|a, b| a.total_cmp(b));
+    ys.dedup_by(|a, b| (*a - *b).abs() < 1e-4);
+
+    // The edges of the current band, sorted by x at its bottom once it is cut
+    let mut active: Vec<&Edge> = vec![];
+    let mut next = 0;
+    for band in ys.windows(2) {
+        let (top, bottom) = (band[0], band[1]);
+        let middle = (top + bottom) / 2.0;
+        while next < edges.len() && edges[next].top.y <= middle {
+            active.push(&edges[next]);
+            next += 1;
+        }
+        active.retain(|e| e.bottom.y >= middle);
+        active.sort_by(|a, b| a.x_at(top).total_cmp(&b.x_at(top)).then(a.x_at(bottom).total_cmp(&b.x_at(bottom))));
+
+        // Sorting by x at the bottom swaps every pair of edges that crosses inside the band once
+        let mut cuts = vec![top, bottom];
+        for i in 1..active.len() {
+            let mut j = i;
+            while j > 0 && active[j - 1].x_at(bottom) > active[j].x_at(bottom) {
+                cuts.extend(crossing(active[j - 1], active[j]).filter(|y| *y > top && *y < bottom));
+                active.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+        cuts.sort_by(|a, b| a.total_cmp(b));
+        cuts.dedup_by(|a, b| (*a - *b).abs() < 1e-4);
+
+        for slab in cuts.windows(2) {
+            fill_slab(&mut mesh, &active, slab[0], slab[1], rule);
+        }
+    }
+
+    for line in lines.iter().filter(|line| line.points.len() > 2) {
+        fringe(&mut mesh, line);
+    }
+    mesh
+}
+
+/// Draws the spans between `top` and `bottom` that are inside by `rule`, where none of the `edges` cross
+fn fill_slab(mesh: &mut Mesh, edges: &[&Edge], top: f32, bottom: f32, rule: FillRule) {
+    let middle = (top + bottom) / 2.0;
+    let mut sorted: Vec<(f32, &Edge)> = edges.iter().map(|e| (e.x_at(middle), *e)).collect();
+    // Already close to sorted, as the edges are sorted at the bottom of the band
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut winding = 0;
+    let mut left: Option<&Edge> = None;
+    for (_, edge) in sorted {
+        let was_inside = rule.inside(winding);
+        winding += edge.winding;
+        match (was_inside, rule.inside(winding)) {
+            (false, true) => left = Some(edge),
+            (true, false) => if let Some(left) = left.take() {
+                let (tl, tr) = (Vec2::new(left.x_at(top), top), Vec2::new(edge.x_at(top), top));
+                let (bl, br) = (Vec2::new(left.x_at(bottom), bottom), Vec2::new(edge.x_at(bottom), bottom));
+                mesh.triangle((tl, 1.0), (tr, 1.0), (br, 1.0));
+                mesh.triangle((tl, 1.0), (br, 1.0), (bl, 1.0));
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Fades the outline out over half a pixel outside of it, so the edge pixels are partially covered
+fn fringe(mesh: &mut Mesh, line: &Polyline) {
+    let points = &line.points;
+    let count = points.len();
+    let area: f32 = (0..count).map(|i| cross(points[i], points[(i + 1) % count])).sum();
+    // The left normal points inwards for counter-clockwise outlines with a positive area
+    let outwards = if area > 0.0 { -0.5 } else { 0.5 };
+    let normal = |a: Vec2<f32>, b: Vec2<f32>| scale(perp(normalize(b - a)), outwards);
+
+    for i in 0..count {
+        let (a, b, c) = (points[i], points[(i + 1) % count], points[(i + 2) % count]);
+        if length(b - a) == 0.0 {
+            continue;
+        }
+        let (n0, n1) = (normal(a, b), normal(b, c));
+        mesh.triangle((a, 0.5), (b, 0.5), (b + n0, 0.0));
+        mesh.triangle((a, 0.5), (b + n0, 0.0), (a + n0, 0.0));
+        // The gap around the corner to the next edge
+        mesh.triangle((b, 0.5), (b + n0, 0.0), (b + n1, 0.0));
+    }
+}
+
+#[test]
+pub fn filling() {
+    let square = |x: f32, y: f32, size: f32, clockwise: bool| {
+        let mut points = vec![Vec2::new(x, y), Vec2::new(x + size, y), Vec2::new(x + size, y + size), Vec2::new(x, y + size)];
+        if !clockwise {
+            points.reverse();
+        }
+        Polyline { points, closed: true }
+    };
+    // The area of everything fully covered, without the fringe
+    let area = |mesh: &Mesh| -> f32 {
+        mesh.vertices.chunks(3)
+            .filter(|t| t.iter().all(|v| v.across >= 1.0))
+            .map(|t| {
+                let (a, b, c) = (Vec2::new(t[0].x, t[0].y), Vec2::new(t[1].x, t[1].y), Vec2::new(t[2].x, t[2].y));
+                cross(b - a, c - a).abs() / 2.0
+            })
+            .sum()
+    };
+
+    assert!((area(&fill(&[square(0.0, 0.0, 10.0, true)], FillRule::NonZero)) - 100.0).abs() < 1e-3);
+
+    // Two overlapping squares going the same way, their overlap is a hole only by the even-odd rule
+    let overlapping = [square(0.0, 0.0, 10.0, true), square(5.0, 5.0, 10.0, true)];
+    assert!((area(&fill(&overlapping, FillRule::NonZero)) - 175.0).abs() < 1e-3);
+    assert!((area(&fill(&overlapping, FillRule::EvenOdd)) - 150.0).abs() < 1e-3);
+
+    // A square inside another going the other way is a hole by both rules
+    let hole = [square(0.0, 0.0, 10.0, true), square(2.0, 2.0, 6.0, false)];
+    assert!((area(&fill(&hole, FillRule::NonZero)) - 64.0).abs() < 1e-3);
+
+    // A self-intersecting bow tie
+    let bow_tie = Polyline { points: vec![Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0), Vec2::new(10.0, 0.0), Vec2::new(0.0, 10.0)], closed: true };
+    assert!((area(&fill(&[bow_tie], FillRule::EvenOdd)) - 50.0).abs() < 1e-3);
+
+    // A star whose edges cross each other between its vertices, with a filled center only by the non-zero rule
+    let star = Polyline {
+        points: (0..5).map(|i| {
+            let angle = std::f32::consts::PI * (0.5 + 0.8 * i as f32);
+            Vec2::new(10.0 * angle.cos(), 10.0 * angle.sin())
+        }).collect(),
+        closed: true,
+    };
+    let (non_zero, even_odd) = (area(&fill(std::slice::from_ref(&star), FillRule::NonZero)), area(&fill(&[star], FillRule::EvenOdd)));
+    // The pentagon in the center of a star with points on a circle of radius 10
+    let center = 5.0 / 2.0 * (10.0 * 0.381_966f32).powi(2) * (72f32.to_radians()).sin();
+    assert!(non_zero > even_odd && (non_zero - even_odd - center).abs() < 0.1);
+}
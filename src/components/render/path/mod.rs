@@ -0,0 +1,446 @@
+use std::f32::consts::PI;
+
+use gl::{DrawArrays, FALSE, FLOAT, TRIANGLES};
+
+use crate::components::context::context;
+use crate::components::render::color::Color;
+use crate::components::render::path::fill::{fill, FillRule};
+use crate::components::render::path::stroke::{stroke, Stroke};
+use crate::components::render::renderer::Renderable;
+use crate::components::render::stack::State::Blend;
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+use crate::components::wrapper::buffer::{Buffer, VertexArray};
+use crate::components::wrapper::shader::Shader;
+use crate::gl_binds::gl11::types::GLuint;
+use crate::gl_binds::gl20::ARRAY_BUFFER;
+
+pub mod stroke;
+pub mod fill;
+
+/// How far, in pixels, flattened curves may be from the real curve
+pub const TOLERANCE: f32 = 0.25;
+
+/// A single drawing command of a [`Path`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    /// Starts a new sub-path
+    MoveTo(Vec2<f32>),
+    LineTo(Vec2<f32>),
+    /// A quadratic bezier curve through a control point
+    QuadTo(Vec2<f32>, Vec2<f32>),
+    /// A cubic bezier curve through two control points
+    CubicTo(Vec2<f32>, Vec2<f32>, Vec2<f32>),
+    /// A circular arc from the `start` to the `end` angle in radians, clockwise on screen while `end > start`.
+    /// A line connects the current point to the start of the arc.
+    Arc { center: Vec2<f32>, radius: f32, start: f32, end: f32 },
+    /// Connects the current point to the start of the sub-path
+    Close,
+}
+
+/// An outline of lines, curves and arcs that can be stroked and filled.
+///
+/// ```ignore
+/// let path = Path::new()
+///     .move_to(0.0, 0.0)
+///     .line_to(100.0, 0.0)
+///     .quad_to(150.0, 50.0, 100.0, 100.0)
+///     .close();
+/// let mut shape = Shape::new(path.stroke(&Stroke::new(2.0).join(LineJoin::Round)), Color::from_u32(0xffffffff));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    commands: Vec<PathCommand>,
+}
+
+/// A sub-path flattened into straight lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<Vec2<f32>>,
+    pub closed: bool,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path::default()
+    }
+
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::MoveTo(Vec2::new(x, y)));
+        self
+    }
+
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::LineTo(Vec2::new(x, y)));
+        self
+    }
+
+    pub fn quad_to(mut self, cx: f32, cy: f32, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::QuadTo(Vec2::new(cx, cy), Vec2::new(x, y)));
+        self
+    }
+
+    pub fn cubic_to(mut self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::CubicTo(Vec2::new(c1x, c1y), Vec2::new(c2x, c2y), Vec2::new(x, y)));
+        self
+    }
+
+    /// A circular arc around `(cx, cy)` from the `start` to the `end` angle in radians
+    pub fn arc(mut self, cx: f32, cy: f32, radius: f32, start: f32, end: f32) -> Self {
+        self.commands.push(PathCommand::Arc { center: Vec2::new(cx, cy), radius, start, end });
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    /// Adds a closed rectangle sub-path
+    pub fn rect(self, bounds: Vec4) -> Self {
+        self.move_to(bounds.left(), bounds.top())
+            .line_to(bounds.right(), bounds.top())
+            .line_to(bounds.right(), bounds.bottom())
+            .line_to(bounds.left(), bounds.bottom())
+            .close()
+    }
+
+    /// Adds a closed circle sub-path
+    pub fn circle(self, cx: f32, cy: f32, radius: f32) -> Self {
+        self.move_to(cx + radius, cy).arc(cx, cy, radius, 0.0, 2.0 * PI).close()
+    }
+
+    pub fn push(&mut self, command: PathCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn commands(&self) -> &Vec<PathCommand> {
+        &self.commands
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Flattens every sub-path into lines no further than `tolerance` from the curves
+    pub fn flatten(&self, tolerance: f32) -> Vec<Polyline> {
+        let tolerance = tolerance.max(0.001);
+        let mut lines = vec![];
+        let mut points: Vec<Vec2<f32>> = vec![];
+
+        for command in &self.commands {
+            let last = points.last().copied();
+            match *command {
+                PathCommand::MoveTo(p) => {
+                    finish(&mut lines, &mut points, false);
+                    points.push(p);
+                }
+                PathCommand::LineTo(p) => points.push(p),
+                PathCommand::QuadTo(c, p) => {
+                    let p0 = last.unwrap_or(c);
+                    let n = quad_segments(p0, c, p, tolerance);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let (a, b) = (lerp(p0, c, t), lerp(c, p, t));
+                        points.push(lerp(a, b, t));
+                    }
+                }
+                PathCommand::CubicTo(c1, c2, p) => {
+                    let p0 = last.unwrap_or(c1);
+                    let n = cubic_segments(p0, c1, c2, p, tolerance);
+                    for i in 1..=n {
+                        points.push(cubic_point(p0, c1, c2, p, i as f32 / n as f32));
+                    }
+                }
+                PathCommand::Arc { center, radius, start, end } => {
+                    let n = arc_segments(radius, end - start, tolerance);
+                    for i in 0..=n {
+                        let angle = start + (end - start) * i as f32 / n as f32;
+                        points.push(center + (radius * angle.cos(), radius * angle.sin()));
+                    }
+                }
+                PathCommand::Close => {
+                    let start = points.first().copied();
+                    finish(&mut lines, &mut points, true);
+                    // Commands after a close continue from the start of the closed sub-path
+                    points.extend(start);
+                }
+            }
+        }
+        finish(&mut lines, &mut points, false);
+        lines
+    }
+
+    /// The geometry of the outline drawn with `stroke`
+    pub fn stroke(&self, stroke_style: &Stroke) -> Mesh {
+        stroke(&self.flatten(TOLERANCE), stroke_style)
+    }
+
+    /// The geometry of the area enclosed by the sub-paths, which are closed implicitly
+    pub fn fill(&self, rule: FillRule) -> Mesh {
+        fill(&self.flatten(TOLERANCE), rule)
+    }
+}
+
+/// Ends the current sub-path, dropping repeated points and sub-paths of a single point
+fn finish(lines: &mut Vec<Polyline>, points: &mut Vec<Vec2<f32>>, closed: bool) {
+    let mut line: Vec<Vec2<f32>> = Vec::with_capacity(points.len());
+    for p in points.drain(..) {
+        if line.last().is_none_or(|last| length(p - *last) > 1e-4) {
+            line.push(p);
+        }
+    }
+    if closed && line.len() > 1 && length(line[0] - line[line.len() - 1]) <= 1e-4 {
+        line.pop();
+    }
+    if line.len() > 1 {
+        lines.push(Polyline { points: line, closed });
+    }
+}
+
+/// How many lines a quadratic curve is flattened into, by Wang's formula
+pub fn quad_segments(p0: Vec2<f32>, c: Vec2<f32>, p1: Vec2<f32>, tolerance: f32) -> usize {
+    let dd = length(p0 - c - c + p1);
+    ((0.25 * dd / tolerance).sqrt().ceil() as usize).clamp(1, 100)
+}
+
+/// How many lines a cubic curve is flattened into, by Wang's formula
+pub fn cubic_segments(p0: Vec2<f32>, c1: Vec2<f32>, c2: Vec2<f32>, p1: Vec2<f32>, tolerance: f32) -> usize {
+    let dd = length(p0 - c1 - c1 + c2).max(length(c1 - c2 - c2 + p1));
+    ((0.75 * dd / tolerance).sqrt().ceil() as usize).clamp(1, 100)
+}
+
+/// How many lines an arc is flattened into so that the chords stay within `tolerance`
+pub fn arc_segments(radius: f32, sweep: f32, tolerance: f32) -> usize {
+    if radius <= tolerance {
+        return 1;
+    }
+    let step = 2.0 * (1.0 - tolerance / radius).acos();
+    ((sweep.abs() / step).ceil() as usize).clamp(1, 256)
+}
+
+fn cubic_point(p0: Vec2<f32>, c1: Vec2<f32>, c2: Vec2<f32>, p1: Vec2<f32>, t: f32) -> Vec2<f32> {
+    let (a, b, c) = (lerp(p0, c1, t), lerp(c1, c2, t), lerp(c2, p1, t));
+    let (d, e) = (lerp(a, b, t), lerp(b, c, t));
+    lerp(d, e, t)
+}
+
+fn lerp(a: Vec2<f32>, b: Vec2<f32>, t: f32) -> Vec2<f32> {
+    Vec2::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+fn scale(v: Vec2<f32>, s: f32) -> Vec2<f32> {
+    Vec2::new(v.x * s, v.y * s)
+}
+
+fn length(v: Vec2<f32>) -> f32 {
+    (v.x * v.x + v.y * v.y).sqrt()
+}
+
+fn normalize(v: Vec2<f32>) -> Vec2<f32> {
+    let l = length(v);
+    if l == 0.0 { v } else { scale(v, 1.0 / l) }
+}
+
+/// The direction rotated a quarter turn, to the left of it on screen
+fn perp(v: Vec2<f32>) -> Vec2<f32> {
+    Vec2::new(-v.y, v.x)
+}
+
+fn dot(a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+fn cross(a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// A vertex of tessellated geometry.
+///
+/// `across` and `along` fade out the edges: both are clamped to `0..1` and multiplied into the alpha,
+/// so a value falling from 1 to 0 over a pixel anti-aliases that edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct PathVertex {
+    pub x: f32,
+    pub y: f32,
+    pub across: f32,
+    pub along: f32,
+}
+
+/// Triangles produced by stroking or filling a [`Path`], ready to be uploaded into a [`Shape`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<PathVertex>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Mesh::default()
+    }
+
+    fn vertex(&mut self, p: Vec2<f32>, across: f32, along: f32) {
+        self.vertices.push(PathVertex { x: p.x, y: p.y, across, along });
+    }
+
+    /// Adds a triangle of `(point, across)` corners, all fully covered along
+    fn triangle(&mut self, a: (Vec2<f32>, f32), b: (Vec2<f32>, f32), c: (Vec2<f32>, f32)) {
+        for (p, across) in [a, b, c] {
+            self.vertex(p, across, 1.0);
+        }
+    }
+
+    pub fn append(&mut self, other: &Mesh) {
+        self.vertices.extend_from_slice(&other.vertices);
+    }
+
+    /// Moves every vertex by `offset`
+    pub fn translate(&mut self, offset: Vec2<f32>) {
+        for v in &mut self.vertices {
+            v.x += offset.x;
+            v.y += offset.y;
+        }
+    }
+
+    /// The number of triangles
+    pub fn len(&self) -> usize {
+        self.vertices.len() / 3
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// The smallest rectangle containing every vertex
+    pub fn bounds(&self) -> Vec4 {
+        let Some(first) = self.vertices.first() else {
+            return Vec4::xywh(0.0, 0.0, 0.0, 0.0);
+        };
+        let (mut left, mut top, mut right, mut bottom) = (first.x, first.y, first.x, first.y);
+        for v in &self.vertices {
+            left = left.min(v.x);
+            top = top.min(v.y);
+            right = right.max(v.x);
+            bottom = bottom.max(v.y);
+        }
+        Vec4::ltrb(left, top, right, bottom)
+    }
+}
+
+/// A [`Mesh`] uploaded to the GPU and drawn in a single color.
+///
+/// The mesh is only uploaded again after it was changed with [`Shape::set_mesh`].
+pub struct Shape {
+    mesh: Mesh,
+    color: Color,
+    vao: Option<VertexArray>,
+    changed: bool,
+}
+
+impl Shape {
+    pub fn new(mesh: Mesh, color: Color) -> Self {
+        Shape {
+            mesh,
+            color,
+            vao: None,
+            changed: true,
+        }
+    }
+
+    pub fn set_mesh(&mut self, mesh: Mesh) {
+        self.mesh = mesh;
+        self.changed = true;
+    }
+
+    pub fn mesh(&self) -> &Mesh {
+        &self.mesh
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    unsafe fn create_vao(mesh: &Mesh) -> VertexArray {
+        let shader = &mut context().renderer().path_shader;
+
+        let mut vao = VertexArray::new();
+        vao.bind();
+
+        let mut vertices = Buffer::new(ARRAY_BUFFER);
+        vertices.set_values(&mesh.vertices);
+        vertices.attribPointerStride(shader.get_attrib_location("vert") as GLuint, 4, FLOAT, FALSE, 0);
+        vao.add_buffer(vertices);
+
+        VertexArray::unbind();
+        vao
+    }
+}
+
+impl Renderable for Shape {
+    unsafe fn pre_render(&mut self) {
+        if self.changed {
+            if let Some(vao) = &mut self.vao {
+                vao.delete();
+            }
+            self.vao = Some(Self::create_vao(&self.mesh));
+            self.changed = false;
+        }
+    }
+
+    unsafe fn render(&self) {
+        let Some(vao) = &self.vao else {
+            return;
+        };
+        if self.mesh.is_empty() {
+            return;
+        }
+        let renderer = context().renderer();
+        renderer.stack().begin();
+        renderer.stack().push(Blend(true));
+
+        let shader = &renderer.path_shader;
+        shader.bind();
+        shader.u_put_float("u_color", self.color.rgba().to_vec());
+
+        vao.bind();
+        DrawArrays(TRIANGLES, 0, self.mesh.vertices.len() as i32);
+        VertexArray::unbind();
+
+        Shader::unbind();
+
+        renderer.stack().end();
+        context().framework().mark_layer_dirty(self.mesh.bounds());
+    }
+}
+
+#[test]
+pub fn flattening() {
+    let path = Path::new()
+        .move_to(0.0, 0.0)
+        .line_to(10.0, 0.0)
+        .line_to(10.0, 0.0)
+        .quad_to(20.0, 0.0, 20.0, 10.0)
+        .close()
+        .line_to(0.0, 10.0);
+    let lines = path.flatten(TOLERANCE);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].closed);
+    assert_eq!(lines[0].points[1], Vec2::new(10.0, 0.0));
+    assert_eq!(*lines[0].points.last().unwrap(), Vec2::new(20.0, 10.0));
+    assert_eq!(lines[1].points, vec![Vec2::new(0.0, 0.0), Vec2::new(0.0, 10.0)]);
+
+    // Straight curves need a single line, round ones more the larger they are
+    assert_eq!(quad_segments(Vec2::new(0.0, 0.0), Vec2::new(5.0, 0.0), Vec2::new(10.0, 0.0), TOLERANCE), 1);
+    let small = cubic_segments(Vec2::new(0.0, 0.0), Vec2::new(0.0, 10.0), Vec2::new(10.0, 10.0), Vec2::new(10.0, 0.0), TOLERANCE);
+    let large = cubic_segments(Vec2::new(0.0, 0.0), Vec2::new(0.0, 100.0), Vec2::new(100.0, 100.0), Vec2::new(100.0, 0.0), TOLERANCE);
+    assert!(small > 1 && large > small);
+
+    let circle = Path::new().circle(0.0, 0.0, 50.0).flatten(TOLERANCE);
+    assert!(circle[0].closed);
+    assert!(circle[0].points.iter().all(|p| (length(*p) - 50.0).abs() < 0.01));
+    assert_eq!(circle[0].points.len(), arc_segments(50.0, 2.0 * PI, TOLERANCE));
+}
@@ -0,0 +1,300 @@
+use std::f32::consts::PI;
+
+use crate::components::render::path::{arc_segments, cross, dot, length, normalize, perp, scale, Mesh, Polyline, TOLERANCE};
+use crate::components::spatial::vec2::Vec2;
+
+/// How the outer corner between two lines is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LineJoin {
+    /// Extends the edges until they meet, falling back to a bevel past the miter limit
+    #[default]
+    Miter,
+    Round,
+    /// Cuts the corner off straight
+    Bevel,
+}
+
+/// How the ends of open lines are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LineCap {
+    /// Ends exactly at the end point
+    #[default]
+    Butt,
+    /// Extends by half the width as a half circle
+    Round,
+    /// Extends by half the width as a rectangle
+    Square,
+}
+
+/// The style of the outline of a path
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// The longest a miter can be relative to the width before it is beveled
+    pub miter_limit: f32,
+    /// Alternating lengths of dashes and gaps, the line is solid while empty
+    pub dashes: Vec<f32>,
+    /// How far into the dash pattern the line starts
+    pub dash_offset: f32,
+}
+
+impl Stroke {
+    pub fn new(width: f32) -> Self {
+        Stroke {
+            width,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+            dashes: vec![],
+            dash_offset: 0.0,
+        }
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    /// Dashes the line with alternating dash and gap lengths, an odd number of lengths is repeated twice
+    pub fn dashes(mut self, dashes: Vec<f32>, offset: f32) -> Self {
+        self.dashes = dashes;
+        self.dash_offset = offset;
+        self
+    }
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Stroke::new(1.0)
+    }
+}
+
+/// Splits a line into the open pieces drawn by a dash pattern
+pub fn dash(line: &Polyline, dashes: &[f32], offset: f32) -> Vec<Polyline> {
+    let mut pattern: Vec<f32> = dashes.iter().map(|d| d.max(0.0)).collect();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_within(..);
+    }
+    let total: f32 = pattern.iter().sum();
+    if pattern.is_empty() || total <= 0.0 {
+        return vec![line.clone()];
+    }
+
+    // Find where in the pattern the line starts
+    let mut index = 0;
+    let mut remaining = offset.rem_euclid(total);
+    while remaining >= pattern[index] {
+        remaining -= pattern[index];
+        index = (index + 1) % pattern.len();
+    }
+    remaining = pattern[index] - remaining;
+
+    let mut points = line.points.clone();
+    if line.closed {
+        points.push(line.points[0]);
+    }
+    let mut pieces = vec![];
+    let mut current = if index % 2 == 0 { vec![points[0]] } else { vec![] };
+    for pair in points.windows(2) {
+        let (mut a, b) = (pair[0], pair[1]);
+        let mut segment = length(b - a);
+        while segment > remaining {
+            let split = a + scale(normalize(b - a), remaining);
+            if index % 2 == 0 {
+                current.push(split);
+                pieces.push(Polyline { points: std::mem::take(&mut current), closed: false });
+            } else {
+                current = vec![split];
+            }
+            segment -= remaining;
+            a = split;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+        remaining -= segment;
+        if index % 2 == 0 {
+            current.push(b);
+        }
+    }
+    if current.len() > 1 {
+        pieces.push(Polyline { points: current, closed: false });
+    }
+    pieces
+}
+
+/// The geometry of every line drawn with `stroke`
+pub fn stroke(lines: &[Polyline], stroke: &Stroke) -> Mesh {
+    let mut mesh = Mesh::new();
+    if stroke.width <= 0.0 {
+        return mesh;
+    }
+    for line in lines {
+        if stroke.dashes.is_empty() {
+            stroke_line(&mut mesh, line, stroke);
+        } else {
+            for piece in dash(line, &stroke.dashes, stroke.dash_offset) {
+                stroke_line(&mut mesh, &piece, stroke);
+            }
+        }
+    }
+    mesh
+}
+
+/// Adds the geometry of a single line.
+///
+/// Every edge is pushed out by half a pixel and fades to nothing there, lines thinner than a pixel end
+/// up with less coverage in the middle instead of getting thinner.
+fn stroke_line(mesh: &mut Mesh, line: &Polyline, stroke: &Stroke) {
+    let half = stroke.width / 2.0;
+    let outer = half + 0.5;
+    let mut points = line.points.clone();
+    let count = points.len();
+    if count < 2 {
+        return;
+    }
+    let closed = line.closed && count > 2;
+
+    // Caps reaching less than half a pixel past the end points pull them in, so the fade ends on time
+    if !closed && stroke.cap != LineCap::Round {
+        let reach = if stroke.cap == LineCap::Square { half } else { 0.0 };
+        if reach < 0.5 {
+            let pull = 0.5 - reach;
+            let start = (points[1] - points[0], length(points[1] - points[0]));
+            points[0] = points[0] + scale(normalize(start.0), pull.min(start.1 / 2.0));
+            let end = (points[count - 2] - points[count - 1], length(points[count - 2] - points[count - 1]));
+            points[count - 1] = points[count - 1] + scale(normalize(end.0), pull.min(end.1 / 2.0));
+        }
+    }
+
+    let segments = if closed { count } else { count - 1 };
+    for i in 0..segments {
+        let (a, b) = (points[i], points[(i + 1) % count]);
+        let n = scale(perp(normalize(b - a)), outer);
+        mesh.triangle((a + n, 0.0), (a, outer), (b, outer));
+        mesh.triangle((a + n, 0.0), (b, outer), (b + n, 0.0));
+        mesh.triangle((a, outer), (a - n, 0.0), (b - n, 0.0));
+        mesh.triangle((a, outer), (b - n, 0.0), (b, outer));
+    }
+
+    let joins = if closed { 0..count } else { 1..count - 1 };
+    for i in joins {
+        let previous = points[(i + count - 1) % count];
+        let (p, next) = (points[i], points[(i + 1) % count]);
+        join(mesh, p, normalize(p - previous), normalize(next - p), outer, stroke);
+    }
+
+    if !closed {
+        cap(mesh, points[0], normalize(points[0] - points[1]), half, outer, stroke.cap);
+        cap(mesh, points[count - 1], normalize(points[count - 1] - points[count - 2]), half, outer, stroke.cap);
+    }
+}
+
+/// Fills the gap on the outer side of the corner at `p` between lines going in `d0` and then `d1`
+fn join(mesh: &mut Mesh, p: Vec2<f32>, d0: Vec2<f32>, d1: Vec2<f32>, outer: f32, stroke: &Stroke) {
+    let turn = cross(d0, d1);
+    if turn.abs() < 1e-6 && dot(d0, d1) > 0.0 {
+        return;
+    }
+    // Lines turning towards their left normal open up on the right
+    let side = if turn > 0.0 { -1.0 } else { 1.0 };
+    let (n0, n1) = (scale(perp(d0), side), scale(perp(d1), side));
+    let (o0, o1) = (p + scale(n0, outer), p + scale(n1, outer));
+
+    match stroke.join {
+        LineJoin::Round => {
+            // A full reversal goes around the front of the line
+            let sweep = if turn.abs() < 1e-6 { -PI * side } else { cross(n0, n1).atan2(dot(n0, n1)) };
+            fan(mesh, p, n0, sweep, outer);
+        }
+        LineJoin::Miter => {
+            let m = normalize(n0 + n1);
+            let cos = dot(m, n0);
+            if cos > 1e-3 && 1.0 / cos <= stroke.miter_limit {
+                let tip = p + scale(m, outer / cos);
+                mesh.triangle((p, outer), (o0, 0.0), (tip, 0.0));
+                mesh.triangle((p, outer), (tip, 0.0), (o1, 0.0));
+            } else {
+                mesh.triangle((p, outer), (o0, 0.0), (o1, 0.0));
+            }
+        }
+        LineJoin::Bevel => mesh.triangle((p, outer), (o0, 0.0), (o1, 0.0)),
+    }
+}
+
+/// Adds the end of a line at `p` facing `d`
+fn cap(mesh: &mut Mesh, p: Vec2<f32>, d: Vec2<f32>, half: f32, outer: f32, cap: LineCap) {
+    let n = scale(perp(d), outer);
+    if cap == LineCap::Round {
+        fan(mesh, p, perp(d), -PI, outer);
+        return;
+    }
+
+    // A solid part up to half a pixel before the end of the cap, and a pixel fading out along the line
+    let reach = if cap == LineCap::Square { half } else { 0.0 };
+    let solid = (reach - 0.5).max(0.0);
+    let (start, end) = (p + scale(d, solid), p + scale(d, solid + 1.0));
+    if solid > 0.0 {
+        mesh.triangle((p + n, 0.0), (p, outer), (start, outer));
+        mesh.triangle((p + n, 0.0), (start, outer), (start + n, 0.0));
+        mesh.triangle((p, outer), (p - n, 0.0), (start - n, 0.0));
+        mesh.triangle((p, outer), (start - n, 0.0), (start, outer));
+    }
+    for (a, b) in [(start + n, start), (start, start - n)] {
+        let across = |q: Vec2<f32>| if q == start { outer } else { 0.0 };
+        let (c, d) = (b + (end - start), a + (end - start));
+        mesh.vertex(a, across(a), 1.0);
+        mesh.vertex(b, across(b), 1.0);
+        mesh.vertex(c, across(b), 0.0);
+        mesh.vertex(a, across(a), 1.0);
+        mesh.vertex(c, across(b), 0.0);
+        mesh.vertex(d, across(a), 0.0);
+    }
+}
+
+/// Adds a circular fan around `p` of radius `outer`, starting at the direction `from` and turning by `sweep` radians
+fn fan(mesh: &mut Mesh, p: Vec2<f32>, from: Vec2<f32>, sweep: f32, outer: f32) {
+    let start = from.y.atan2(from.x);
+    let n = arc_segments(outer, sweep, TOLERANCE);
+    let point = |i: usize| {
+        let angle = start + sweep * i as f32 / n as f32;
+        p + (outer * angle.cos(), outer * angle.sin())
+    };
+    for i in 0..n {
+        mesh.triangle((p, outer), (point(i), 0.0), (point(i + 1), 0.0));
+    }
+}
+
+#[test]
+pub fn stroking() {
+    let line = Polyline { points: vec![Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0)], closed: false };
+
+    // A 10 pixel wide line covers 9 pixels fully and fades over a pixel on every side
+    let mesh = stroke(std::slice::from_ref(&line), &Stroke::new(10.0));
+    let bounds = mesh.bounds();
+    assert_eq!((bounds.left(), bounds.top(), bounds.right(), bounds.bottom()), (-0.5, -5.5, 100.5, 5.5));
+    let square = stroke(std::slice::from_ref(&line), &Stroke::new(10.0).cap(LineCap::Square)).bounds();
+    assert_eq!((square.left(), square.right()), (-5.5, 105.5));
+
+    let pieces = dash(&line, &[30.0, 10.0], 20.0);
+    let lengths: Vec<f32> = pieces.iter().map(|p| length(p.points[1] - p.points[0])).collect();
+    assert_eq!(lengths, vec![10.0, 30.0, 30.0]);
+    assert_eq!(pieces[1].points[0], Vec2::new(20.0, 0.0));
+
+    // The outer side of a right angle gets a miter, and a bevel with a low limit
+    let corner = Polyline { points: vec![Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)], closed: false };
+    let bevel = stroke(std::slice::from_ref(&corner), &Stroke::new(2.0).miter_limit(1.0));
+    let miter = stroke(&[corner], &Stroke::new(2.0));
+    assert_eq!(bevel.len() + 1, miter.len());
+}
@@ -31,6 +31,8 @@ pub struct Renderer {
     pub texture_shader: Shader,
     pub mask_shader: Shader,
     pub circle_shader: Shader,
+    /// Draws tessellated [`Path`](crate::components::render::path::Path) geometry
+    pub path_shader: Shader,
     pub blend_shader: Shader,
     /// up, down
     pub blur_shaders: (Shader, Shader),
@@ -55,6 +57,7 @@ impl Renderer {
             texture_shader: Shader::new(shader_file("shaders/test_n/vertex.glsl"), shader_file("shaders/test_n/fragment.glsl")),
             mask_shader: Shader::new(shader_file("shaders/mask/vertex.glsl"), shader_file("shaders/mask/fragment.glsl")),
             circle_shader: Shader::new(shader_file("shaders/circle/vertex.glsl"), shader_file("shaders/circle/fragment.glsl")),
+            path_shader: Shader::new(shader_file("shaders/path/vertex.glsl"), shader_file("shaders/path/fragment.glsl")),
             blur_shaders: (
                 Shader::new(default_vert.clone(), shader_file("shaders/blur/blur_up.frag")),
                 Shader::new(default_vert.clone(), shader_file("shaders/blur/blur_down.frag")),
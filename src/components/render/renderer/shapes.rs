@@ -10,9 +10,10 @@ use crate::components::render::stack::State::{Blend, Texture2D};
 use crate::components::spatial::vec4::Vec4;
 use crate::components::wrapper::buffer::{Buffer, VertexArray};
 use crate::components::wrapper::shader::Shader;
+use crate::components::wrapper::texture::Texture;
 use crate::gl_binds::gl11::{Disable, DrawElements, Enable, CULL_FACE, DEPTH_TEST, TRIANGLES, UNSIGNED_INT};
 use crate::gl_binds::gl11::types::{GLsizei, GLuint};
-use crate::gl_binds::gl20::ELEMENT_ARRAY_BUFFER;
+use crate::gl_binds::gl20::{ActiveTexture, ELEMENT_ARRAY_BUFFER, TEXTURE0};
 use crate::gl_binds::gl30::{Begin, BindVertexArray, End, Vertex2f, FRAMEBUFFER_SRGB};
 use crate::gl_binds::gl41::DrawElementsInstanced;
 
@@ -20,6 +21,8 @@ pub struct Rect {
     bounds: Changing<Vec4>,
     color: (Color, Color, Color, Color),
     radius: f32,
+    /// The texture tinted by the colors and the region of it that is drawn
+    texture: Option<(Texture, Vec4)>,
     vao: VertexArray,
 }
 
@@ -60,6 +63,7 @@ impl Rect {
             bounds: Changing::new(vec4.clone()),
             color: colors.to_colors(),
             radius: 0.,
+            texture: None,
             vao: Self::create_vao(&vec4)
         }
     }
@@ -76,6 +80,12 @@ impl Rect {
         self.color = colors.to_colors();
     }
 
+    /// Fills the rect with the `uv` region of `texture`, given as left, top, right and bottom from 0 to 1,
+    /// multiplied by the colors
+    pub fn set_texture(&mut self, texture: Option<&Texture>, uv: Vec4) {
+        self.texture = texture.map(|texture| (texture.clone(), uv));
+    }
+
     pub(super) unsafe fn draw_rect(&self) {
         let renderer = context().renderer();
         let bounds = self.bounds.current();// + Vec4::ltrb(-0.5, -0.5, 0.5, 0.5); // correct for blending created by the shader
@@ -92,6 +102,16 @@ impl Rect {
         shader.u_put_float("u_color_lb", self.color.2.rgba().to_vec());
         shader.u_put_float("u_color_rb", self.color.3.rgba().to_vec());
         shader.u_put_float("u_random", vec![random()]);
+        match &self.texture {
+            Some((texture, uv)) => {
+                ActiveTexture(TEXTURE0);
+                texture.bind();
+                shader.u_put_int("u_texture", vec![0]);
+                shader.u_put_int("u_textured", vec![1]);
+                shader.u_put_float("u_uv", vec![uv.left(), uv.top(), uv.right(), uv.bottom()]);
+            }
+            None => shader.u_put_int("u_textured", vec![0]),
+        }
 
         self.vao.bind();
 
@@ -99,6 +119,9 @@ impl Rect {
         // DrawArraysInstanced(gl::TRIANGLES, 0, 6, 1);
 
         VertexArray::unbind();
+        if self.texture.is_some() {
+            Texture::unbind();
+        }
 
         Shader::unbind();
 
@@ -118,6 +141,10 @@ impl Rect {
     pub fn radius(&self) -> f32 {
         self.radius
     }
+
+    pub fn texture(&self) -> Option<&Texture> {
+        self.texture.as_ref().map(|(texture, _)| texture)
+    }
 }
 
 impl Renderable for Rect {
@@ -5,7 +5,8 @@ use gl::{BLEND, DEPTH, Disable, Enable, TEXTURE_2D};
 use crate::components::context::context;
 use crate::components::framework::layer::Layer;
 use crate::components::spatial::vec2::Vec2;
-use crate::gl_binds::gl11::{Scalef, Translatef};
+use crate::components::spatial::vec4::Vec4;
+use crate::gl_binds::gl11::{Scalef, Scissor, Translatef, SCISSOR_TEST};
 use crate::gl_binds::gl11::types::GLenum;
 
 unsafe fn enable_disable(state: GLenum, value: bool) {
@@ -15,6 +16,18 @@ unsafe fn enable_disable(state: GLenum, value: bool) {
     }
 }
 
+/// Limits drawing to `area`, which is in window pixels
+unsafe fn scissor(area: &Vec4) {
+    let height = context().window().height() as f32;
+    Enable(SCISSOR_TEST);
+    Scissor(
+        area.left().floor() as i32,
+        (height - area.bottom()).floor() as i32,
+        area.width().max(0.0).ceil() as i32,
+        area.height().max(0.0).ceil() as i32,
+    );
+}
+
 #[derive(Debug, Clone)]
 pub enum State {
     Depth(bool),
@@ -22,6 +35,9 @@ pub enum State {
     Blend(bool),
     Translate(f32, f32),
     Scale(f32, f32),
+    /// Only draws inside the area, which is translated and scaled like everything else and
+    /// is also limited to the area of any clip pushed before it
+    Clip(Vec4),
 }
 
 impl State {
@@ -32,12 +48,35 @@ impl State {
             State::Blend(v) => enable_disable(BLEND, v),
             State::Translate(x, y) => {
                 context().window().mouse.pos += (-x, -y);
-                context().renderer().stack().current_translate += (x, y);
+                let stack = context().renderer().stack();
+                stack.current_translate += (x, y);
+                stack.origin += (x * stack.scale.x, y * stack.scale.y);
                 Translatef(x, y, 0.0);
             },
             State::Scale(x, y) => {
+                context().renderer().stack().scale *= (x, y);
                 Scalef(x, y, 1.0);
             }
+            State::Clip(area) => {
+                let stack = context().renderer().stack();
+                let (origin, scale) = (stack.origin, stack.scale);
+                let mut area = Vec4::ltrb(
+                    origin.x + area.left() * scale.x,
+                    origin.y + area.top() * scale.y,
+                    origin.x + area.right() * scale.x,
+                    origin.y + area.bottom() * scale.y,
+                );
+                if let Some(parent) = stack.clips.last() {
+                    area = Vec4::ltrb(
+                        area.left().max(parent.left()),
+                        area.top().max(parent.top()),
+                        area.right().min(parent.right()),
+                        area.bottom().min(parent.bottom()),
+                    );
+                }
+                stack.clips.push(area);
+                scissor(&area);
+            }
         }
     }
 
@@ -48,12 +87,23 @@ impl State {
             State::Blend(v) => enable_disable(BLEND, !v),
             State::Translate(x, y) => {
                 context().window().mouse.pos += (x, y);
-                context().renderer().stack().current_translate += (-x, -y);
+                let stack = context().renderer().stack();
+                stack.current_translate += (-x, -y);
+                stack.origin += (-x * stack.scale.x, -y * stack.scale.y);
                 Translatef(-x, -y, 0.0);
             }
             State::Scale(x, y) => {
+                context().renderer().stack().scale /= (x, y);
                 Scalef(1.0/x, 1.0/y, 1.0);
             }
+            State::Clip(_) => {
+                let stack = context().renderer().stack();
+                stack.clips.pop();
+                match stack.clips.last() {
+                    None => Disable(SCISSOR_TEST),
+                    Some(parent) => scissor(parent),
+                }
+            }
         }
     }
 
@@ -79,6 +129,10 @@ impl State {
                 State::Scale(x2, y2) => x1 == x2 && y1 == y2,
                 _ => false,
             },
+            State::Clip(a1) => match other {
+                State::Clip(a2) => a1 == a2,
+                _ => false,
+            },
         }
     }
 
@@ -89,6 +143,7 @@ impl State {
             State::Blend(_) => 2,
             State::Translate(_, _) => 3,
             State::Scale(_, _) => 4,
+            State::Clip(_) => 5,
         }
     }
 }
@@ -130,6 +185,11 @@ pub struct Stack {
     markers: Vec<usize>,
     current: HashMap<u8, GlState>,
     current_translate: Vec2<f32>,
+    /// Where the translated origin is, and how much everything is scaled, in window pixels
+    origin: Vec2<f32>,
+    scale: Vec2<f32>,
+    /// The clipped areas, in window pixels
+    clips: Vec<Vec4>,
 }
 
 impl Stack {
@@ -139,6 +199,9 @@ impl Stack {
             markers: vec![],
             current: HashMap::new(),
             current_translate: Vec2::zero(),
+            origin: Vec2::zero(),
+            scale: Vec2::new(1.0, 1.0),
+            clips: vec![],
         }
     }
 
@@ -150,6 +213,7 @@ impl Stack {
         self.stack.clear();
         self.current.clear();
         self.markers.clear();
+        self.clips.clear();
     }
 
     pub fn begin(&mut self) {
@@ -0,0 +1,904 @@
+use std::collections::hash_map::DefaultHasher;
+use std::f32::consts::PI;
+use std::hash::{Hash, Hasher};
+
+use image::RgbaImage;
+
+use crate::components::framework::theme::parse_hex;
+use crate::components::render::color::Color;
+use crate::components::render::path::fill::FillRule;
+use crate::components::render::path::stroke::{LineCap, LineJoin, Stroke};
+use crate::components::render::path::{Mesh, Path, PathCommand, PathVertex};
+use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
+
+/// Elements whose contents are never drawn directly
+const SKIPPED: [&str; 12] = [
+    "defs", "style", "title", "desc", "metadata", "clipPath", "mask", "symbol", "pattern", "marker",
+    "linearGradient", "radialGradient",
+];
+
+/// An affine transform `[a, b, c, d, e, f]`, mapping `(x, y)` to `(a*x + c*y + e, b*x + d*y + f)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform(pub [f32; 6]);
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    pub fn translate(x: f32, y: f32) -> Self {
+        Transform([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Transform([x, 0.0, 0.0, y, 0.0, 0.0])
+    }
+
+    /// A rotation by `degrees`, clockwise on screen
+    pub fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Transform([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    /// The transform applying `other` first and then `self`
+    pub fn then(&self, other: &Transform) -> Transform {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        Transform([
+            a * a2 + c * b2,
+            b * a2 + d * b2,
+            a * c2 + c * d2,
+            b * c2 + d * d2,
+            a * e2 + c * f2 + e,
+            b * e2 + d * f2 + f,
+        ])
+    }
+
+    pub fn apply(&self, p: Vec2<f32>) -> Vec2<f32> {
+        let [a, b, c, d, e, f] = self.0;
+        Vec2::new(a * p.x + c * p.y + e, b * p.x + d * p.y + f)
+    }
+
+    /// How much the transform scales lengths on average, used for stroke widths
+    pub fn scale_factor(&self) -> f32 {
+        let [a, b, c, d, _, _] = self.0;
+        (a * d - b * c).abs().sqrt()
+    }
+}
+
+/// How the fill or stroke of an SVG shape is painted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Paint {
+    Color(Color),
+    /// The color given when tessellating or rasterizing, at an opacity
+    Current(f32),
+}
+
+impl Paint {
+    fn resolve(&self, current: Color) -> Color {
+        match self {
+            Paint::Color(color) => *color,
+            Paint::Current(opacity) => current.set_alpha_f32(current.alpha() * opacity),
+        }
+    }
+
+    fn with_opacity(self, opacity: f32) -> Paint {
+        match self {
+            Paint::Color(color) => Paint::Color(color.set_alpha_f32(color.alpha() * opacity)),
+            Paint::Current(alpha) => Paint::Current(alpha * opacity),
+        }
+    }
+}
+
+/// A filled and/or stroked path of an [`Svg`], in the coordinates of its view box
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgShape {
+    pub path: Path,
+    pub fill: Option<(Paint, FillRule)>,
+    pub stroke: Option<(Paint, Stroke)>,
+}
+
+/// A parsed SVG document, limited to what icons use.
+///
+/// Supported are `path`, `rect`, `circle`, `ellipse`, `line`, `polyline` and `polygon` elements inside
+/// nested `g` groups, their `transform`s, and `fill`, `stroke` and opacity attributes or `style` properties.
+/// Gradients, patterns, clipping, masks, text and `use` are not drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Svg {
+    /// The area of the drawing that is shown
+    view_box: Vec4,
+    shapes: Vec<SvgShape>,
+    /// A hash of the source, identifying rasterized textures of this SVG
+    key: u64,
+}
+
+/// The presentation attributes inherited from groups
+#[derive(Debug, Clone)]
+struct Inherited {
+    transform: Transform,
+    fill: Option<Paint>,
+    fill_rule: FillRule,
+    fill_opacity: f32,
+    stroke: Option<Paint>,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    cap: LineCap,
+    join: LineJoin,
+    miter_limit: f32,
+    dashes: Vec<f32>,
+    dash_offset: f32,
+    opacity: f32,
+}
+
+impl Default for Inherited {
+    fn default() -> Self {
+        Inherited {
+            transform: Transform::IDENTITY,
+            fill: Some(Paint::Color(Color::from_u32(0xff000000))),
+            fill_rule: FillRule::NonZero,
+            fill_opacity: 1.0,
+            stroke: None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            dashes: vec![],
+            dash_offset: 0.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Inherited {
+    /// Applies a presentation attribute, ignoring unknown ones and invalid values
+    fn set(&mut self, name: &str, value: &str) {
+        let value = value.trim();
+        let number = || numbers(value).first().copied();
+        match name {
+            "transform" => self.transform = self.transform.then(&parse_transform(value)),
+            "fill" => self.fill = parse_paint(value).unwrap_or(self.fill),
+            "stroke" => self.stroke = parse_paint(value).unwrap_or(self.stroke),
+            "fill-rule" => self.fill_rule = if value == "evenodd" { FillRule::EvenOdd } else { FillRule::NonZero },
+            "fill-opacity" => self.fill_opacity = number().unwrap_or(1.0),
+            "stroke-opacity" => self.stroke_opacity = number().unwrap_or(1.0),
+            "opacity" => self.opacity *= number().unwrap_or(1.0),
+            "stroke-width" => self.stroke_width = number().unwrap_or(1.0),
+            "stroke-miterlimit" => self.miter_limit = number().unwrap_or(4.0),
+            "stroke-dashoffset" => self.dash_offset = number().unwrap_or(0.0),
+            "stroke-dasharray" => self.dashes = if value == "none" { vec![] } else { numbers(value) },
+            "stroke-linecap" => {
+                self.cap = match value {
+                    "round" => LineCap::Round,
+                    "square" => LineCap::Square,
+                    _ => LineCap::Butt,
+                }
+            }
+            "stroke-linejoin" => {
+                self.join = match value {
+                    "round" => LineJoin::Round,
+                    "bevel" => LineJoin::Bevel,
+                    _ => LineJoin::Miter,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Applies the attributes of an element, with properties of its `style` attribute taking precedence
+    fn apply(&mut self, attributes: &[(String, String)]) {
+        for (name, value) in attributes {
+            self.set(name, value);
+        }
+        if let Some(style) = attribute(attributes, "style") {
+            for property in style.split(';') {
+                if let Some((name, value)) = property.split_once(':') {
+                    self.set(name.trim(), value);
+                }
+            }
+        }
+    }
+
+    fn shape(&self, path: Path) -> SvgShape {
+        let path = transform_path(&path, &self.transform);
+        let scale = self.transform.scale_factor();
+        let stroke = Stroke::new(self.stroke_width * scale)
+            .cap(self.cap)
+            .join(self.join)
+            .miter_limit(self.miter_limit)
+            .dashes(self.dashes.iter().map(|d| d * scale).collect(), self.dash_offset * scale);
+        SvgShape {
+            path,
+            fill: self.fill.map(|paint| (paint.with_opacity(self.fill_opacity * self.opacity), self.fill_rule)),
+            stroke: self.stroke
+                .filter(|_| self.stroke_width > 0.0)
+                .map(|paint| (paint.with_opacity(self.stroke_opacity * self.opacity), stroke)),
+        }
+    }
+}
+
+impl Svg {
+    /// Parses an SVG document
+    pub fn parse(source: &str) -> Result<Svg, String> {
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+
+        let mut view_box = None;
+        let mut shapes = vec![];
+        let mut stack: Vec<Inherited> = vec![];
+        let mut skipping = 0;
+
+        for tag in tags(source)? {
+            let Tag { name, attributes, closing, empty } = tag;
+            if closing {
+                if skipping > 0 {
+                    skipping -= 1;
+                } else {
+                    stack.pop();
+                }
+                continue;
+            }
+            if skipping > 0 || SKIPPED.contains(&name.as_str()) {
+                if !empty {
+                    skipping += 1;
+                }
+                continue;
+            }
+
+            let mut state = stack.last().cloned().unwrap_or_default();
+            state.apply(&attributes);
+            match name.as_str() {
+                "svg" if view_box.is_none() => {
+                    let size = |name| attribute(&attributes, name).and_then(|v| numbers(v).first().copied());
+                    view_box = match attribute(&attributes, "viewBox").map(numbers).as_deref() {
+                        Some([x, y, width, height]) => Some(Vec4::xywh(*x, *y, *width, *height)),
+                        _ => size("width").zip(size("height")).map(|(width, height)| Vec4::xywh(0.0, 0.0, width, height)),
+                    };
+                }
+                name => {
+                    if let Some(path) = element_path(name, &attributes)? {
+                        shapes.push(state.shape(path));
+                    }
+                }
+            }
+            if !empty {
+                stack.push(state);
+            }
+        }
+
+        let view_box = view_box.ok_or("SVG has neither a viewBox nor a width and height")?;
+        if view_box.width() <= 0.0 || view_box.height() <= 0.0 {
+            return Err("SVG has an empty viewBox".to_string());
+        }
+        Ok(Svg {
+            view_box,
+            shapes,
+            key: hasher.finish(),
+        })
+    }
+
+    /// The size of the view box
+    pub fn size(&self) -> Vec2<f32> {
+        self.view_box.wh()
+    }
+
+    pub fn shapes(&self) -> &Vec<SvgShape> {
+        &self.shapes
+    }
+
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    /// A copy with every paint in the current color, keeping only the opacity of the shapes
+    pub fn mask(&self) -> Svg {
+        let current = |paint: Paint| Paint::Current(match paint {
+            Paint::Color(color) => color.alpha(),
+            Paint::Current(opacity) => opacity,
+        });
+        let mut mask = self.clone();
+        for shape in &mut mask.shapes {
+            shape.fill = shape.fill.map(|(paint, rule)| (current(paint), rule));
+            shape.stroke = shape.stroke.take().map(|(paint, stroke)| (current(paint), stroke));
+        }
+        mask
+    }
+
+    /// Maps the view box into `size`, scaled evenly and centered
+    pub fn fit_transform(&self, size: Vec2<f32>) -> Transform {
+        let scale = (size.x / self.view_box.width()).min(size.y / self.view_box.height());
+        let offset = Vec2::new(
+            (size.x - self.view_box.width() * scale) / 2.0 - self.view_box.x() * scale,
+            (size.y - self.view_box.height() * scale) / 2.0 - self.view_box.y() * scale,
+        );
+        Transform::translate(offset.x, offset.y).then(&Transform::scale(scale, scale))
+    }
+
+    /// The geometry of every fill and stroke fitted into `size`, with `current` as the current color
+    pub fn tessellate(&self, size: Vec2<f32>, current: Color) -> Vec<(Mesh, Color)> {
+        let transform = self.fit_transform(size);
+        let scale = transform.scale_factor();
+        let mut meshes = vec![];
+        for shape in &self.shapes {
+            let path = transform_path(&shape.path, &transform);
+            if let Some((paint, rule)) = &shape.fill {
+                meshes.push((path.fill(*rule), paint.resolve(current)));
+            }
+            if let Some((paint, stroke)) = &shape.stroke {
+                let mut stroke = stroke.clone();
+                stroke.width *= scale;
+                stroke.dashes.iter_mut().for_each(|d| *d *= scale);
+                stroke.dash_offset *= scale;
+                meshes.push((path.stroke(&stroke), paint.resolve(current)));
+            }
+        }
+        meshes
+    }
+
+    /// Draws the SVG fitted into an image of `width` by `height` pixels, with `current` as the current color
+    pub fn rasterize(&self, width: u32, height: u32, current: Color) -> RgbaImage {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut pixels = vec![0.0f32; (width * height * 4) as usize];
+        for (mesh, color) in self.tessellate(Vec2::new(width as f32, height as f32), current) {
+            for triangle in mesh.vertices.chunks_exact(3) {
+                rasterize_triangle(&mut pixels, width, height, triangle, color);
+            }
+        }
+
+        // The pixels are blended premultiplied, textures are not
+        let bytes = pixels.chunks_exact(4).flat_map(|p| {
+            let alpha = p[3];
+            let straight = |c: f32| if alpha > 0.0 { (c / alpha * 255.0).round().clamp(0.0, 255.0) as u8 } else { 0 };
+            [straight(p[0]), straight(p[1]), straight(p[2]), (alpha * 255.0).round().clamp(0.0, 255.0) as u8]
+        }).collect();
+        RgbaImage::from_raw(width, height, bytes).expect("pixel buffer matches the image size")
+    }
+}
+
+/// Blends a triangle of tessellated geometry into premultiplied RGBA pixels.
+///
+/// Pixel centers exactly on an edge belong to only one of the two triangles sharing it, so shared edges
+/// of translucent geometry are not blended twice.
+fn rasterize_triangle(pixels: &mut [f32], width: u32, height: u32, triangle: &[PathVertex], color: Color) {
+    let (a, mut b, mut c) = (triangle[0], triangle[1], triangle[2]);
+    let edge = |a: &PathVertex, b: &PathVertex, x: f32, y: f32| (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x);
+    let mut area = edge(&a, &b, c.x, c.y);
+    if area == 0.0 {
+        return;
+    }
+    if area < 0.0 {
+        std::mem::swap(&mut b, &mut c);
+        area = -area;
+    }
+    let owns = |a: &PathVertex, b: &PathVertex| b.y > a.y || (b.y == a.y && b.x < a.x);
+    let owned = [owns(&b, &c), owns(&c, &a), owns(&a, &b)];
+
+    let left = a.x.min(b.x).min(c.x).floor().max(0.0) as u32;
+    let top = a.y.min(b.y).min(c.y).floor().max(0.0) as u32;
+    let right = (a.x.max(b.x).max(c.x).ceil().max(0.0) as u32).min(width);
+    let bottom = (a.y.max(b.y).max(c.y).ceil().max(0.0) as u32).min(height);
+    let [red, green, blue, alpha] = color.rgba();
+
+    for y in top..bottom {
+        for x in left..right {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let weights = [edge(&b, &c, px, py), edge(&c, &a, px, py), edge(&a, &b, px, py)];
+            if weights.iter().zip(owned).any(|(w, owned)| *w < 0.0 || (*w == 0.0 && !owned)) {
+                continue;
+            }
+            let [wa, wb, wc] = weights.map(|w| w / area);
+            let across = wa * a.across + wb * b.across + wc * c.across;
+            let along = wa * a.along + wb * b.along + wc * c.along;
+            let coverage = alpha * across.clamp(0.0, 1.0) * along.clamp(0.0, 1.0);
+
+            let pixel = &mut pixels[((y * width + x) * 4) as usize..][..4];
+            for (channel, value) in pixel.iter_mut().zip([red, green, blue, 1.0]) {
+                *channel = value * coverage + *channel * (1.0 - coverage);
+            }
+        }
+    }
+}
+
+/// An opening or closing tag of an XML document
+#[derive(Debug)]
+struct Tag {
+    name: String,
+    attributes: Vec<(String, String)>,
+    closing: bool,
+    /// If the tag closes itself, like `<path/>`
+    empty: bool,
+}
+
+/// Splits an XML document into its tags, skipping text, comments, declarations and CDATA
+fn tags(source: &str) -> Result<Vec<Tag>, String> {
+    let mut tags = vec![];
+    let mut rest = source;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        let skip_to = |rest: &str, end: &str| rest.find(end).map(|i| i + end.len()).ok_or(format!("Unclosed '{}'", rest.chars().take(10).collect::<String>()));
+        if rest.starts_with("<!--") {
+            rest = &rest[skip_to(rest, "-->")?..];
+            continue;
+        }
+        if rest.starts_with("<![CDATA[") {
+            rest = &rest[skip_to(rest, "]]>")?..];
+            continue;
+        }
+        if rest.starts_with("<?") || rest.starts_with("<!") {
+            rest = &rest[skip_to(rest, ">")?..];
+            continue;
+        }
+
+        let closing = rest.starts_with("</");
+        let mut chars = rest[if closing { 2 } else { 1 }..].char_indices().peekable();
+        let offset = if closing { 2 } else { 1 };
+        let name_end = chars.find(|(_, c)| c.is_whitespace() || *c == '>' || *c == '/').map(|(i, _)| i + offset).ok_or("Unclosed tag")?;
+        let name = rest[offset..name_end].to_string();
+        let mut attributes = vec![];
+        let mut body = &rest[name_end..];
+        loop {
+            body = body.trim_start();
+            if let Some(after) = body.strip_prefix("/>") {
+                tags.push(Tag { name, attributes, closing, empty: true });
+                body = after;
+                break;
+            }
+            if let Some(after) = body.strip_prefix('>') {
+                tags.push(Tag { name, attributes, closing, empty: false });
+                body = after;
+                break;
+            }
+            let (key, value) = body.split_once('=').ok_or(format!("Invalid attribute in <{}>", name))?;
+            let value = value.trim_start();
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'').ok_or(format!("Unquoted attribute in <{}>", name))?;
+            let end = value[1..].find(quote).ok_or(format!("Unclosed attribute in <{}>", name))?;
+            attributes.push((key.trim().to_string(), unescape(&value[1..end + 1])));
+            body = &value[end + 2..];
+        }
+        rest = body;
+    }
+    Ok(tags)
+}
+
+fn unescape(value: &str) -> String {
+    value.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+/// Parses a color, `none` is `Some(None)` and anything unsupported `None`
+fn parse_paint(value: &str) -> Option<Option<Paint>> {
+    let color = |r: f32, g: f32, b: f32| Some(Some(Paint::Color(Color::from_f32(r, g, b, 1.0))));
+    match value {
+        "none" | "transparent" => Some(None),
+        "currentColor" => Some(Some(Paint::Current(1.0))),
+        "black" => color(0.0, 0.0, 0.0),
+        "white" => color(1.0, 1.0, 1.0),
+        "red" => color(1.0, 0.0, 0.0),
+        "green" => color(0.0, 128.0 / 255.0, 0.0),
+        "blue" => color(0.0, 0.0, 1.0),
+        "gray" | "grey" => color(128.0 / 255.0, 128.0 / 255.0, 128.0 / 255.0),
+        _ if value.starts_with('#') => parse_hex(&value[1..]).map(|c| Some(Paint::Color(c))),
+        _ if value.starts_with("rgb") => {
+            let inner = value.split_once('(')?.1.trim_end_matches(')');
+            // Unlike the color channels, the alpha is between 0 and 1
+            let channels: Vec<f32> = inner.split(',').enumerate().map(|(i, c)| {
+                let c = c.trim();
+                match c.strip_suffix('%') {
+                    Some(percent) => percent.parse::<f32>().unwrap_or(0.0) / 100.0,
+                    None if i == 3 => c.parse::<f32>().unwrap_or(1.0),
+                    None => c.parse::<f32>().unwrap_or(0.0) / 255.0,
+                }
+            }).collect();
+            match channels[..] {
+                [r, g, b] => color(r, g, b),
+                [r, g, b, a] => Some(Some(Paint::Color(Color::from_f32(r, g, b, a)))),
+                _ => None,
+            }
+        }
+        // Gradients and other paint servers are not supported, and draw nothing
+        _ if value.starts_with("url(") => Some(None),
+        _ => None,
+    }
+}
+
+/// Parses a `transform` attribute, a list of `matrix`, `translate`, `scale`, `rotate`, `skewX` and `skewY`
+pub fn parse_transform(value: &str) -> Transform {
+    let mut transform = Transform::IDENTITY;
+    for part in value.split(')') {
+        let Some((name, arguments)) = part.split_once('(') else {
+            continue;
+        };
+        let n = numbers(arguments);
+        let next = match (name.trim().trim_start_matches(',').trim(), n.as_slice()) {
+            ("matrix", [a, b, c, d, e, f]) => Transform([*a, *b, *c, *d, *e, *f]),
+            ("translate", [x]) => Transform::translate(*x, 0.0),
+            ("translate", [x, y]) => Transform::translate(*x, *y),
+            ("scale", [s]) => Transform::scale(*s, *s),
+            ("scale", [x, y]) => Transform::scale(*x, *y),
+            ("rotate", [angle]) => Transform::rotate(*angle),
+            ("rotate", [angle, x, y]) => Transform::translate(*x, *y)
+                .then(&Transform::rotate(*angle))
+                .then(&Transform::translate(-x, -y)),
+            ("skewX", [angle]) => Transform([1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0]),
+            ("skewY", [angle]) => Transform([1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0]),
+            _ => continue,
+        };
+        transform = transform.then(&next);
+    }
+    transform
+}
+
+/// Every number in a list separated by whitespace and commas, allowing the compact forms of path data
+pub fn numbers(value: &str) -> Vec<f32> {
+    let mut scanner = Scanner { bytes: value.as_bytes(), pos: 0 };
+    let mut numbers = vec![];
+    while let Some(n) = scanner.number() {
+        numbers.push(n);
+    }
+    numbers
+}
+
+/// Reads numbers and commands out of path data like `M1.5.5-2e1,3z`
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn skip_separators(&mut self) {
+        while self.pos < self.bytes.len() && (self.bytes[self.pos].is_ascii_whitespace() || self.bytes[self.pos] == b',') {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_separators();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |s: &mut Self| {
+            let start = s.pos;
+            while s.pos < s.bytes.len() && s.bytes[s.pos].is_ascii_digit() {
+                s.pos += 1;
+            }
+            s.pos > start
+        };
+        if matches!(self.bytes.get(self.pos), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+        let mut valid = digits(self);
+        if self.bytes.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            valid |= digits(self);
+        }
+        if !valid {
+            self.pos = start;
+            return None;
+        }
+        if matches!(self.bytes.get(self.pos), Some(b'e' | b'E')) {
+            let mantissa = self.pos;
+            self.pos += 1;
+            if matches!(self.bytes.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                self.pos = mantissa;
+            }
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos]).ok()?.parse().ok()
+    }
+
+    /// An arc flag, which may be written without a separator before the next number
+    fn flag(&mut self) -> Option<bool> {
+        match self.peek()? {
+            b'0' => { self.pos += 1; Some(false) }
+            b'1' => { self.pos += 1; Some(true) }
+            _ => None,
+        }
+    }
+
+    fn point(&mut self) -> Option<Vec2<f32>> {
+        Some(Vec2::new(self.number()?, self.number()?))
+    }
+}
+
+/// The outline of a shape element, `None` for elements that are not shapes
+fn element_path(name: &str, attributes: &[(String, String)]) -> Result<Option<Path>, String> {
+    let number = |name: &str| attribute(attributes, name).and_then(|v| numbers(v).first().copied()).unwrap_or(0.0);
+    let path = match name {
+        "path" => parse_path(attribute(attributes, "d").unwrap_or(""))?,
+        "rect" => {
+            let (x, y, width, height) = (number("x"), number("y"), number("width"), number("height"));
+            if width <= 0.0 || height <= 0.0 {
+                return Ok(None);
+            }
+            let (rx, ry) = match (attribute(attributes, "rx").is_some(), attribute(attributes, "ry").is_some()) {
+                (true, false) => (number("rx"), number("rx")),
+                (false, true) => (number("ry"), number("ry")),
+                _ => (number("rx"), number("ry")),
+            };
+            rounded_rect(x, y, width, height, rx.min(width / 2.0), ry.min(height / 2.0))
+        }
+        "circle" => ellipse(number("cx"), number("cy"), number("r"), number("r")),
+        "ellipse" => ellipse(number("cx"), number("cy"), number("rx"), number("ry")),
+        "line" => Path::new().move_to(number("x1"), number("y1")).line_to(number("x2"), number("y2")),
+        "polyline" | "polygon" => {
+            let points = numbers(attribute(attributes, "points").unwrap_or(""));
+            let mut path = Path::new();
+            for (i, p) in points.chunks_exact(2).enumerate() {
+                path = if i == 0 { path.move_to(p[0], p[1]) } else { path.line_to(p[0], p[1]) };
+            }
+            if name == "polygon" { path.close() } else { path }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(path))
+}
+
+/// Parses SVG path data into a [`Path`], with arcs approximated by cubic curves
+pub fn parse_path(data: &str) -> Result<Path, String> {
+    let mut scanner = Scanner { bytes: data.as_bytes(), pos: 0 };
+    let mut path = Path::new();
+    let mut current = Vec2::zero();
+    let mut start = Vec2::zero();
+    // The second control point of the last curve, reflected by the smooth curve commands
+    let mut last_control: Option<(u8, Vec2<f32>)> = None;
+    let mut command = None;
+
+    while let Some(next) = scanner.peek() {
+        if next.is_ascii_alphabetic() {
+            scanner.pos += 1;
+            command = Some(next);
+        }
+        let c = command.ok_or("Path data has to start with a command")?;
+        let relative = c.is_ascii_lowercase();
+        let offset = if relative { current } else { Vec2::zero() };
+        let invalid = || format!("Invalid path data for '{}' at {}", c as char, scanner_pos(data, next));
+        let read = |s: &mut Scanner| s.point().map(|p| p + offset);
+
+        let control = match c.to_ascii_uppercase() {
+            b'M' => {
+                current = read(&mut scanner).ok_or_else(invalid)?;
+                start = current;
+                path.push(PathCommand::MoveTo(current));
+                // Further pairs after a move are lines
+                command = Some(if relative { b'l' } else { b'L' });
+                None
+            }
+            b'L' => {
+                current = read(&mut scanner).ok_or_else(invalid)?;
+                path.push(PathCommand::LineTo(current));
+                None
+            }
+            b'H' => {
+                let x = scanner.number().ok_or_else(invalid)?;
+                current = Vec2::new(if relative { current.x + x } else { x }, current.y);
+                path.push(PathCommand::LineTo(current));
+                None
+            }
+            b'V' => {
+                let y = scanner.number().ok_or_else(invalid)?;
+                current = Vec2::new(current.x, if relative { current.y + y } else { y });
+                path.push(PathCommand::LineTo(current));
+                None
+            }
+            b'C' | b'S' => {
+                let c1 = if c.eq_ignore_ascii_case(&b'S') {
+                    reflect(last_control, b'C', current)
+                } else {
+                    read(&mut scanner).ok_or_else(invalid)?
+                };
+                let c2 = read(&mut scanner).ok_or_else(invalid)?;
+                current = read(&mut scanner).ok_or_else(invalid)?;
+                path.push(PathCommand::CubicTo(c1, c2, current));
+                Some((b'C', c2))
+            }
+            b'Q' | b'T' => {
+                let control = if c.eq_ignore_ascii_case(&b'T') {
+                    reflect(last_control, b'Q', current)
+                } else {
+                    read(&mut scanner).ok_or_else(invalid)?
+                };
+                current = read(&mut scanner).ok_or_else(invalid)?;
+                path.push(PathCommand::QuadTo(control, current));
+                Some((b'Q', control))
+            }
+            b'A' => {
+                let (rx, ry) = (scanner.number().ok_or_else(invalid)?, scanner.number().ok_or_else(invalid)?);
+                let rotation = scanner.number().ok_or_else(invalid)?;
+                let (large, sweep) = (scanner.flag().ok_or_else(invalid)?, scanner.flag().ok_or_else(invalid)?);
+                let end = read(&mut scanner).ok_or_else(invalid)?;
+                for command in arc_cubics(current, end, rx, ry, rotation, large, sweep) {
+                    path.push(command);
+                }
+                current = end;
+                None
+            }
+            b'Z' => {
+                path.push(PathCommand::Close);
+                current = start;
+                None
+            }
+            _ => return Err(invalid()),
+        };
+        last_control = control;
+    }
+    Ok(path)
+}
+
+fn scanner_pos(data: &str, next: u8) -> String {
+    format!("'{}' in \"{}\"", next as char, data.chars().take(40).collect::<String>())
+}
+
+/// The first control point of a smooth curve, the last one reflected if the previous command was of `kind`
+fn reflect(last: Option<(u8, Vec2<f32>)>, kind: u8, current: Vec2<f32>) -> Vec2<f32> {
+    match last {
+        Some((last_kind, control)) if last_kind == kind => current + (current - control),
+        _ => current,
+    }
+}
+
+/// A rectangle with elliptical corners
+fn rounded_rect(x: f32, y: f32, width: f32, height: f32, rx: f32, ry: f32) -> Path {
+    if rx <= 0.0 || ry <= 0.0 {
+        return Path::new().rect(Vec4::xywh(x, y, width, height));
+    }
+    // How far along the tangents the control points of a quarter circle are
+    let k = 0.552_284_8;
+    let (kx, ky) = (rx * k, ry * k);
+    let (right, bottom) = (x + width, y + height);
+    Path::new()
+        .move_to(x + rx, y)
+        .line_to(right - rx, y)
+        .cubic_to(right - rx + kx, y, right, y + ry - ky, right, y + ry)
+        .line_to(right, bottom - ry)
+        .cubic_to(right, bottom - ry + ky, right - rx + kx, bottom, right - rx, bottom)
+        .line_to(x + rx, bottom)
+        .cubic_to(x + rx - kx, bottom, x, bottom - ry + ky, x, bottom - ry)
+        .line_to(x, y + ry)
+        .cubic_to(x, y + ry - ky, x + rx - kx, y, x + rx, y)
+        .close()
+}
+
+fn ellipse(cx: f32, cy: f32, rx: f32, ry: f32) -> Path {
+    if rx <= 0.0 || ry <= 0.0 {
+        return Path::new();
+    }
+    rounded_rect(cx - rx, cy - ry, rx * 2.0, ry * 2.0, rx, ry)
+}
+
+/// Cubic curves approximating an SVG elliptical arc, as described in the SVG implementation notes
+pub fn arc_cubics(from: Vec2<f32>, to: Vec2<f32>, rx: f32, ry: f32, rotation: f32, large: bool, sweep: bool) -> Vec<PathCommand> {
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 || from == to {
+        return vec![PathCommand::LineTo(to)];
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    // The midpoint between the ends, in the coordinates of the unrotated ellipse
+    let (dx, dy) = ((from.x - to.x) / 2.0, (from.y - to.y) / 2.0);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    // Radii too small to reach are scaled up
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = (rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1).max(0.0);
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut root = (numerator / denominator).sqrt();
+    if large == sweep {
+        root = -root;
+    }
+    let (cx1, cy1) = (root * rx * y1 / ry, -root * ry * x1 / rx);
+    let center = Vec2::new(
+        cos * cx1 - sin * cy1 + (from.x + to.x) / 2.0,
+        sin * cx1 + cos * cy1 + (from.y + to.y) / 2.0,
+    );
+
+    let angle = |ux: f32, uy: f32| uy.atan2(ux);
+    let start = angle((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut delta = angle((-x1 - cx1) / rx, (-y1 - cy1) / ry) - start;
+    if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    } else if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    }
+
+    // Quarter turns or less keep the cubic approximation close
+    let count = (delta.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+    let step = delta / count as f32;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    let point = |t: f32| {
+        let (x, y) = (rx * t.cos(), ry * t.sin());
+        Vec2::new(cos * x - sin * y + center.x, sin * x + cos * y + center.y)
+    };
+    let derivative = |t: f32| {
+        let (x, y) = (-rx * t.sin(), ry * t.cos());
+        Vec2::new(cos * x - sin * y, sin * x + cos * y)
+    };
+
+    (0..count).map(|i| {
+        let (a, b) = (start + step * i as f32, start + step * (i + 1) as f32);
+        let (pa, pb) = (point(a), point(b));
+        let (da, db) = (derivative(a), derivative(b));
+        let end = if i + 1 == count { to } else { pb };
+        PathCommand::CubicTo(
+            Vec2::new(pa.x + da.x * k, pa.y + da.y * k),
+            Vec2::new(pb.x - db.x * k, pb.y - db.y * k),
+            end,
+        )
+    }).collect()
+}
+
+/// Applies `transform` to every point of a path, which must not contain circular arcs
+/// unless the transform keeps circles round
+pub fn transform_path(path: &Path, transform: &Transform) -> Path {
+    let t = |p: Vec2<f32>| transform.apply(p);
+    let mut result = Path::new();
+    for command in path.commands() {
+        result.push(match *command {
+            PathCommand::MoveTo(p) => PathCommand::MoveTo(t(p)),
+            PathCommand::LineTo(p) => PathCommand::LineTo(t(p)),
+            PathCommand::QuadTo(c, p) => PathCommand::QuadTo(t(c), t(p)),
+            PathCommand::CubicTo(c1, c2, p) => PathCommand::CubicTo(t(c1), t(c2), t(p)),
+            PathCommand::Arc { center, radius, start, end } => {
+                let [a, b, ..] = transform.0;
+                PathCommand::Arc { center: t(center), radius: radius * transform.scale_factor(), start: start + b.atan2(a), end: end + b.atan2(a) }
+            }
+            PathCommand::Close => PathCommand::Close,
+        });
+    }
+    result
+}
+
+#[test]
+pub fn parsing() {
+    assert_eq!(numbers("1.5.5-2e1,3 -.5"), vec![1.5, 0.5, -20.0, 3.0, -0.5]);
+
+    let path = parse_path("M10 10h5v5l-5 0zm1,1 L2 2").unwrap();
+    assert_eq!(path.commands(), &vec![
+        PathCommand::MoveTo(Vec2::new(10.0, 10.0)),
+        PathCommand::LineTo(Vec2::new(15.0, 10.0)),
+        PathCommand::LineTo(Vec2::new(15.0, 15.0)),
+        PathCommand::LineTo(Vec2::new(10.0, 15.0)),
+        PathCommand::Close,
+        PathCommand::MoveTo(Vec2::new(11.0, 11.0)),
+        PathCommand::LineTo(Vec2::new(2.0, 2.0)),
+    ]);
+    assert!(parse_path("10 10").is_err());
+
+    // A half circle from the left to the right of (10, 10), with flags written without separators
+    let arc = parse_path("M0 10a10 10 0 01 20 0").unwrap();
+    let lines = arc.flatten(0.1);
+    assert!(lines[0].points.iter().all(|p| ((p.x - 10.0).powi(2) + (p.y - 10.0).powi(2)).sqrt() - 10.0 < 0.1));
+    assert!(lines[0].points.iter().any(|p| p.y < 1.0));
+
+    let svg = Svg::parse(r##"<?xml version="1.0"?>
+        <!-- An icon -->
+        <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+            <defs><rect width="10" height="10"/></defs>
+            <g fill="#ff0000" transform="translate(5 0)">
+                <rect width="5" height="10" style="opacity: 0.5"/>
+            </g>
+            <circle cx="5" cy="5" r="1" fill="none" stroke="currentColor"/>
+        </svg>
+    "##).unwrap();
+    assert_eq!(svg.size(), Vec2::new(10.0, 10.0));
+    assert_eq!(svg.shapes().len(), 2);
+    assert_eq!(svg.shapes()[0].fill.map(|f| f.0), Some(Paint::Color(Color::from_f32(1.0, 0.0, 0.0, 0.5))));
+    assert_eq!(svg.shapes()[1].fill, None);
+    assert!(Svg::parse("<svg><path d=\"M0 0\"/></svg>").is_err());
+    assert!(Svg::parse("<svg width=\"1\" height=\"1\"><!-- äöüäöü").is_err());
+
+    // The right half is red at half opacity, drawn at twice the size
+    let image = svg.rasterize(20, 20, Color::from_u32(0xff00ff00));
+    assert_eq!(image.get_pixel(2, 2).0, [0, 0, 0, 0]);
+    assert_eq!(image.get_pixel(17, 2).0, [255, 0, 0, 128]);
+    assert_eq!(image.get_pixel(8, 10).0[1], 255);
+}
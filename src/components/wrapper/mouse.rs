@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use glfw::{Action, MouseButton, WindowEvent};
 
 use crate::components::spatial::vec2::Vec2;
+use crate::components::spatial::vec4::Vec4;
 
 pub struct Mouse {
     pub(crate) pos: Vec2<f32>,
@@ -10,6 +11,7 @@ pub struct Mouse {
     click_pos: Vec2<f32>,
     pub(super) delta: Vec2<f32>,
     pub(super) pressed: HashSet<MouseButton>,
+    pub(crate) covered: bool,
 }
 
 impl Mouse {
@@ -20,6 +22,7 @@ impl Mouse {
             click_pos: Vec2::new(0.0, 0.0),
             delta: Vec2::new(0.0, 0.0),
             pressed: HashSet::new(),
+            covered: false,
         }
     }
 
@@ -51,6 +54,14 @@ impl Mouse {
     pub fn pos(&self) -> &Vec2<f32> {
         &self.pos
     }
+    /// If something above the elements handling the current event is under the mouse, such as a popup
+    pub fn covered(&self) -> bool {
+        self.covered
+    }
+    /// If the mouse is over `bounds`, and not over something covering them
+    pub fn hovers(&self, bounds: &Vec4) -> bool {
+        !self.covered && self.pos.intersects(bounds)
+    }
     pub fn is_pressed(&self, button: MouseButton) -> bool {
         self.pressed.contains(&button)
     }
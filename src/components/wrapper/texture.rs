@@ -60,4 +60,9 @@ impl Texture {
     pub unsafe fn unbind() {
         BindTexture(TEXTURE_2D, 0);
     }
+
+    pub unsafe fn delete(&mut self) {
+        DeleteTextures(1, &self.texture_id);
+        self.texture_id = 0;
+    }
 }
\ No newline at end of file